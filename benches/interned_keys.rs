@@ -0,0 +1,45 @@
+//! Compares the allocations of `parse` and `parse_interned` on an array of
+//! 10 000 objects sharing the keys `id` and `name`, and checks interning
+//! allocates less.
+//!
+//! Run with `cargo bench --bench interned_keys`.
+
+mod common;
+
+use std::time::Instant;
+
+use json_parser::{parse, parse_interned};
+
+const ELEMENTS: usize = 10_000;
+
+/// Prints the time and allocations of one call to `parse`, returning the
+/// allocations
+fn measure<T>(name: &str, parse: impl FnOnce() -> T) -> usize {
+    common::reset();
+    let start = Instant::now();
+    let value = std::hint::black_box(parse());
+    let elapsed = start.elapsed();
+    let allocations = common::allocations();
+    drop(value);
+
+    println!("{name}");
+    println!("  time:        {elapsed:?}");
+    println!("  allocations: {allocations}");
+    allocations
+}
+
+fn main() {
+    let elements: Vec<_> = (0..ELEMENTS)
+        .map(|i| format!(r#"{{"id": {i}, "name": "n{i}"}}"#))
+        .collect();
+    let input = format!("[{}]", elements.join(","));
+
+    let plain = measure("parse", || parse(&input).unwrap());
+    let interned = measure("parse_interned", || parse_interned(&input).unwrap());
+
+    // two fewer key allocations for every element
+    assert!(
+        interned + ELEMENTS < plain,
+        "parse_interned made {interned} allocations against parse's {plain}"
+    );
+}
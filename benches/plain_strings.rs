@@ -0,0 +1,37 @@
+//! Measures parsing a large document made of plain (escape-free) strings.
+//!
+//! Run with `cargo bench --bench plain_strings`.
+
+mod common;
+
+use std::time::Instant;
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let items: Vec<String> = (0..50_000)
+        .map(|i| format!(r#"{{"name":"user number {i}","city":"somewhere far away"}}"#))
+        .collect();
+    let input = format!("[{}]", items.join(","));
+
+    common::reset();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let value = json_parser::parse(&input).unwrap();
+        std::hint::black_box(value);
+    }
+    let elapsed = start.elapsed();
+
+    let mib = input.len() as f64 * f64::from(ITERATIONS) / (1024.0 * 1024.0);
+    println!("input size:       {} bytes", input.len());
+    println!("time per parse:   {:?}", elapsed / ITERATIONS);
+    println!("throughput:       {:.1} MiB/s", mib / elapsed.as_secs_f64());
+    println!(
+        "allocations:      {} per parse",
+        common::allocations() / ITERATIONS as usize
+    );
+    println!(
+        "allocated bytes:  {} per parse",
+        common::allocated_bytes() / ITERATIONS as usize
+    );
+}
@@ -0,0 +1,64 @@
+//! Compares parsing 1 000 small documents with `parse` against a single
+//! `Parser` that reuses its token buffer, and checks that once the buffer
+//! has grown the `Parser` never allocates it again.
+//!
+//! Run with `cargo bench --bench reuse_buffer`.
+
+mod common;
+
+use std::time::Instant;
+
+use json_parser::{parse, ParseError, Parser, Token, Tokenizer, Value};
+
+const DOCUMENT: &str = r#"{"id": 42, "ok": true, "tags": [1, 2, 3], "meta": null}"#;
+const ITERATIONS: u32 = 1_000;
+
+/// Prints the time and allocations of `ITERATIONS` calls to `parse`,
+/// returning the allocations
+fn measure(name: &str, mut parse: impl FnMut(&str) -> Result<Value, ParseError>) -> usize {
+    common::reset();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(parse(std::hint::black_box(DOCUMENT)).unwrap());
+    }
+    let elapsed = start.elapsed();
+    let allocations = common::allocations();
+
+    println!("{name}");
+    println!("  time per parse: {:?}", elapsed / ITERATIONS);
+    println!("  allocations:    {allocations} in {ITERATIONS} parses");
+    allocations
+}
+
+fn main() {
+    let plain = measure("parse", parse);
+    let mut parser = Parser::new();
+    // the first document grows the buffer to fit
+    parser.parse_reuse(DOCUMENT).unwrap();
+    let reused = measure("Parser::parse_reuse", |input| parser.parse_reuse(input));
+
+    // `parse` differs only in growing a new token buffer every time
+    let buffer = buffer_allocations();
+    println!("token buffer allocations per parse: {buffer}");
+    assert!(buffer > 0);
+    assert_eq!(
+        reused,
+        plain - buffer * ITERATIONS as usize,
+        "parse_reuse allocated a token buffer after warming up"
+    );
+}
+
+/// The allocations of pushing the tokens of `DOCUMENT` one by one onto an
+/// empty `Vec`, as `parse` does
+fn buffer_allocations() -> usize {
+    let mut tokenizer = Tokenizer::new();
+    let count = tokenizer.feed(DOCUMENT).unwrap().len() + tokenizer.finish().unwrap().len();
+
+    common::reset();
+    let mut buffer = Vec::new();
+    for _ in 0..count {
+        buffer.push(Token::Null);
+    }
+    std::hint::black_box(&mut buffer);
+    common::allocations()
+}
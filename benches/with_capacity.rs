@@ -0,0 +1,45 @@
+//! Compares pushing 10 000 elements into an empty array against one
+//! created with `Value::array_with_capacity`.
+//!
+//! Run with `cargo bench --bench with_capacity`.
+
+mod common;
+
+use std::time::Instant;
+
+use json_parser::Value;
+
+const ELEMENTS: usize = 10_000;
+const ITERATIONS: u32 = 1_000;
+
+fn fill(mut value: Value) -> Value {
+    if let Value::Array(array) = &mut value {
+        for i in 0..ELEMENTS {
+            array.push(Value::from(i));
+        }
+    }
+    value
+}
+
+fn measure(name: &str, make: impl Fn() -> Value) {
+    common::reset();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(fill(make()));
+    }
+    let elapsed = start.elapsed();
+
+    println!("{name}");
+    println!("  time per fill:  {:?}", elapsed / ITERATIONS);
+    println!(
+        "  allocations:    {} per fill",
+        common::allocations() / ITERATIONS as usize
+    );
+}
+
+fn main() {
+    measure("default array", || Value::Array(Vec::new()));
+    measure("pre-allocated array", || {
+        Value::array_with_capacity(ELEMENTS)
+    });
+}
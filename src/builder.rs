@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::Value;
+
+/// Builds a `Value::Object` one member at a time
+#[derive(Debug, Default)]
+pub struct ObjectBuilder {
+    object: HashMap<String, Value>,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `key` to `value`, replacing any earlier value for that key
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.object.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets `key` only when `value` is `Some`, leaving it out otherwise
+    pub fn set_if_some(self, key: impl Into<String>, value: Option<impl Into<Value>>) -> Self {
+        match value {
+            Some(value) => self.set(key, value),
+            None => self,
+        }
+    }
+
+    pub fn build(self) -> Value {
+        Value::Object(self.object)
+    }
+}
+
+/// Builds a `Value::Array` one element at a time
+#[derive(Debug, Default)]
+pub struct ArrayBuilder {
+    array: Vec<Value>,
+}
+
+impl ArrayBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(mut self, value: impl Into<Value>) -> Self {
+        self.array.push(value.into());
+        self
+    }
+
+    pub fn build(self) -> Value {
+        Value::Array(self.array)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ArrayBuilder, ObjectBuilder};
+    use crate::Value;
+
+    #[test]
+    fn object_builder_skips_none() {
+        let nickname: Option<Value> = None;
+        let mut tags = ArrayBuilder::new();
+        for tag in ["a", "b"] {
+            tags = tags.push(Value::String(tag.to_string()));
+        }
+
+        let value = ObjectBuilder::new()
+            .set("name", Value::String("Ada".to_string()))
+            .set_if_some("nickname", nickname)
+            .set_if_some("age", Some(Value::num(36.0)))
+            .set("tags", tags.build())
+            .build();
+
+        assert_eq!(
+            value,
+            Value::Object(HashMap::from([
+                ("name".to_string(), Value::String("Ada".to_string())),
+                ("age".to_string(), Value::num(36.0)),
+                (
+                    "tags".to_string(),
+                    Value::Array(vec![
+                        Value::String("a".to_string()),
+                        Value::String("b".to_string()),
+                    ])
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn set_replaces_existing_key() {
+        let value = ObjectBuilder::new()
+            .set("k", Value::Null)
+            .set("k", Value::Boolean(true))
+            .build();
+
+        assert_eq!(
+            value,
+            Value::Object(HashMap::from([("k".to_string(), Value::Boolean(true))]))
+        );
+    }
+
+    #[test]
+    fn empty_builders() {
+        assert_eq!(ObjectBuilder::new().build(), Value::Object(HashMap::new()));
+        assert_eq!(ArrayBuilder::new().build(), Value::Array(vec![]));
+    }
+}
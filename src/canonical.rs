@@ -0,0 +1,313 @@
+use std::fmt::Write;
+
+use crate::{NumberRepr, Value};
+
+impl Value {
+    /// Serializes the value following the JSON Canonicalization Scheme
+    /// (RFC 8785): no whitespace, object keys sorted by their UTF-16 code
+    /// units and numbers written the way ECMAScript's `Number.toString` does
+    ///
+    /// Two semantically identical values always produce the same bytes.
+    /// NaN and infinities have no JSON representation and are written as
+    /// `null`, as `JSON.stringify` does.
+    pub fn canonical_json(&self) -> String {
+        let mut output = String::new();
+        write_canonical(self, &mut output);
+        output
+    }
+
+    /// A deep copy with numbers normalized the way `canonical_json` writes
+    /// them, so equal numbers serialize the same whatever their form:
+    /// whole numbers become `Integer`, `-0` included, everything else
+    /// `Float`, and NaN and infinities become `null`
+    ///
+    /// Raw literals from `ParseConfig::preserve_raw_numbers` are converted
+    /// too, so `1.0e0` and `1` end up identical.
+    ///
+    /// Objects don't keep their key order, so there's nothing to sort in
+    /// the copy; every serializer in this crate writes keys sorted.
+    pub fn to_canonical(&self) -> Value {
+        let mut copy = self.clone();
+        copy.walk_mut(|value| {
+            if let Value::Number(num) = value {
+                *value = canonical_number(num);
+            }
+        });
+        copy
+    }
+}
+
+fn canonical_number(num: &NumberRepr) -> Value {
+    // exact for integers and raw integer literals beyond 2^53
+    if let Some(int) = num.as_i64() {
+        return Value::Number(NumberRepr::Integer(int));
+    }
+    let float = num.as_f64();
+    // whole floats in `i64` range; the upper bound, 2^63, is excluded
+    let int_range = i64::MIN as f64..-(i64::MIN as f64);
+    if !float.is_finite() {
+        Value::Null
+    } else if float.fract() == 0.0 && int_range.contains(&float) {
+        Value::Number(NumberRepr::Integer(float as i64))
+    } else {
+        Value::Number(NumberRepr::Float(float))
+    }
+}
+
+fn write_canonical(value: &Value, output: &mut String) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Boolean(b) => output.push_str(if *b { "true" } else { "false" }),
+        Value::Number(num) => write_number(num.as_f64(), output),
+        Value::String(s) => write_string(s, output),
+        Value::Array(array) => {
+            output.push('[');
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_canonical(value, output);
+            }
+            output.push(']');
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            output.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_string(key, output);
+                output.push(':');
+                write_canonical(value, output);
+            }
+            output.push('}');
+        }
+    }
+}
+
+/// ECMAScript `Number::toString` (ECMA-262 section 6.1.6.1.20)
+fn write_number(num: f64, output: &mut String) {
+    if !num.is_finite() {
+        output.push_str("null");
+        return;
+    }
+    // also covers -0
+    if num == 0.0 {
+        output.push('0');
+        return;
+    }
+    if num < 0.0 {
+        output.push('-');
+    }
+
+    // `{:e}` gives the shortest digits that round-trip, e.g. `1.2345e6`
+    let formatted = format!("{:e}", num.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    // the value is `digits` * 10^(n - k)
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    if k <= n && n <= 21 {
+        output.push_str(&digits);
+        output.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        let (int_part, frac_part) = digits.split_at(n as usize);
+        output.push_str(int_part);
+        output.push('.');
+        output.push_str(frac_part);
+    } else if -6 < n && n <= 0 {
+        output.push_str("0.");
+        output.extend(std::iter::repeat_n('0', -n as usize));
+        output.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        output.push_str(first);
+        if !rest.is_empty() {
+            output.push('.');
+            output.push_str(rest);
+        }
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        write!(output, "e{sign}{}", (n - 1).abs()).unwrap();
+    }
+}
+
+fn write_string(s: &str, output: &mut String) {
+    output.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c < '\u{20}' => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{json, parse, parse_with_config, to_string, NumberRepr, ParseConfig, Value};
+
+    fn check_number(num: f64, expected: &str) {
+        assert_eq!(Value::num(num).canonical_json(), expected);
+    }
+
+    #[test]
+    fn key_order_does_not_matter() {
+        let a = parse(r#"{"b":1,"a":2}"#).unwrap();
+        let b = parse(r#"{ "a" : 2, "b" : 1 }"#).unwrap();
+
+        assert_eq!(a.canonical_json(), r#"{"a":2,"b":1}"#);
+        assert_eq!(a.canonical_json(), b.canonical_json());
+    }
+
+    #[test]
+    fn escaped_keys_match_their_plain_form() {
+        let escaped = parse(r#"{"\u0061": 1, "\u00e9\n": {"\ud83d\ude00": 2}}"#).unwrap();
+        let plain = parse("{\"a\": 1, \"é\\n\": {\"\u{1f600}\": 2}}").unwrap();
+
+        assert_eq!(escaped.canonical_json(), plain.canonical_json());
+        assert_eq!(
+            escaped.canonical_json(),
+            "{\"a\":1,\"é\\n\":{\"\u{1f600}\":2}}"
+        );
+    }
+
+    #[test]
+    fn nested_values_have_no_whitespace() {
+        let value = parse(r#"{ "z": [true, null, {"y": "x", "a": []}] }"#).unwrap();
+
+        assert_eq!(
+            value.canonical_json(),
+            r#"{"z":[true,null,{"a":[],"y":"x"}]}"#
+        );
+    }
+
+    #[test]
+    fn keys_sorted_by_utf16_code_units() {
+        // U+1F600 is a surrogate pair starting 0xD83D, which sorts before U+FB01
+        let value = Value::Object(HashMap::from([
+            ("\u{fb01}".to_string(), Value::Null),
+            ("\u{1f600}".to_string(), Value::Null),
+            ("a".to_string(), Value::Null),
+        ]));
+
+        assert_eq!(
+            value.canonical_json(),
+            "{\"a\":null,\"\u{1f600}\":null,\"\u{fb01}\":null}"
+        );
+    }
+
+    #[test]
+    fn integers_have_no_fraction() {
+        check_number(0.0, "0");
+        check_number(-0.0, "0");
+        check_number(3.0, "3");
+        check_number(-42.0, "-42");
+        check_number(1e20, "100000000000000000000");
+    }
+
+    #[test]
+    fn fractions_use_shortest_digits() {
+        check_number(0.1, "0.1");
+        check_number(123.456, "123.456");
+        check_number(0.000001, "0.000001");
+        check_number(4.5, "4.5");
+    }
+
+    #[test]
+    fn large_and_small_numbers_use_exponents() {
+        check_number(1e21, "1e+21");
+        check_number(1.5e300, "1.5e+300");
+        check_number(1e-7, "1e-7");
+        check_number(-2.5e-10, "-2.5e-10");
+        check_number(5e-324, "5e-324");
+    }
+
+    #[test]
+    fn non_finite_numbers_become_null() {
+        check_number(f64::NAN, "null");
+        check_number(f64::INFINITY, "null");
+    }
+
+    #[test]
+    fn strings_escape_only_what_is_required() {
+        let value = Value::String("é\"\\\n\u{1f}/".to_string());
+
+        assert_eq!(value.canonical_json(), "\"é\\\"\\\\\\n\\u001f/\"");
+    }
+
+    #[test]
+    fn to_canonical_ignores_original_key_order() {
+        let a = parse(r#"{"z": [-0, 1.0], "a": {"y": 2, "b": 1e2}}"#).unwrap();
+        let b = parse(r#"{"a": {"b": 100, "y": 2}, "z": [0, 1]}"#).unwrap();
+
+        let (a, b) = (a.to_canonical(), b.to_canonical());
+        assert_eq!(a, b);
+        assert_eq!(to_string(&a).unwrap(), to_string(&b).unwrap());
+        assert_eq!(to_string(&a).unwrap(), r#"{"a":{"b":100,"y":2},"z":[0,1]}"#);
+    }
+
+    #[test]
+    fn to_canonical_leaves_original_untouched() {
+        let original = json!([-0.0, f64::NAN, {"k": f64::INFINITY}]);
+        let canonical = original.to_canonical();
+
+        assert_eq!(canonical, json!([0, null, {"k": null}]));
+        assert_eq!(to_string(&canonical).unwrap(), r#"[0,null,{"k":null}]"#);
+        let Value::Array(items) = &original else {
+            panic!("expected an array");
+        };
+        assert!(matches!(&items[0], Value::Number(n) if n.as_f64().is_sign_negative()));
+        assert!(matches!(&items[1], Value::Number(n) if n.as_f64().is_nan()));
+    }
+
+    #[test]
+    fn to_canonical_normalizes_raw_numbers() {
+        let config = ParseConfig {
+            preserve_raw_numbers: true,
+            ..ParseConfig::default()
+        };
+        let canonical = |input| {
+            let value = parse_with_config(input, &config).unwrap().to_canonical();
+            to_string(&value).unwrap()
+        };
+
+        assert_eq!(canonical(r#"{"a":1.0e0}"#), canonical(r#"{"a":1}"#));
+        assert_eq!(canonical(r#"{"a":1.0e0}"#), r#"{"a":1}"#);
+        assert_eq!(canonical("[2.50, -0.0, 1E2]"), canonical("[2.5, 0, 100]"));
+        assert_eq!(canonical("[2.50, -0.0, 1E2]"), "[2.5,0,100]");
+        assert_eq!(canonical("9007199254740993"), "9007199254740993");
+        assert_eq!(canonical("1e400"), "null");
+    }
+
+    #[test]
+    fn to_canonical_makes_whole_floats_integers() {
+        let value = json!([1.0, 1.5, -3e3, 1e19]).to_canonical();
+        let Value::Array(items) = value else {
+            panic!("expected an array");
+        };
+
+        assert!(matches!(items[0], Value::Number(NumberRepr::Integer(1))));
+        assert!(matches!(items[1], Value::Number(NumberRepr::Float(f)) if f == 1.5));
+        assert!(matches!(
+            items[2],
+            Value::Number(NumberRepr::Integer(-3000))
+        ));
+        // beyond `i64`, so it has to stay a float
+        assert!(matches!(items[3], Value::Number(NumberRepr::Float(_))));
+    }
+}
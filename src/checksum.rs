@@ -0,0 +1,146 @@
+//! A content hash of a document that doesn't depend on key order
+
+use crate::Value;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Value {
+    /// A 64-bit hash of the document for change detection and caching
+    ///
+    /// Equal values always hash the same, whatever order their objects'
+    /// members were inserted in: member hashes are combined with a
+    /// commutative sum. Numbers hash by the bit pattern of their `f64`
+    /// value, so `1` and `1.0` agree. The hash is FNV-1a over a fixed
+    /// encoding and doesn't depend on the process, the platform or the
+    /// standard library's `Hasher`, so it's safe to persist.
+    pub fn stable_hash(&self) -> u64 {
+        let mut hasher = Fnv(FNV_OFFSET);
+        hash_value(self, &mut hasher);
+        hasher.0
+    }
+}
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    /// Strings are length-prefixed so `["ab", "c"]` and `["a", "bc"]`
+    /// differ
+    fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+}
+
+fn hash_value(value: &Value, hasher: &mut Fnv) {
+    match value {
+        Value::Null => hasher.write(&[0]),
+        Value::Boolean(b) => hasher.write(&[1, u8::from(*b)]),
+        Value::Number(num) => {
+            let float = num.as_f64();
+            // `-0.0 == 0.0` and every NaN equals every other, so each
+            // group must hash alike
+            let float = if float == 0.0 {
+                0.0
+            } else if float.is_nan() {
+                f64::NAN
+            } else {
+                float
+            };
+            hasher.write(&[2]);
+            hasher.write_u64(float.to_bits());
+        }
+        Value::String(s) => {
+            hasher.write(&[3]);
+            hasher.write_str(s);
+        }
+        Value::Array(array) => {
+            hasher.write(&[4]);
+            hasher.write_u64(array.len() as u64);
+            for element in array {
+                hash_value(element, hasher);
+            }
+        }
+        Value::Object(object) => {
+            let members = object
+                .iter()
+                .map(|(key, value)| {
+                    let mut member = Fnv(FNV_OFFSET);
+                    member.write_str(key);
+                    hash_value(value, &mut member);
+                    member.0
+                })
+                .fold(0u64, u64::wrapping_add);
+            hasher.write(&[5]);
+            hasher.write_u64(object.len() as u64);
+            hasher.write_u64(members);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{json, parse};
+
+    #[test]
+    fn key_order_does_not_matter() {
+        let a = parse(r#"{"a": 1, "b": {"x": [1, 2], "y": null}, "c": "s"}"#).unwrap();
+        let b = parse(r#"{"c": "s", "b": {"y": null, "x": [1, 2]}, "a": 1}"#).unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(a.stable_hash(), b.stable_hash());
+    }
+
+    #[test]
+    fn equal_numbers_hash_alike() {
+        assert_eq!(json!(1).stable_hash(), json!(1.0).stable_hash());
+        assert_eq!(json!(0.0).stable_hash(), json!(-0.0).stable_hash());
+        assert_eq!(
+            json!(f64::NAN).stable_hash(),
+            json!(-f64::NAN).stable_hash()
+        );
+    }
+
+    #[test]
+    fn different_documents_hash_differently() {
+        let hashes = [
+            json!(null),
+            json!(false),
+            json!(true),
+            json!(0),
+            json!(""),
+            json!([]),
+            json!({}),
+            json!(["ab", "c"]),
+            json!(["a", "bc"]),
+            json!([[1], 2]),
+            json!([1, [2]]),
+            json!({"a": 1, "b": 2}),
+            json!({"a": 2, "b": 1}),
+        ]
+        .map(|value| value.stable_hash());
+
+        for (i, a) in hashes.iter().enumerate() {
+            for b in &hashes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn hash_is_fixed_across_runs() {
+        // FNV-1a of the type tag alone
+        assert_eq!(json!(null).stable_hash(), 0xaf63_bd4c_8601_b7df);
+    }
+}
@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::{NumberRepr, Value};
+
+/// `==` and `cmp` share one total order on numbers in which NaN equals
+/// NaN, so equality is reflexive for every document
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Values of different variants order as `null < boolean < number <
+/// string < array < object`. Within a variant, numbers compare by value
+/// with NaN after everything else and `-0 == 0`, strings and arrays
+/// compare lexicographically, and objects compare as their key/value
+/// pairs sorted by key.
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => compare_numbers(a, b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => a.cmp(b),
+            (Value::Object(a), Value::Object(b)) => {
+                let a: BTreeMap<_, _> = a.iter().collect();
+                let b: BTreeMap<_, _> = b.iter().collect();
+                a.cmp(&b)
+            }
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl Value {
+    /// Structural equality, the equivalence `cmp` uses
+    ///
+    /// Objects are equal when they have the same keys with deep-equal
+    /// values, in whatever order they were written, and arrays when their
+    /// elements are pairwise deep-equal. Numbers compare by value, so
+    /// `1 == 1.0` and `-0 == 0`, and `NaN` equals `NaN`. This is the
+    /// relation `==` implements, spelled out for readers who'd otherwise
+    /// have to check how `HashMap` compares.
+    pub fn deep_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => compare_numbers(a, b) == Ordering::Equal,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.deep_eq(b))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.deep_eq(b)))
+            }
+            _ => false,
+        }
+    }
+}
+
+fn rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Boolean(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+fn compare_numbers(a: &NumberRepr, b: &NumberRepr) -> Ordering {
+    a.total_cmp(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::{json, parse, Value};
+
+    #[test]
+    fn sorts_mixed_array() {
+        let mut values = vec![
+            json!({"b": 1}),
+            json!("b"),
+            json!([2]),
+            Value::num(f64::NAN),
+            json!(10),
+            json!(true),
+            json!({"a": 2}),
+            json!(null),
+            json!("a"),
+            json!([1, 5]),
+            json!(-1.5),
+            json!(false),
+            json!([1]),
+        ];
+        values.sort_unstable();
+
+        let expected = [
+            "null",
+            "false",
+            "true",
+            "-1.5",
+            "10",
+            "NaN",
+            "\"a\"",
+            "\"b\"",
+            "[1]",
+            "[1,5]",
+            "[2]",
+            "{\"a\":2}",
+            "{\"b\":1}",
+        ];
+        let actual: Vec<String> = values
+            .iter()
+            .map(|v| match v {
+                Value::Number(n) if n.as_f64().is_nan() => "NaN".to_string(),
+                v => v.to_string(),
+            })
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn objects_compare_by_sorted_entries() {
+        assert!(json!({"a": 1, "b": 2}) < json!({"a": 1, "c": 0}));
+        assert!(json!({"a": 1}) < json!({"a": 1, "b": 0}));
+        assert!(json!({"a": 2}) > json!({"a": 1, "z": 0}));
+    }
+
+    #[test]
+    fn numbers_order_by_value() {
+        assert_eq!(json!(-0.0).cmp(&json!(0)), Ordering::Equal);
+        assert!(json!(2) < json!(10));
+        assert!(Value::num(f64::INFINITY) < Value::num(f64::NAN));
+        assert_eq!(
+            Value::num(f64::NAN).cmp(&Value::num(f64::NAN)),
+            Ordering::Equal
+        );
+        assert!(json!(1e300) < json!("0"));
+    }
+
+    #[test]
+    fn deep_eq_ignores_key_order() {
+        let a = parse(r#"{"a": 1, "b": {"x": [1, {"p": null, "q": true}], "y": "s"}}"#).unwrap();
+        let b = parse(r#"{"b": {"y": "s", "x": [1, {"q": true, "p": null}]}, "a": 1.0}"#).unwrap();
+
+        assert!(a.deep_eq(&b));
+        assert!(b.deep_eq(&a));
+    }
+
+    #[test]
+    fn deep_eq_differences() {
+        assert!(!json!({"a": 1}).deep_eq(&json!({"a": 1, "b": 2})));
+        assert!(!json!({"a": 1}).deep_eq(&json!({"b": 1})));
+        assert!(!json!([1, 2]).deep_eq(&json!([2, 1])));
+        assert!(!json!([1]).deep_eq(&json!([1, 1])));
+        assert!(!json!(null).deep_eq(&json!(false)));
+        assert!(!json!("1").deep_eq(&json!(1)));
+    }
+
+    #[test]
+    fn deep_eq_treats_nan_as_equal() {
+        let nan = json!({"n": [Value::num(f64::NAN)]});
+
+        assert_eq!(nan, nan.clone());
+        assert!(nan.deep_eq(&nan.clone()));
+        assert!(json!(-0.0).deep_eq(&json!(0)));
+    }
+}
@@ -0,0 +1,566 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{NumberRepr, Value};
+
+/// The reason a `Value` couldn't be converted into a Rust type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The value was a different variant than the target type needs
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    /// An integer type was requested but the number has a fractional part
+    NotAnInteger,
+
+    /// The number is whole but doesn't fit in the requested integer type
+    OutOfRange { target: &'static str },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongType { expected, found } => write!(f, "expected {expected}, found {found}"),
+            Self::NotAnInteger => f.write_str("expected an integer, found a fractional number"),
+            Self::OutOfRange { target } => write!(f, "number out of range for {target}"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Why a member couldn't be read from an object with `Value::try_get` or
+/// `Value::try_get_as`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessError {
+    /// The value isn't an object, so it has no members
+    NotAnObject,
+
+    /// The object has no member with this key
+    MissingKey(String),
+
+    /// The member exists but couldn't be converted to the requested type
+    Conversion { key: String, error: ConversionError },
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => f.write_str("expected an object"),
+            Self::MissingKey(key) => write!(f, "missing key {key:?}"),
+            Self::Conversion { key, error } => write!(f, "key {key:?}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for AccessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Conversion { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    /// The member `key` of an object, like `get` but saying why it's
+    /// missing
+    pub fn try_get(&self, key: &str) -> Result<&Value, AccessError> {
+        self.as_object()
+            .ok_or(AccessError::NotAnObject)?
+            .get(key)
+            .ok_or_else(|| AccessError::MissingKey(key.to_string()))
+    }
+
+    /// The member `key` of an object converted to `T`, e.g.
+    /// `config.try_get_as::<u32>("port")`
+    pub fn try_get_as<T>(&self, key: &str) -> Result<T, AccessError>
+    where
+        T: for<'a> TryFrom<&'a Value, Error = ConversionError>,
+    {
+        T::try_from(self.try_get(key)?).map_err(|error| AccessError::Conversion {
+            key: key.to_string(),
+            error,
+        })
+    }
+}
+
+fn wrong_type(expected: &'static str, found: &Value) -> ConversionError {
+    ConversionError::WrongType {
+        expected,
+        found: found.type_name(),
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<NumberRepr> for Value {
+    fn from(value: NumberRepr) -> Self {
+        Value::Number(value)
+    }
+}
+
+macro_rules! from_integer {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::Number(
+                        i64::try_from(value)
+                            .map_or(NumberRepr::Float(value as f64), NumberRepr::Integer),
+                    )
+                }
+            }
+        )*
+    };
+}
+
+// only unsigned integers beyond `i64::MAX` fall back to a float and lose
+// precision
+from_integer!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Number(NumberRepr::Float(value.into()))
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(NumberRepr::Float(value))
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<Cow<'_, str>> for Value {
+    fn from(value: Cow<'_, str>) -> Self {
+        Value::String(value.into_owned())
+    }
+}
+
+/// `()` becomes `null`
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Null
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<Value>> From<&[T]> for Value {
+    fn from(value: &[T]) -> Self {
+        Value::Array(value.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<V: Into<Value>> From<HashMap<String, V>> for Value {
+    fn from(value: HashMap<String, V>) -> Self {
+        Value::Object(value.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+/// `None` becomes `null`, so `Some(None)` does too
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Collects into a `Value::Array`
+impl<T: Into<Value>> FromIterator<T> for Value {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Value::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Collects key/value pairs into a `Value::Object`. As when parsing, a
+/// repeated key keeps the last value seen.
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Value {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Value::Object(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Appends to an array. `null` is treated as an empty array; any other
+/// variant panics.
+impl<T: Into<Value>> Extend<T> for Value {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        if self.is_null() {
+            *self = Value::Array(Vec::new());
+        }
+        match self {
+            Value::Array(array) => array.extend(iter.into_iter().map(Into::into)),
+            other => panic!("cannot extend {} with array elements", other.type_name()),
+        }
+    }
+}
+
+/// Inserts into an object, overwriting existing keys. `null` is treated
+/// as an empty object; any other variant panics.
+impl<K: Into<String>, V: Into<Value>> Extend<(K, V)> for Value {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        if self.is_null() {
+            *self = Value::Object(HashMap::new());
+        }
+        match self {
+            Value::Object(object) => {
+                object.extend(iter.into_iter().map(|(k, v)| (k.into(), v.into())))
+            }
+            other => panic!("cannot extend {} with object entries", other.type_name()),
+        }
+    }
+}
+
+macro_rules! try_from_value {
+    ($($target:ty, $variant:ident, $expected:literal;)*) => {
+        $(
+            impl TryFrom<Value> for $target {
+                type Error = ConversionError;
+
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    match value {
+                        Value::$variant(inner) => Ok(inner),
+                        other => Err(wrong_type($expected, &other)),
+                    }
+                }
+            }
+
+            impl TryFrom<&Value> for $target {
+                type Error = ConversionError;
+
+                fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                    match value {
+                        Value::$variant(inner) => Ok(inner.clone()),
+                        other => Err(wrong_type($expected, other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+try_from_value! {
+    bool, Boolean, "boolean";
+    String, String, "string";
+    Vec<Value>, Array, "array";
+    HashMap<String, Value>, Object, "object";
+}
+
+impl TryFrom<&Value> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(num) => Ok(num.as_f64()),
+            other => Err(wrong_type("number", other)),
+        }
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        f64::try_from(&value)
+    }
+}
+
+/// Integers must be whole and in range, they're never truncated or
+/// saturated
+macro_rules! try_from_integer {
+    ($($target:ident)*) => {
+        $(
+            impl TryFrom<&Value> for $target {
+                type Error = ConversionError;
+
+                fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                    let out_of_range = ConversionError::OutOfRange {
+                        target: stringify!($target),
+                    };
+                    if let Value::Number(num) = value {
+                        if let Some(int) = num.as_i64() {
+                            return $target::try_from(int).map_err(|_| out_of_range);
+                        }
+                    }
+                    let n = f64::try_from(value)?;
+                    if n.is_finite() && n.fract() != 0.0 {
+                        return Err(ConversionError::NotAnInteger);
+                    }
+                    // `MAX as f64 + 1.0` is exact for the narrow types and
+                    // rounds to the next power of two for the 64-bit ones,
+                    // which is the first value that doesn't fit either way
+                    if n >= $target::MIN as f64 && n < $target::MAX as f64 + 1.0 {
+                        Ok(n as $target)
+                    } else {
+                        Err(out_of_range)
+                    }
+                }
+            }
+
+            impl TryFrom<Value> for $target {
+                type Error = ConversionError;
+
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    $target::try_from(&value)
+                }
+            }
+        )*
+    };
+}
+
+try_from_integer!(i64 u64 i32 u32 usize);
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+
+    use super::{AccessError, ConversionError};
+    use crate::tests::one_of_each;
+    use crate::{json, Value};
+
+    /// Runs `convert` over `one_of_each` and returns the error messages,
+    /// `None` where the conversion succeeded
+    fn messages<T>(convert: impl Fn(Value) -> Result<T, ConversionError>) -> Vec<Option<String>> {
+        one_of_each()
+            .into_iter()
+            .map(|v| convert(v).err().map(|e| e.to_string()))
+            .collect()
+    }
+
+    fn expected_messages(expected: &str, ok_at: usize) -> Vec<Option<String>> {
+        ["null", "boolean", "string", "number", "array", "object"]
+            .iter()
+            .enumerate()
+            .map(|(i, found)| (i != ok_at).then(|| format!("expected {expected}, found {found}")))
+            .collect()
+    }
+
+    #[test]
+    fn try_from_checks_variant_for_every_target() {
+        assert_eq!(messages(bool::try_from), expected_messages("boolean", 1));
+        assert_eq!(messages(String::try_from), expected_messages("string", 2));
+        assert_eq!(messages(f64::try_from), expected_messages("number", 3));
+        assert_eq!(messages(i64::try_from), expected_messages("number", 3));
+        assert_eq!(messages(u64::try_from), expected_messages("number", 3));
+        assert_eq!(messages(i32::try_from), expected_messages("number", 3));
+        assert_eq!(messages(u32::try_from), expected_messages("number", 3));
+        assert_eq!(messages(usize::try_from), expected_messages("number", 3));
+        assert_eq!(
+            messages(Vec::<Value>::try_from),
+            expected_messages("array", 4)
+        );
+        assert_eq!(
+            messages(HashMap::<String, Value>::try_from),
+            expected_messages("object", 5)
+        );
+    }
+
+    #[test]
+    fn try_from_extracts_inner_values() {
+        assert_eq!(bool::try_from(json!(true)), Ok(true));
+        assert_eq!(String::try_from(&json!("s")), Ok("s".to_string()));
+        assert_eq!(f64::try_from(json!(1.5)), Ok(1.5));
+        assert_eq!(i64::try_from(&json!(-7)), Ok(-7));
+        assert_eq!(u64::try_from(json!(7)), Ok(7));
+        assert_eq!(i32::try_from(json!(-2147483648.0)), Ok(i32::MIN));
+        assert_eq!(u32::try_from(json!(4294967295.0)), Ok(u32::MAX));
+        assert_eq!(usize::try_from(&json!(3)), Ok(3));
+        assert_eq!(Vec::<Value>::try_from(json!([1])), Ok(vec![json!(1)]));
+        assert_eq!(
+            HashMap::<String, Value>::try_from(&json!({"k": 2})),
+            Ok(HashMap::from([("k".to_string(), json!(2))]))
+        );
+        let n: f64 = json!(4).try_into().unwrap();
+        assert_eq!(n, 4.0);
+    }
+
+    #[test]
+    fn integers_reject_fractions_and_out_of_range() {
+        assert_eq!(
+            i64::try_from(json!(1.5)),
+            Err(ConversionError::NotAnInteger)
+        );
+        assert_eq!(
+            u64::try_from(json!(-1)).unwrap_err().to_string(),
+            "number out of range for u64"
+        );
+        assert_eq!(
+            i32::try_from(json!(2147483648.0)),
+            Err(ConversionError::OutOfRange { target: "i32" })
+        );
+        assert_eq!(
+            u32::try_from(json!(4294967296.0)),
+            Err(ConversionError::OutOfRange { target: "u32" })
+        );
+        assert_eq!(
+            i64::try_from(json!(9223372036854775808.0)),
+            Err(ConversionError::OutOfRange { target: "i64" })
+        );
+        assert_eq!(
+            usize::try_from(Value::num(f64::NAN)),
+            Err(ConversionError::OutOfRange { target: "usize" })
+        );
+        assert_eq!(
+            ConversionError::NotAnInteger.to_string(),
+            "expected an integer, found a fractional number"
+        );
+    }
+
+    #[test]
+    fn collects_arrays() {
+        let v: Value = (0..3).map(Value::from).collect();
+        assert_eq!(v, json!([0, 1, 2]));
+
+        let nested: Value = (1..=2).map(|n| (0..n).collect::<Value>()).collect();
+        assert_eq!(nested, json!([[0], [0, 1]]));
+    }
+
+    #[test]
+    fn collects_objects() {
+        let v: Value = ["a", "b"]
+            .into_iter()
+            .zip(1..)
+            .map(|(k, n)| (k, (0..n).map(|i| (format!("k{i}"), i)).collect::<Value>()))
+            .collect();
+        assert_eq!(v, json!({"a": {"k0": 0}, "b": {"k0": 0, "k1": 1}}));
+
+        let owned: Value = vec![("x".to_string(), Value::Null)].into_iter().collect();
+        assert_eq!(owned, json!({"x": null}));
+    }
+
+    #[test]
+    fn collect_keeps_last_duplicate() {
+        let v: Value = [("k", 1), ("k", 2)].into_iter().collect();
+        assert_eq!(v, json!({"k": 2}));
+    }
+
+    #[test]
+    fn extends_existing_containers() {
+        let mut array = json!([1]);
+        array.extend(["two", "three"]);
+        assert_eq!(array, json!([1, "two", "three"]));
+
+        let mut object = json!({"a": 1});
+        object.extend([("a", 10), ("b", 2)]);
+        assert_eq!(object, json!({"a": 10, "b": 2}));
+
+        let mut from_null = Value::Null;
+        from_null.extend([true]);
+        assert_eq!(from_null, json!([true]));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot extend object with array elements")]
+    fn extend_wrong_variant_panics() {
+        json!({}).extend([1]);
+    }
+
+    #[test]
+    fn try_get_reports_why_a_member_is_missing() {
+        let value = json!({"a": 1});
+
+        assert_eq!(value.try_get("a"), Ok(&json!(1)));
+        assert_eq!(
+            value.try_get("b"),
+            Err(AccessError::MissingKey("b".to_string()))
+        );
+        assert_eq!(json!([1]).try_get("a"), Err(AccessError::NotAnObject));
+        assert_eq!(
+            value.try_get("b").unwrap_err().to_string(),
+            r#"missing key "b""#
+        );
+    }
+
+    #[test]
+    fn try_get_as_converts_the_member() {
+        let value = json!({"a": 1});
+
+        assert_eq!(value.try_get_as::<u32>("a"), Ok(1));
+        assert_eq!(value.try_get_as::<f64>("a"), Ok(1.0));
+        assert_eq!(
+            value.try_get_as::<String>("a"),
+            Err(AccessError::Conversion {
+                key: "a".to_string(),
+                error: ConversionError::WrongType {
+                    expected: "string",
+                    found: "number"
+                }
+            })
+        );
+        assert_eq!(
+            value.try_get_as::<bool>("a").unwrap_err().to_string(),
+            r#"key "a": expected boolean, found number"#
+        );
+        assert_eq!(
+            value.try_get_as::<u32>("b"),
+            Err(AccessError::MissingKey("b".to_string()))
+        );
+        assert_eq!(
+            json!(null).try_get_as::<u32>("a"),
+            Err(AccessError::NotAnObject)
+        );
+    }
+
+    #[test]
+    fn from_scalars() {
+        assert_eq!(Value::from(true), json!(true));
+        assert_eq!(Value::from(3), json!(3));
+        assert_eq!(Value::from(-3i8), json!(-3));
+        assert_eq!(Value::from(7usize), json!(7));
+        assert_eq!(Value::from(1.5f32), json!(1.5));
+        assert_eq!(Value::from(2.5), json!(2.5));
+        assert_eq!(Value::from("s"), json!("s"));
+        assert_eq!(Value::from("s".to_string()), json!("s"));
+        assert_eq!(Value::from(Cow::Borrowed("s")), json!("s"));
+        assert_eq!(Value::from(Cow::<str>::Owned("s".to_string())), json!("s"));
+        assert_eq!(Value::from(()), json!(null));
+    }
+
+    #[test]
+    fn from_sequences_and_maps() {
+        assert_eq!(Value::from(vec![1, 2]), json!([1, 2]));
+        assert_eq!(Value::from(&[1, 2][..]), json!([1, 2]));
+        assert_eq!(Value::from(vec![json!(null)]), json!([null]));
+        assert_eq!(Value::from(vec!["a", "b"]), json!(["a", "b"]));
+        assert_eq!(
+            Value::from(HashMap::from([("k".to_string(), vec![true])])),
+            json!({"k": [true]})
+        );
+    }
+
+    #[test]
+    fn from_options() {
+        assert_eq!(Value::from(Some(1)), json!(1));
+        assert_eq!(Value::from(None::<i32>), json!(null));
+        assert_eq!(Value::from(Some(Some("s"))), json!("s"));
+        assert_eq!(Value::from(Some(None::<&str>)), json!(null));
+        assert_eq!(Value::from(None::<Option<&str>>), json!(null));
+    }
+}
@@ -0,0 +1,311 @@
+//! serde support, enabled by the `serde` feature: `Value` can be
+//! deserialized from any serde format and can itself act as a
+//! `Deserializer` for typed structs.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{
+    self, value::MapDeserializer, value::SeqDeserializer, DeserializeOwned, Deserializer,
+    EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::Deserialize;
+
+use crate::{parse, NumberRepr, ParseError, Value};
+
+/// Parses `input` and deserializes the result into `T`
+pub fn from_str_as<T: DeserializeOwned>(input: &str) -> Result<T, ParseError> {
+    let value = parse(input)?;
+    T::deserialize(value).map_err(|e| ParseError::Deserialize(e.0))
+}
+
+/// Error raised while deserializing a `Value` into a Rust type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError(String);
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            array.push(element);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut object = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Boolean(b) => visitor.visit_bool(b),
+            Value::Number(num) => visit_number(&num, visitor),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Array(array) => visitor.visit_seq(SeqDeserializer::new(array.into_iter())),
+            Value::Object(object) => visitor.visit_map(MapDeserializer::new(object.into_iter())),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::Null => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Object(object) if object.len() == 1 => {
+                let (variant, value) = object.into_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            other => Err(de::Error::custom(format!(
+                "expected a string or single-key object for an enum, found {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Whole numbers go through the integer visitors so they can land in
+/// integer fields, everything else stays a float
+fn visit_number<'de, V: Visitor<'de>>(
+    num: &NumberRepr,
+    visitor: V,
+) -> Result<V::Value, DeserializeError> {
+    if let Some(int) = num.as_i64() {
+        return match u64::try_from(int) {
+            Ok(unsigned) => visitor.visit_u64(unsigned),
+            Err(_) => visitor.visit_i64(int),
+        };
+    }
+    let n = num.as_f64();
+    if n.fract() == 0.0 && n >= 0.0 && n < u64::MAX as f64 {
+        visitor.visit_u64(n as u64)
+    } else if n.fract() == 0.0 && n >= i64::MIN as f64 && n < 0.0 {
+        visitor.visit_i64(n as i64)
+    } else {
+        visitor.visit_f64(n)
+    }
+}
+
+/// `{"Variant": value}` form of an externally tagged enum
+struct EnumDeserializer {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = DeserializeError;
+    type Variant = Value;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Value), Self::Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Value {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self {
+            Value::Null => Ok(()),
+            other => Err(de::Error::custom(format!(
+                "expected null for a unit variant, found {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::from_str_as;
+    use crate::{json, ParseError, Value};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn deserializes_struct() {
+        let point: Point = from_str_as(r#"{"x":1,"y":2}"#).unwrap();
+        assert_eq!(point, Point { x: 1, y: 2 });
+    }
+
+    #[test]
+    fn deserializes_nested_types() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Shape {
+            Empty,
+            Circle(f64),
+            Line { from: Point, to: Point },
+        }
+
+        let shapes: Vec<Option<Shape>> = from_str_as(
+            r#"["Empty", null, {"Circle": 1.5},
+                {"Line": {"from": {"x": 0, "y": 0}, "to": {"x": -3, "y": 4}}}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            shapes,
+            vec![
+                Some(Shape::Empty),
+                None,
+                Some(Shape::Circle(1.5)),
+                Some(Shape::Line {
+                    from: Point { x: 0, y: 0 },
+                    to: Point { x: -3, y: 4 },
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_type_mismatch() {
+        let err = from_str_as::<Point>(r#"{"x":"one","y":2}"#).unwrap_err();
+        assert!(matches!(err, ParseError::Deserialize(_)));
+        assert!(matches!(
+            from_str_as::<Point>("{"),
+            Err(ParseError::TokenizeError(_) | ParseError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn value_round_trips_through_itself() {
+        let value = json!({"a": [1, true, null, "s"], "b": {"c": 2.5}});
+        assert_eq!(Value::deserialize(value.clone()).unwrap(), value);
+    }
+}
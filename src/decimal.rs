@@ -0,0 +1,89 @@
+use rust_decimal::Decimal;
+
+use crate::{NumberRepr, Value};
+
+impl Value {
+    /// The number as an exact `Decimal`, `None` for other variants, NaN,
+    /// infinities and magnitudes beyond `Decimal`'s 96-bit range
+    ///
+    /// Integers and raw literals (see `ParseConfig::preserve_raw_numbers`)
+    /// convert exactly, up to `Decimal`'s 28 digits. Floats start from the
+    /// shortest decimal text that round-trips to the same `f64`, so `0.1`
+    /// becomes exactly `0.1`, but digits past `f64` precision are already
+    /// gone.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        let num = match self {
+            Value::Number(NumberRepr::Integer(int)) => return Some(Decimal::from(*int)),
+            Value::Number(NumberRepr::Raw(literal)) => {
+                return Decimal::from_scientific(literal)
+                    .or_else(|_| Decimal::from_str_exact(literal))
+                    .ok();
+            }
+            Value::Number(NumberRepr::Float(num)) => *num,
+            _ => return None,
+        };
+        if !num.is_finite() {
+            return None;
+        }
+        // `{:e}` gives the shortest round-trip digits, e.g. `1.2345e6`
+        Decimal::from_scientific(&format!("{num:e}")).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rust_decimal::Decimal;
+
+    use crate::{parse, parse_with_config, ParseConfig, Value};
+
+    fn decimal(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn converts_to_exact_decimal() {
+        assert_eq!(parse("0.1").unwrap().as_decimal(), Some(decimal("0.1")));
+        assert_eq!(
+            parse("[-123.456]").unwrap()[0].as_decimal(),
+            Some(decimal("-123.456"))
+        );
+        assert_eq!(
+            parse("2.5e-7").unwrap().as_decimal(),
+            Some(decimal("0.00000025"))
+        );
+        assert_eq!(
+            parse("1e20").unwrap().as_decimal(),
+            Some(decimal("100000000000000000000"))
+        );
+    }
+
+    #[test]
+    fn raw_numbers_convert_exactly() {
+        let config = ParseConfig {
+            preserve_raw_numbers: true,
+            ..ParseConfig::default()
+        };
+        let value = parse_with_config(
+            "[0.1, 1.2345678901234567890123, 9223372036854775807]",
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(value[0].as_decimal(), Some(decimal("0.1")));
+        assert_eq!(
+            value[1].as_decimal(),
+            Some(decimal("1.2345678901234567890123"))
+        );
+        assert_eq!(value[2].as_decimal(), Some(Decimal::from(i64::MAX)));
+    }
+
+    #[test]
+    fn rejects_non_numbers_and_unrepresentable() {
+        assert_eq!(Value::str("0.1").as_decimal(), None);
+        assert_eq!(Value::num(f64::NAN).as_decimal(), None);
+        assert_eq!(Value::num(f64::INFINITY).as_decimal(), None);
+        assert_eq!(Value::num(1e300).as_decimal(), None);
+    }
+}
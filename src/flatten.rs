@@ -0,0 +1,302 @@
+//! Converting between nested documents and single-level objects keyed by
+//! delimited paths such as `"a.b.0.c"`
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::pointer::array_index;
+use crate::Value;
+
+/// Flattens `value` into a single-level object keyed by `.` separated
+/// paths, e.g. `{"a": {"b": [1]}}` becomes `{"a.b.0": 1}`
+///
+/// See [`flatten_with`] for the details.
+pub fn flatten(value: &Value) -> Value {
+    flatten_with(value, '.')
+}
+
+/// Flattens `value` into a single-level object whose keys are the paths
+/// to its leaves, segments joined by `delimiter` and array elements named
+/// by their index
+///
+/// A `delimiter` or `\` inside a key is escaped with a `\`, so the key
+/// `"a.b"` becomes the segment `a\.b` and [`unflatten_with`] can tell it
+/// apart from a nested path.
+///
+/// Empty arrays and objects are kept as leaves: `{"a": []}` flattens to
+/// `{"a": []}`, so they survive the round trip. A document that has no
+/// members to flatten, a scalar or an empty container, is returned as is.
+///
+/// # Panics
+///
+/// When `delimiter` is `\`, which is taken by the escaping.
+pub fn flatten_with(value: &Value, delimiter: char) -> Value {
+    assert_ne!(delimiter, '\\', "`\\` can't be used as a flatten delimiter");
+    if is_leaf(value) {
+        return value.clone();
+    }
+    let mut flat = HashMap::new();
+    flatten_into(&mut String::new(), true, value, delimiter, &mut flat);
+    Value::Object(flat)
+}
+
+/// Rebuilds the nested document from the output of [`flatten`]
+///
+/// See [`unflatten_with`] for the details.
+pub fn unflatten(value: &Value) -> Value {
+    unflatten_with(value, '.')
+}
+
+/// Rebuilds a nested document from an object keyed by `delimiter`
+/// separated paths, the inverse of [`flatten_with`]
+///
+/// A level whose segments are exactly `0`, `1`, ... `n - 1` becomes an
+/// array, so an object that originally had only such keys comes back as
+/// an array. Anything other than an object is returned as is.
+///
+/// When one key is a prefix of another, such as `a` and `a.b`, there's no
+/// way to keep both; the keys are applied in sorted order and a later
+/// path replaces any leaf in its way, so `a.b` wins.
+pub fn unflatten_with(value: &Value, delimiter: char) -> Value {
+    let Value::Object(flat) = value else {
+        return value.clone();
+    };
+    let mut entries: Vec<_> = flat.iter().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+
+    let mut root = Node::Branch(BTreeMap::new());
+    for (key, leaf) in entries {
+        let mut node = &mut root;
+        for segment in split_path(key, delimiter) {
+            if !matches!(node, Node::Branch(_)) {
+                *node = Node::Branch(BTreeMap::new());
+            }
+            let Node::Branch(children) = node else {
+                unreachable!("node was just made a branch");
+            };
+            node = children
+                .entry(segment)
+                .or_insert_with(|| Node::Branch(BTreeMap::new()));
+        }
+        *node = Node::Leaf(leaf.clone());
+    }
+    root.into_value()
+}
+
+/// A level of the document being rebuilt, whose members aren't yet known
+/// to form an object or an array
+enum Node {
+    Leaf(Value),
+    Branch(BTreeMap<String, Node>),
+}
+
+impl Node {
+    fn into_value(self) -> Value {
+        let children = match self {
+            Node::Leaf(value) => return value,
+            Node::Branch(children) => children,
+        };
+        // `BTreeMap` orders "10" before "2", so check every index is
+        // present rather than relying on the order
+        let is_array = !children.is_empty()
+            && children
+                .keys()
+                .all(|key| array_index(key).is_some_and(|i| i < children.len()));
+        if is_array {
+            let mut elements: Vec<_> = children
+                .into_iter()
+                .map(|(key, child)| (array_index(&key), child.into_value()))
+                .collect();
+            elements.sort_unstable_by_key(|(index, _)| *index);
+            Value::Array(elements.into_iter().map(|(_, value)| value).collect())
+        } else {
+            Value::Object(
+                children
+                    .into_iter()
+                    .map(|(key, child)| (key, child.into_value()))
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Scalars and empty containers, which flatten to themselves
+fn is_leaf(value: &Value) -> bool {
+    match value {
+        Value::Array(array) => array.is_empty(),
+        Value::Object(object) => object.is_empty(),
+        _ => true,
+    }
+}
+
+/// Adds the leaves under `value` to `flat`, `is_root` telling whether
+/// `path` has no segments yet; it can't be told from an empty `path`,
+/// which is also the path of the key `""`
+fn flatten_into(
+    path: &mut String,
+    is_root: bool,
+    value: &Value,
+    delimiter: char,
+    flat: &mut HashMap<String, Value>,
+) {
+    if is_leaf(value) {
+        flat.insert(path.clone(), value.clone());
+        return;
+    }
+    let len = path.len();
+    let push_segment = |path: &mut String, segment: &str| {
+        if !is_root {
+            path.push(delimiter);
+        }
+        for c in segment.chars() {
+            if c == delimiter || c == '\\' {
+                path.push('\\');
+            }
+            path.push(c);
+        }
+    };
+    match value {
+        Value::Array(array) => {
+            for (i, element) in array.iter().enumerate() {
+                push_segment(path, &i.to_string());
+                flatten_into(path, false, element, delimiter, flat);
+                path.truncate(len);
+            }
+        }
+        Value::Object(object) => {
+            for (key, member) in object {
+                push_segment(path, key);
+                flatten_into(path, false, member, delimiter, flat);
+                path.truncate(len);
+            }
+        }
+        _ => unreachable!("scalars are leaves"),
+    }
+}
+
+/// The unescaped segments of a flattened key
+fn split_path(key: &str, delimiter: char) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut chars = key.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = chars.next().unwrap_or('\\');
+                segments.last_mut().unwrap().push(escaped);
+            }
+            c if c == delimiter => segments.push(String::new()),
+            c => segments.last_mut().unwrap().push(c),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flatten, flatten_with, unflatten, unflatten_with};
+    use crate::{json, parse, Value};
+
+    #[test]
+    fn flattens_to_dotted_keys() {
+        let doc = json!({"a": {"b": [{"c": 1}, true]}, "d": null});
+
+        assert_eq!(
+            flatten(&doc),
+            json!({"a.b.0.c": 1, "a.b.1": true, "d": null})
+        );
+    }
+
+    #[test]
+    fn key_containing_the_delimiter() {
+        let doc = json!({"a.b": {"c": 1}, "a": {"b": {"c": 2}}, "x\\y": 3});
+        let flat = flatten(&doc);
+
+        assert_eq!(flat, json!({"a\\.b.c": 1, "a.b.c": 2, "x\\\\y": 3}));
+        assert_eq!(unflatten(&flat), doc);
+    }
+
+    #[test]
+    fn custom_delimiter() {
+        let doc = json!({"a": {"b.c": [1, 2]}, "d/e": 3});
+        let flat = flatten_with(&doc, '/');
+
+        assert_eq!(flat, json!({"a/b.c/0": 1, "a/b.c/1": 2, "d\\/e": 3}));
+        assert_eq!(unflatten_with(&flat, '/'), doc);
+    }
+
+    #[test]
+    fn empty_containers_survive() {
+        let doc = json!({"a": {}, "b": [], "c": [{}, []]});
+
+        assert_eq!(
+            flatten(&doc),
+            json!({"a": {}, "b": [], "c.0": {}, "c.1": []})
+        );
+        assert_eq!(unflatten(&flatten(&doc)), doc);
+    }
+
+    #[test]
+    fn leaves_flatten_to_themselves() {
+        for doc in [json!(1), json!("s"), json!(null), json!([]), json!({})] {
+            assert_eq!(flatten(&doc), doc);
+            assert_eq!(unflatten(&doc), doc);
+        }
+    }
+
+    #[test]
+    fn consecutive_indices_become_arrays() {
+        let flat = json!({
+            "list.0": "a", "list.1": "b", "list.2": "c", "list.3": "d", "list.4": "e",
+            "list.5": "f", "list.6": "g", "list.7": "h", "list.8": "i", "list.9": "j",
+            "list.10": "k",
+            "gap.0": 1, "gap.2": 2,
+            "padded.00": 1,
+        });
+        let doc = unflatten(&flat);
+
+        assert_eq!(
+            doc["list"],
+            json!(["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"])
+        );
+        assert_eq!(doc["gap"], json!({"0": 1, "2": 2}));
+        assert_eq!(doc["padded"], json!({"00": 1}));
+    }
+
+    #[test]
+    fn longer_path_replaces_a_leaf_prefix() {
+        assert_eq!(
+            unflatten(&json!({"a": 1, "a.b": 2})),
+            json!({"a": {"b": 2}})
+        );
+    }
+
+    #[test]
+    fn empty_keys_at_the_root() {
+        assert_eq!(flatten(&json!({"": {"a": 1}})), json!({".a": 1}));
+        assert_eq!(flatten(&json!({"": {"": 0}})), json!({".": 0}));
+        assert_eq!(flatten(&json!({"": 1})), json!({"": 1}));
+    }
+
+    #[test]
+    fn round_trips() {
+        let docs = [
+            r#"{"a": 1}"#,
+            r#"{"": {"": 0}}"#,
+            r#"{"": {"a": 1}}"#,
+            r#"[1, [2, [3, []]], {"": {"": 0}}]"#,
+            r#"{"users": [{"name": "amy", "tags": ["x", "y"]}, {"name": "bo", "tags": []}]}"#,
+            r#"{"a.b": {".": {"\\": "..", "\\.": [null, false]}}, "": 2.5}"#,
+            r#"{"deep": {"er": {"est": {"list": [[[[1]]]]}}}}"#,
+        ];
+        for doc in docs {
+            let doc: Value = parse(doc).unwrap();
+            assert_eq!(unflatten(&flatten(&doc)), doc, "{doc:?}");
+            assert_eq!(unflatten_with(&flatten_with(&doc, ':'), ':'), doc);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be used as a flatten delimiter")]
+    fn backslash_delimiter_panics() {
+        flatten_with(&json!({"a": 1}), '\\');
+    }
+}
@@ -0,0 +1,303 @@
+//! The line-per-value format of the `gron` tool, which makes documents
+//! greppable: `json.users[0].name = "amy";`
+
+use std::fmt::Write;
+
+use crate::{escape_string, parse, unescape_string, ParseError, Value};
+
+/// Writes `value` as one `path = value;` assignment per line, the path
+/// starting at `root_name`
+///
+/// Arrays and objects get a line of their own declaring them empty
+/// (`json.users = [];`) before the lines for their contents, so the
+/// output rebuilds the same structure through [`from_gron`]. Keys that
+/// aren't identifiers are written in brackets as quoted strings
+/// (`json["first name"]`), and strings are escaped as the serializer
+/// escapes them. Members come out in key order.
+///
+/// ```
+/// use json_parser::{json, to_gron};
+///
+/// let doc = json!({"users": [{"name": "amy"}]});
+/// assert_eq!(
+///     to_gron(&doc, "json"),
+///     "json = {};\njson.users = [];\njson.users[0] = {};\njson.users[0].name = \"amy\";\n"
+/// );
+/// ```
+pub fn to_gron(value: &Value, root_name: &str) -> String {
+    let mut output = String::new();
+    write_gron(&mut root_name.to_string(), value, &mut output);
+    output
+}
+
+/// Rebuilds a document from the output of [`to_gron`]
+///
+/// Every non-blank line must be a `path = value;` assignment whose path
+/// starts at the same root name, and a container has to be assigned
+/// before anything inside it. An array element must either replace an
+/// existing one or come right after the last. Lines that break these
+/// rules give [`ParseError::InvalidGron`] with their 1-based line number.
+pub fn from_gron(input: &str) -> Result<Value, ParseError> {
+    let mut root: Option<(&str, Value)> = None;
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let invalid = || ParseError::InvalidGron { line: line_number };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (name, steps, value) = parse_assignment(line).ok_or_else(invalid)?;
+        match (&mut root, steps.split_last()) {
+            (None, None) => root = Some((name, value)),
+            (Some((root_name, document)), last) if *root_name == name => match last {
+                None => *document = value,
+                Some((last, parents)) => {
+                    assign(document, parents, last, value).ok_or_else(invalid)?
+                }
+            },
+            _ => return Err(invalid()),
+        }
+    }
+    root.map(|(_, document)| document)
+        .ok_or(ParseError::EmptyInput)
+}
+
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+fn write_gron(path: &mut String, value: &Value, output: &mut String) {
+    match value {
+        Value::Null => writeln!(output, "{path} = null;"),
+        Value::Boolean(b) => writeln!(output, "{path} = {b};"),
+        Value::Number(num) => writeln!(output, "{path} = {num};"),
+        Value::String(s) => writeln!(output, "{path} = \"{}\";", escape_string(s)),
+        Value::Array(_) => writeln!(output, "{path} = [];"),
+        Value::Object(_) => writeln!(output, "{path} = {{}};"),
+    }
+    .expect("writing to a String never fails");
+
+    let len = path.len();
+    match value {
+        Value::Array(array) => {
+            for (i, element) in array.iter().enumerate() {
+                write!(path, "[{i}]").unwrap();
+                write_gron(path, element, output);
+                path.truncate(len);
+            }
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            for (key, member) in entries {
+                if is_identifier(key) {
+                    write!(path, ".{key}").unwrap();
+                } else {
+                    write!(path, "[\"{}\"]", escape_string(key)).unwrap();
+                }
+                write_gron(path, member, output);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `key` can follow a `.` in a path: an ASCII letter, `_` or `$`
+/// followed by those or digits
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Splits a trimmed `root.steps = value;` line into its parts
+fn parse_assignment(line: &str) -> Option<(&str, Vec<Step>, Value)> {
+    let line = line.strip_suffix(';')?;
+    let name_len = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(line.len());
+    let (name, mut rest) = line.split_at(name_len);
+    if !is_identifier(name) {
+        return None;
+    }
+
+    let mut steps = Vec::new();
+    loop {
+        if let Some(after) = rest.strip_prefix('.') {
+            let len = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+                .unwrap_or(after.len());
+            let key = &after[..len];
+            if !is_identifier(key) {
+                return None;
+            }
+            steps.push(Step::Key(key.to_string()));
+            rest = &after[len..];
+        } else if let Some(after) = rest.strip_prefix("[\"") {
+            let len = closing_quote(after)?;
+            let key = unescape_string(&after[..len]).ok()?;
+            steps.push(Step::Key(key));
+            rest = after[len + 1..].strip_prefix(']')?;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (index, after) = after.split_once(']')?;
+            if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            steps.push(Step::Index(index.parse().ok()?));
+            rest = after;
+        } else {
+            break;
+        }
+    }
+
+    let value = rest.trim_start().strip_prefix('=')?;
+    Some((name, steps, parse(value).ok()?))
+}
+
+/// Byte offset of the `"` ending a string whose content starts `input`
+fn closing_quote(input: &str) -> Option<usize> {
+    let mut is_escaping = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            _ if is_escaping => is_escaping = false,
+            '\\' => is_escaping = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Stores `value` at `last` inside the container `parents` leads to,
+/// `None` if that container wasn't declared or `last` doesn't fit it
+fn assign(document: &mut Value, parents: &[Step], last: &Step, value: Value) -> Option<()> {
+    let mut current = document;
+    for step in parents {
+        current = match (step, current) {
+            (Step::Key(key), Value::Object(object)) => object.get_mut(key)?,
+            (Step::Index(i), Value::Array(array)) => array.get_mut(*i)?,
+            _ => return None,
+        };
+    }
+    match (last, current) {
+        (Step::Key(key), Value::Object(object)) => {
+            object.insert(key.clone(), value);
+        }
+        (Step::Index(i), Value::Array(array)) if *i < array.len() => array[*i] = value,
+        (Step::Index(i), Value::Array(array)) if *i == array.len() => array.push(value),
+        _ => return None,
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_gron, to_gron};
+    use crate::{json, parse, ParseError};
+
+    #[test]
+    fn lines_for_every_value_and_container() {
+        let doc = parse(
+            r#"{"users": [{"name": "amy", "admin": true}, {"name": "bo", "age": null}],
+                "count": 2.5}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            to_gron(&doc, "json"),
+            "json = {};\n\
+             json.count = 2.5;\n\
+             json.users = [];\n\
+             json.users[0] = {};\n\
+             json.users[0].admin = true;\n\
+             json.users[0].name = \"amy\";\n\
+             json.users[1] = {};\n\
+             json.users[1].age = null;\n\
+             json.users[1].name = \"bo\";\n"
+        );
+    }
+
+    #[test]
+    fn lines_can_be_grepped() {
+        let doc = json!({"users": [{"name": "amy"}, {"name": "bo"}], "name": "root"});
+        let gron = to_gron(&doc, "json");
+        let names: Vec<_> = gron
+            .lines()
+            .filter(|line| line.contains(".name = "))
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                "json.name = \"root\";",
+                "json.users[0].name = \"amy\";",
+                "json.users[1].name = \"bo\";"
+            ]
+        );
+    }
+
+    #[test]
+    fn awkward_keys_and_strings_are_quoted() {
+        let doc = json!({"first name": "a\"b\n", "2x": [], "ok_$1": {}, "": 1});
+
+        assert_eq!(
+            to_gron(&doc, "doc"),
+            "doc = {};\n\
+             doc[\"\"] = 1;\n\
+             doc[\"2x\"] = [];\n\
+             doc[\"first name\"] = \"a\\\"b\\n\";\n\
+             doc.ok_$1 = {};\n"
+        );
+    }
+
+    #[test]
+    fn scalar_root() {
+        assert_eq!(to_gron(&json!("hi"), "json"), "json = \"hi\";\n");
+        assert_eq!(from_gron("json = \"hi\";"), Ok(json!("hi")));
+    }
+
+    #[test]
+    fn round_trip() {
+        let doc = parse(
+            r#"{"a": [1, [2, [3]], {"b": null}], "c d": {"e]\"": "x\\y", "f": []},
+                "g": -1e-7, "h": false, "": {"": ""}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(from_gron(&to_gron(&doc, "json")), Ok(doc));
+    }
+
+    #[test]
+    fn whitespace_and_blank_lines_are_ignored() {
+        let input = "\n  json = [];\n\njson[0]   =   {\"a\": 1} ;\n";
+
+        assert_eq!(from_gron(input), Ok(json!([{"a": 1}])));
+    }
+
+    #[test]
+    fn invalid_lines_report_line_number() {
+        let cases = [
+            ("json = {};\njson.a = 1", 2),
+            ("json = {};\njson.a.b = 1;", 2),
+            ("json = [];\njson[1] = 1;", 2),
+            ("json = {};\njson[0] = 1;", 2),
+            ("json = {};\nother.a = 1;", 2),
+            ("json.a = 1;", 1),
+            ("json = {};\n\njson.a = ;", 3),
+            ("json = {};\njson[\"a] = 1;", 2),
+            ("json = {};\njson.1 = 1;", 2),
+        ];
+        for (input, line) in cases {
+            assert_eq!(
+                from_gron(input),
+                Err(ParseError::InvalidGron { line }),
+                "{input:?}"
+            );
+        }
+        assert_eq!(from_gron(" \n"), Err(ParseError::EmptyInput));
+    }
+}
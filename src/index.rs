@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+
+use crate::Value;
+
+static NULL: Value = Value::Null;
+
+/// `value["key"]`, `null` when the value isn't an object or has no such key
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        match self {
+            Value::Object(object) => object.get(key).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+/// `value[0]`, `null` when the value isn't an array or is too short
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        match self {
+            Value::Array(array) => array.get(index).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+/// `value["key"] = ...`, inserting `null` for a missing key first
+///
+/// A `null` value is turned into an empty object, so assignments through
+/// missing keys build nested objects. Panics for any other non-object.
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        if let Value::Null = self {
+            *self = Value::Object(HashMap::new());
+        }
+        match self {
+            Value::Object(object) => object.entry(key.to_string()).or_default(),
+            other => panic!("cannot index into {} with a key", other.type_name()),
+        }
+    }
+}
+
+/// `value[0] = ...` for an existing element
+///
+/// Panics when the value isn't an array or the index is out of range,
+/// arrays are never extended.
+impl IndexMut<usize> for Value {
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        match self {
+            Value::Array(array) => {
+                let len = array.len();
+                array.get_mut(index).unwrap_or_else(|| {
+                    panic!("index {index} out of range for array of length {len}")
+                })
+            }
+            other => panic!("cannot index into {} with a number", other.type_name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{json, Value};
+
+    fn users() -> Value {
+        json!({"users": [{"name": "Ada", "tags": ["a"]}, {"name": "Grace"}]})
+    }
+
+    #[test]
+    fn chained_access() {
+        let value = users();
+
+        assert_eq!(value["users"][0]["name"], json!("Ada"));
+        assert_eq!(value["users"][1]["name"], json!("Grace"));
+        assert_eq!(value["users"][0]["tags"][0], json!("a"));
+    }
+
+    #[test]
+    fn missing_paths_are_null() {
+        let value = users();
+
+        assert_eq!(value["nobody"], Value::Null);
+        assert_eq!(value["users"][5], Value::Null);
+        assert_eq!(value["nobody"]["deeper"][3]["still"], Value::Null);
+    }
+
+    #[test]
+    fn wrong_type_access_is_null() {
+        let value = users();
+
+        assert_eq!(value[0], Value::Null);
+        assert_eq!(value["users"]["name"], Value::Null);
+        assert_eq!(value["users"][0]["name"][0], Value::Null);
+        assert_eq!(json!(1)["a"], Value::Null);
+    }
+
+    #[test]
+    fn index_mut_builds_structure() {
+        let mut value = Value::Null;
+        value["config"]["theme"] = json!("dark");
+        value["config"]["sizes"] = json!([1, 2]);
+        value["config"]["sizes"][1] = json!(3);
+
+        assert_eq!(value, json!({"config": {"theme": "dark", "sizes": [1, 3]}}));
+    }
+
+    #[test]
+    fn index_mut_replaces_existing() {
+        let mut value = users();
+        value["users"][1]["name"] = json!("Hopper");
+
+        assert_eq!(value["users"][1]["name"], json!("Hopper"));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 2 out of range for array of length 2")]
+    fn index_mut_past_end_panics() {
+        let mut value = json!([1, 2]);
+        value[2] = json!(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index into number with a key")]
+    fn index_mut_key_on_scalar_panics() {
+        let mut value = json!(1);
+        value["a"] = json!(2);
+    }
+}
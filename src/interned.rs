@@ -0,0 +1,249 @@
+//! Parsing into a tree whose object keys are shared, for documents that
+//! repeat the same keys thousands of times
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::parse::{parse_document, Document};
+use crate::{NumberRepr, ParseConfig, ParseError};
+
+/// A document parsed by [`parse_interned`], the same as [`Value`](crate::Value)
+/// except that equal object keys share one `Arc<str>`
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedValue {
+    Null,
+    Boolean(bool),
+    String(String),
+    Number(NumberRepr),
+    Array(Vec<InternedValue>),
+    Object(HashMap<Arc<str>, InternedValue>),
+}
+
+/// Parses `input` like [`parse`](crate::parse), allocating each distinct
+/// object key once and sharing it between every object that uses it
+///
+/// An array of 10 000 `{"id": .., "name": ..}` objects holds two key
+/// allocations rather than 20 000.
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use json_parser::{parse_interned, InternedValue};
+///
+/// let value = parse_interned(r#"[{"id": 1}, {"id": 2}]"#).unwrap();
+/// let InternedValue::Array(items) = value else { panic!() };
+/// let keys: Vec<_> = items
+///     .iter()
+///     .map(|item| match item {
+///         InternedValue::Object(object) => object.keys().next().unwrap().clone(),
+///         _ => panic!(),
+///     })
+///     .collect();
+/// assert!(Arc::ptr_eq(&keys[0], &keys[1]));
+/// ```
+pub fn parse_interned(input: &str) -> Result<InternedValue, ParseError> {
+    parse_interned_with_config(input, &ParseConfig::default())
+}
+
+/// [`parse_interned`] following `config`, like
+/// [`parse_with_config`](crate::parse_with_config)
+pub fn parse_interned_with_config(
+    input: &str,
+    config: &ParseConfig,
+) -> Result<InternedValue, ParseError> {
+    parse_document(input, config)
+}
+
+impl Document for InternedValue {
+    type Key = Arc<str>;
+
+    /// Every key seen so far in the document
+    type Keys = HashSet<Arc<str>>;
+
+    /// The shared copy of `key`, allocated the first time it's seen
+    fn key(keys: &mut Self::Keys, key: Cow<str>) -> Arc<str> {
+        if let Some(shared) = keys.get(&*key) {
+            return shared.clone();
+        }
+        let shared: Arc<str> = Arc::from(key);
+        keys.insert(shared.clone());
+        shared
+    }
+
+    fn null() -> Self {
+        InternedValue::Null
+    }
+
+    fn boolean(value: bool) -> Self {
+        InternedValue::Boolean(value)
+    }
+
+    fn number(num: NumberRepr) -> Self {
+        InternedValue::Number(num)
+    }
+
+    fn string(s: String) -> Self {
+        InternedValue::String(s)
+    }
+
+    fn array(array: Vec<Self>) -> Self {
+        InternedValue::Array(array)
+    }
+
+    fn object(object: HashMap<Arc<str>, Self>) -> Self {
+        InternedValue::Object(object)
+    }
+
+    fn is_container(&self) -> bool {
+        matches!(self, InternedValue::Array(_) | InternedValue::Object(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{parse_interned, parse_interned_with_config, InternedValue};
+    use crate::{parse, parse_with_config, NumberRepr, ParseConfig, ParseError, Token};
+    use crate::{TokenParseError, Value};
+
+    /// The same document with its keys copied out
+    fn to_value(value: InternedValue) -> Value {
+        match value {
+            InternedValue::Null => Value::Null,
+            InternedValue::Boolean(b) => Value::Boolean(b),
+            InternedValue::String(s) => Value::String(s),
+            InternedValue::Number(num) => Value::Number(num),
+            InternedValue::Array(array) => Value::Array(array.into_iter().map(to_value).collect()),
+            InternedValue::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), to_value(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn same_document_as_parse() {
+        let inputs = [
+            r#"{"a": [1, "x\ny", null, 2.5], "b": {"a": true, "c": {}}}"#,
+            r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, []]"#,
+            r#""top""#,
+            "[1, 2,]",
+        ];
+        for input in inputs {
+            let value = parse_interned(input).map(to_value);
+            assert_eq!(value, parse(input), "{input}");
+        }
+    }
+
+    #[test]
+    fn equal_keys_share_one_allocation() {
+        let value = parse_interned(r#"[{"id": 1, "x": {"id": 2}}, {"id": 3}]"#).unwrap();
+        let mut keys = Vec::new();
+        collect_keys(&value, "id", &mut keys);
+
+        assert_eq!(keys.len(), 3);
+        assert!(Arc::ptr_eq(&keys[0], &keys[1]));
+        assert!(Arc::ptr_eq(&keys[0], &keys[2]));
+    }
+
+    fn collect_keys(value: &InternedValue, name: &str, keys: &mut Vec<Arc<str>>) {
+        match value {
+            InternedValue::Array(array) => {
+                array.iter().for_each(|item| collect_keys(item, name, keys));
+            }
+            InternedValue::Object(object) => {
+                for (key, member) in object {
+                    if &**key == name {
+                        keys.push(key.clone());
+                    }
+                    collect_keys(member, name, keys);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn escaped_keys_are_unescaped() {
+        let value = parse_interned(r#"{"a\u0062": 1}"#).unwrap();
+        let InternedValue::Object(object) = value else {
+            panic!("expected an object");
+        };
+
+        assert_eq!(object.get("ab"), Some(&InternedValue::Number(1.into())));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_interned("  "), Err(ParseError::EmptyInput));
+        assert_eq!(
+            parse_interned("}"),
+            Err(TokenParseError::UnexpectedToken(Token::RightBrace).into())
+        );
+        assert_eq!(
+            parse_interned(r#"{"a":}"#),
+            Err(TokenParseError::ExpectedValue.into())
+        );
+        assert_eq!(
+            parse_interned(r#"{"a":"#),
+            Err(TokenParseError::UnexpectedEof.into())
+        );
+        assert_eq!(
+            parse_interned("[1 2]"),
+            Err(TokenParseError::ExpectedComma.into())
+        );
+        assert_eq!(
+            parse_interned("1 2"),
+            Err(TokenParseError::TrailingTokens.into())
+        );
+    }
+
+    #[test]
+    fn follows_the_config() {
+        let config = ParseConfig {
+            require_container: true,
+            preserve_raw_numbers: true,
+            allow_special_floats: true,
+            reject_duplicate_keys: true,
+            ..ParseConfig::default()
+        };
+        let inputs = [
+            r#"[1.50, NaN, {"a": -0}]"#,
+            r#"{"a": 1, "\u0061": 2}"#,
+            "1",
+            " [] ",
+        ];
+        for input in inputs {
+            let value = parse_interned_with_config(input, &config).map(to_value);
+            assert_eq!(value, parse_with_config(input, &config), "{input}");
+        }
+
+        let config = ParseConfig {
+            empty_as_null: true,
+            no_surrounding_whitespace: true,
+            ..ParseConfig::default()
+        };
+        assert_eq!(
+            parse_interned_with_config("", &config),
+            Ok(InternedValue::Null)
+        );
+        assert_eq!(
+            parse_interned_with_config(" 1", &config),
+            Err(ParseError::SurroundingWhitespace)
+        );
+        assert_eq!(
+            parse_interned_with_config(
+                "1.0",
+                &ParseConfig {
+                    preserve_raw_numbers: true,
+                    ..ParseConfig::default()
+                }
+            ),
+            Ok(InternedValue::Number(NumberRepr::Raw("1.0".into())))
+        );
+    }
+}
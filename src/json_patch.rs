@@ -0,0 +1,672 @@
+//! JSON Patch (RFC 6902): a list of operations that edit a document
+//! through JSON Pointers
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::pointer::array_index;
+use crate::{JsonPointer, PointerError, Value};
+
+/// Why `apply` rejected a patch
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// The patch document isn't an array of operations
+    NotAnArray,
+
+    /// The operation at `index` in the patch couldn't be applied
+    Operation { index: usize, kind: OperationError },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnArray => f.write_str("JSON patch must be an array of operations"),
+            Self::Operation { index, kind } => write!(f, "operation {index}: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Operation {
+                kind: OperationError::Pointer { error, .. },
+                ..
+            } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// What was wrong with a single patch operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum OperationError {
+    /// The operation isn't an object
+    NotAnObject,
+
+    /// The `op` member isn't one of the six operations
+    UnknownOp(String),
+
+    /// A required member is missing, or `path`/`from` isn't a string
+    MissingMember(&'static str),
+
+    /// The `path` or `from` pointer is malformed or doesn't fit the
+    /// document
+    Pointer {
+        member: &'static str,
+        error: PointerError,
+    },
+
+    /// A `move` whose `path` is inside the value at `from`
+    MoveIntoChild,
+
+    /// A `test` whose value didn't match the document
+    TestFailed,
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => f.write_str("operation is not an object"),
+            Self::UnknownOp(op) => write!(f, "unknown operation {op:?}"),
+            Self::MissingMember(member) => write!(f, "missing or invalid \"{member}\""),
+            Self::Pointer { member, error } => write!(f, "\"{member}\": {error}"),
+            Self::MoveIntoChild => f.write_str("cannot move a value into one of its children"),
+            Self::TestFailed => f.write_str("test failed"),
+        }
+    }
+}
+
+/// Applies the operations in `patch` to `target` in order
+///
+/// Either every operation succeeds or `target` is left untouched: the
+/// patch is applied to a copy that replaces `target` at the end.
+///
+/// ```
+/// use json_parser::{json, json_patch};
+///
+/// let mut doc = json!({"a": [1, 2]});
+/// let patch = json!([
+///     {"op": "add", "path": "/a/-", "value": 3},
+///     {"op": "move", "from": "/a/0", "path": "/first"},
+/// ]);
+/// json_patch::apply(&mut doc, &patch).unwrap();
+/// assert_eq!(doc, json!({"a": [2, 3], "first": 1}));
+/// ```
+pub fn apply(target: &mut Value, patch: &Value) -> Result<(), PatchError> {
+    let operations = patch.as_array().ok_or(PatchError::NotAnArray)?;
+    let mut document = target.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(&mut document, operation)
+            .map_err(|kind| PatchError::Operation { index, kind })?;
+    }
+    *target = document;
+    Ok(())
+}
+
+/// Builds a patch of `add`, `remove` and `replace` operations that turns
+/// `old` into `new` when passed to `apply`
+///
+/// Objects are diffed member by member, in key order. Arrays are compared
+/// position by position: elements at the same index are diffed, extra
+/// elements in `new` are appended and surplus elements of `old` are
+/// removed from the end. Anything else that differs is replaced whole.
+///
+/// ```
+/// use json_parser::{json, json_patch};
+///
+/// let patch = json_patch::diff(&json!({"a/b": [1], "c": 2}), &json!({"a/b": [1, 3]}));
+/// assert_eq!(
+///     patch,
+///     json!([
+///         {"op": "add", "path": "/a~1b/1", "value": 3},
+///         {"op": "remove", "path": "/c"},
+///     ])
+/// );
+/// ```
+pub fn diff(old: &Value, new: &Value) -> Value {
+    let mut operations = Vec::new();
+    diff_into(&mut operations, &JsonPointer::new(), old, new);
+    Value::Array(operations)
+}
+
+fn diff_into(operations: &mut Vec<Value>, path: &JsonPointer, old: &Value, new: &Value) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let child = path.clone().push_key(key);
+                match (old.get(key), new.get(key)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_into(operations, &child, old_value, new_value)
+                    }
+                    (Some(_), None) => operations.push(operation("remove", &child, None)),
+                    (None, new_value) => operations.push(operation("add", &child, new_value)),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for (index, (old_value, new_value)) in old.iter().zip(new).enumerate() {
+                diff_into(
+                    operations,
+                    &path.clone().push_index(index),
+                    old_value,
+                    new_value,
+                );
+            }
+            for (index, value) in new.iter().enumerate().skip(old.len()) {
+                operations.push(operation(
+                    "add",
+                    &path.clone().push_index(index),
+                    Some(value),
+                ));
+            }
+            // from the end, so the remaining indices stay put
+            for index in (new.len()..old.len()).rev() {
+                operations.push(operation("remove", &path.clone().push_index(index), None));
+            }
+        }
+        _ => operations.push(operation("replace", path, Some(new))),
+    }
+}
+
+fn operation(op: &str, path: &JsonPointer, value: Option<&Value>) -> Value {
+    let mut operation = HashMap::from([
+        ("op".to_string(), Value::from(op)),
+        ("path".to_string(), Value::from(path.to_string())),
+    ]);
+    if let Some(value) = value {
+        operation.insert("value".to_string(), value.clone());
+    }
+    Value::Object(operation)
+}
+
+fn apply_operation(document: &mut Value, operation: &Value) -> Result<(), OperationError> {
+    if !operation.is_object() {
+        return Err(OperationError::NotAnObject);
+    }
+    let op = operation["op"]
+        .as_str()
+        .ok_or(OperationError::MissingMember("op"))?;
+    let path = pointer_member(operation, "path")?;
+    match op {
+        "add" => add(document, path, value_member(operation)?.clone()),
+        "remove" => remove(document, &path).map(drop),
+        "replace" => {
+            let value = value_member(operation)?.clone();
+            let slot = document
+                .pointer_mut(&path)
+                .ok_or_else(|| not_found("path"))?;
+            *slot = value;
+            Ok(())
+        }
+        "move" => {
+            let from = pointer_member(operation, "from")?;
+            let into_child =
+                from.len() < path.len() && from.tokens().zip(path.tokens()).all(|(a, b)| a == b);
+            if into_child {
+                return Err(OperationError::MoveIntoChild);
+            }
+            let value = remove(document, &from).map_err(|e| with_member(e, "from"))?;
+            add(document, path, value)
+        }
+        "copy" => {
+            let from = pointer_member(operation, "from")?;
+            let value = document.pointer(&from).ok_or_else(|| not_found("from"))?;
+            add(document, path, value.clone())
+        }
+        "test" => {
+            let expected = value_member(operation)?;
+            match document.pointer(&path) {
+                Some(actual) if actual == expected => Ok(()),
+                _ => Err(OperationError::TestFailed),
+            }
+        }
+        other => Err(OperationError::UnknownOp(other.to_string())),
+    }
+}
+
+/// Sets the value at `path`: object members are added or replaced, array
+/// elements are inserted before the index, or appended for `-`
+fn add(document: &mut Value, mut path: JsonPointer, value: Value) -> Result<(), OperationError> {
+    let Some(last) = path.pop() else {
+        *document = value;
+        return Ok(());
+    };
+    let parent = document
+        .pointer_mut(&path)
+        .ok_or_else(|| not_found("path"))?;
+    match parent {
+        Value::Object(object) => {
+            object.insert(last, value);
+        }
+        Value::Array(array) => {
+            let index = match last.as_str() {
+                "-" => array.len(),
+                _ => array_index(&last).ok_or(pointer_error("path", PointerError::InvalidIndex))?,
+            };
+            if index > array.len() {
+                return Err(not_found("path"));
+            }
+            array.insert(index, value);
+        }
+        _ => return Err(pointer_error("path", PointerError::NotAContainer)),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut Value, path: &JsonPointer) -> Result<Value, OperationError> {
+    document
+        .pointer_remove(path)
+        .map_err(|error| pointer_error("path", error))
+}
+
+/// The pointer in the string member `member`
+fn pointer_member(operation: &Value, member: &'static str) -> Result<JsonPointer, OperationError> {
+    operation[member]
+        .as_str()
+        .ok_or(OperationError::MissingMember(member))?
+        .parse()
+        .map_err(|error| pointer_error(member, error))
+}
+
+/// The `value` member, which may be `null` but must be present
+fn value_member(operation: &Value) -> Result<&Value, OperationError> {
+    operation
+        .get("value")
+        .ok_or(OperationError::MissingMember("value"))
+}
+
+fn pointer_error(member: &'static str, error: PointerError) -> OperationError {
+    OperationError::Pointer { member, error }
+}
+
+fn not_found(member: &'static str) -> OperationError {
+    pointer_error(member, PointerError::NotFound)
+}
+
+/// A pointer error from the `path` of a helper re-attributed to `member`
+fn with_member(error: OperationError, member: &'static str) -> OperationError {
+    match error {
+        OperationError::Pointer { error, .. } => pointer_error(member, error),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, diff, OperationError, PatchError};
+    use crate::{json, PointerError, Value};
+
+    fn patched(mut document: Value, patch: Value) -> Result<Value, PatchError> {
+        apply(&mut document, &patch).map(|()| document)
+    }
+
+    fn operation_error(index: usize, kind: OperationError) -> Result<Value, PatchError> {
+        Err(PatchError::Operation { index, kind })
+    }
+
+    fn path_error(error: PointerError) -> OperationError {
+        OperationError::Pointer {
+            member: "path",
+            error,
+        }
+    }
+
+    /// The examples from RFC 6902 appendix A
+    #[test]
+    fn rfc_6902_examples() {
+        let cases = [
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux"}]),
+                json!({"baz": "qux", "foo": "bar"}),
+            ),
+            (
+                json!({"foo": ["bar", "baz"]}),
+                json!([{"op": "add", "path": "/foo/1", "value": "qux"}]),
+                json!({"foo": ["bar", "qux", "baz"]}),
+            ),
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "remove", "path": "/baz"}]),
+                json!({"foo": "bar"}),
+            ),
+            (
+                json!({"foo": ["bar", "qux", "baz"]}),
+                json!([{"op": "remove", "path": "/foo/1"}]),
+                json!({"foo": ["bar", "baz"]}),
+            ),
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "replace", "path": "/baz", "value": "boo"}]),
+                json!({"baz": "boo", "foo": "bar"}),
+            ),
+            (
+                json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+                json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]),
+                json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}),
+            ),
+            (
+                json!({"foo": ["all", "grass", "cows", "eat"]}),
+                json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}]),
+                json!({"foo": ["all", "cows", "eat", "grass"]}),
+            ),
+            (
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+                json!([
+                    {"op": "test", "path": "/baz", "value": "qux"},
+                    {"op": "test", "path": "/foo/1", "value": 2}
+                ]),
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+            ),
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}]),
+                json!({"foo": "bar", "child": {"grandchild": {}}}),
+            ),
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]),
+                json!({"foo": "bar", "baz": "qux"}),
+            ),
+            (
+                json!({"foo": ["bar"]}),
+                json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]),
+                json!({"foo": ["bar", ["abc", "def"]]}),
+            ),
+            (
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": 10}]),
+                json!({"/": 9, "~1": 10}),
+            ),
+        ];
+        for (document, patch, expected) in cases {
+            assert_eq!(patched(document, patch.clone()), Ok(expected), "{patch}");
+        }
+
+        assert_eq!(
+            patched(
+                json!({"baz": "qux"}),
+                json!([{"op": "test", "path": "/baz", "value": "bar"}])
+            ),
+            operation_error(0, OperationError::TestFailed)
+        );
+        assert_eq!(
+            patched(
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz/bat", "value": "qux"}])
+            ),
+            operation_error(0, path_error(PointerError::NotFound))
+        );
+        assert_eq!(
+            patched(
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": "10"}])
+            ),
+            operation_error(0, OperationError::TestFailed)
+        );
+    }
+
+    #[test]
+    fn array_indices_shift_between_operations() {
+        // each operation sees the array left by the one before
+        assert_eq!(
+            patched(
+                json!([0, 1, 2, 3]),
+                json!([
+                    {"op": "remove", "path": "/0"},
+                    {"op": "remove", "path": "/0"},
+                    {"op": "add", "path": "/1", "value": "x"},
+                    {"op": "add", "path": "/3", "value": "end"},
+                ])
+            ),
+            Ok(json!([2, "x", 3, "end"]))
+        );
+        // move within an array removes first, then inserts at the index
+        // of the shortened array
+        assert_eq!(
+            patched(
+                json!(["a", "b", "c"]),
+                json!([{"op": "move", "from": "/0", "path": "/2"}])
+            ),
+            Ok(json!(["b", "c", "a"]))
+        );
+        assert_eq!(
+            patched(
+                json!(["a", "b", "c"]),
+                json!([{"op": "move", "from": "/2", "path": "/0"}])
+            ),
+            Ok(json!(["c", "a", "b"]))
+        );
+        assert_eq!(
+            patched(
+                json!(["a"]),
+                json!([{"op": "copy", "from": "/0", "path": "/-"}])
+            ),
+            Ok(json!(["a", "a"]))
+        );
+        // past the end, `-` in non-add operations, and leading zeros
+        for (op, path) in [("add", "/2"), ("remove", "/1"), ("replace", "/-")] {
+            let patch = json!([{"op": op, "path": path, "value": 0}]);
+            assert_eq!(
+                patched(json!(["a"]), patch),
+                operation_error(0, path_error(PointerError::NotFound)),
+                "{op} {path}"
+            );
+        }
+        assert_eq!(
+            patched(
+                json!(["a"]),
+                json!([{"op": "add", "path": "/01", "value": 0}])
+            ),
+            operation_error(0, path_error(PointerError::InvalidIndex))
+        );
+    }
+
+    #[test]
+    fn move_and_copy() {
+        assert_eq!(
+            patched(
+                json!({"a": {"b": 1}}),
+                json!([{"op": "move", "from": "/a", "path": "/a/b/c"}])
+            ),
+            operation_error(0, OperationError::MoveIntoChild)
+        );
+        // a sibling sharing a prefix isn't a child
+        assert_eq!(
+            patched(
+                json!({"a": 1}),
+                json!([{"op": "move", "from": "/a", "path": "/ab"}])
+            ),
+            Ok(json!({"ab": 1}))
+        );
+        assert_eq!(
+            patched(
+                json!({"a": 1}),
+                json!([{"op": "move", "from": "/a", "path": "/a"}])
+            ),
+            Ok(json!({"a": 1}))
+        );
+        assert_eq!(
+            patched(
+                json!({"a": {"b": [1]}}),
+                json!([{"op": "copy", "from": "/a", "path": "/c"}])
+            ),
+            Ok(json!({"a": {"b": [1]}, "c": {"b": [1]}}))
+        );
+        assert_eq!(
+            patched(
+                json!({}),
+                json!([{"op": "move", "from": "/x", "path": "/y"}])
+            ),
+            operation_error(
+                0,
+                OperationError::Pointer {
+                    member: "from",
+                    error: PointerError::NotFound
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn whole_document_operations() {
+        assert_eq!(
+            patched(
+                json!({"a": 1}),
+                json!([{"op": "replace", "path": "", "value": [1]}])
+            ),
+            Ok(json!([1]))
+        );
+        assert_eq!(
+            patched(
+                json!({"a": 1}),
+                json!([{"op": "add", "path": "", "value": null}])
+            ),
+            Ok(json!(null))
+        );
+        assert_eq!(
+            patched(
+                json!({"a": 1}),
+                json!([{"op": "test", "path": "", "value": {"a": 1.0}}])
+            ),
+            Ok(json!({"a": 1}))
+        );
+    }
+
+    #[test]
+    fn failed_patch_leaves_target_untouched() {
+        let mut document = json!({"a": [1, 2], "b": "keep"});
+        let patch = json!([
+            {"op": "remove", "path": "/b"},
+            {"op": "add", "path": "/a/0", "value": 0},
+            {"op": "test", "path": "/a/0", "value": 1},
+        ]);
+        assert_eq!(
+            apply(&mut document, &patch),
+            Err(PatchError::Operation {
+                index: 2,
+                kind: OperationError::TestFailed
+            })
+        );
+        assert_eq!(document, json!({"a": [1, 2], "b": "keep"}));
+    }
+
+    #[test]
+    fn malformed_operations() {
+        let cases = [
+            (json!([1]), OperationError::NotAnObject),
+            (json!([{"path": "/a"}]), OperationError::MissingMember("op")),
+            (
+                json!([{"op": "frobnicate", "path": "/a"}]),
+                OperationError::UnknownOp("frobnicate".to_string()),
+            ),
+            (
+                json!([{"op": "add"}]),
+                OperationError::MissingMember("path"),
+            ),
+            (
+                json!([{"op": "add", "path": "/a"}]),
+                OperationError::MissingMember("value"),
+            ),
+            (
+                json!([{"op": "copy", "path": "/a"}]),
+                OperationError::MissingMember("from"),
+            ),
+            (
+                json!([{"op": "remove", "path": "a"}]),
+                path_error(PointerError::Malformed),
+            ),
+        ];
+        for (patch, kind) in cases {
+            assert_eq!(
+                patched(json!({"a": 1}), patch.clone()),
+                operation_error(0, kind),
+                "{patch}"
+            );
+        }
+        assert_eq!(
+            patched(json!({}), json!({"op": "add"})),
+            Err(PatchError::NotAnArray)
+        );
+        assert_eq!(
+            PatchError::Operation {
+                index: 3,
+                kind: path_error(PointerError::NotFound)
+            }
+            .to_string(),
+            "operation 3: \"path\": JSON pointer refers to a missing value"
+        );
+    }
+
+    #[test]
+    fn diff_emits_minimal_operations() {
+        let old = json!({"a": {"b": 1, "c": [1, 2, 3]}, "d": "x", "e~f": null});
+        let new = json!({"a": {"b": 2, "c": [1, 4]}, "d": "x", "g": [true]});
+        assert_eq!(
+            diff(&old, &new),
+            json!([
+                {"op": "replace", "path": "/a/b", "value": 2},
+                {"op": "replace", "path": "/a/c/1", "value": 4},
+                {"op": "remove", "path": "/a/c/2"},
+                {"op": "remove", "path": "/e~0f"},
+                {"op": "add", "path": "/g", "value": [true]},
+            ])
+        );
+        assert_eq!(diff(&old, &old), json!([]));
+        assert_eq!(
+            diff(&json!([1]), &json!({"a": 1})),
+            json!([{"op": "replace", "path": "", "value": {"a": 1}}])
+        );
+    }
+
+    /// Small xorshift64 generator, fixed seed so failures are reproducible
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+
+        fn document(&mut self, depth: u32) -> Value {
+            // keys with `/` and `~` exercise pointer escaping
+            const KEYS: [&str; 5] = ["a", "b", "a/b", "~1", ""];
+            let kinds = if depth == 0 { 4 } else { 6 };
+            match self.next(kinds) {
+                0 => Value::Null,
+                1 => Value::Boolean(self.next(2) == 0),
+                2 => Value::from(self.next(4) as i64),
+                3 => Value::from(KEYS[self.next(5) as usize]),
+                4 => (0..self.next(4))
+                    .map(|_| self.document(depth - 1))
+                    .collect(),
+                _ => (0..self.next(4))
+                    .map(|_| (KEYS[self.next(5) as usize], self.document(depth - 1)))
+                    .collect(),
+            }
+        }
+    }
+
+    #[test]
+    fn diff_round_trips_random_documents() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        for _ in 0..2000 {
+            let old = random.document(3);
+            let new = random.document(3);
+            let patch = diff(&old, &new);
+            assert_eq!(
+                patched(old.clone(), patch.clone()),
+                Ok(new.clone()),
+                "{old} -> {new} via {patch}"
+            );
+            assert_eq!(diff(&new, &new), json!([]));
+        }
+    }
+}
@@ -0,0 +1,684 @@
+mod filter;
+
+use std::fmt;
+
+use filter::FilterExpr;
+
+use crate::Value;
+
+/// Why a JSONPath expression couldn't be parsed
+///
+/// Positions are character offsets into the expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonPathError {
+    /// The expression doesn't start with `$`
+    MissingRoot,
+    /// A character that can't start a segment, such as the `x` in `$x`
+    UnexpectedChar { position: usize },
+    /// A `[` with no matching `]`
+    UnclosedBracket { position: usize },
+    /// A name, index, slice, union or filter that isn't valid; `position`
+    /// is where the selector starts
+    InvalidSelector { position: usize },
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRoot => f.write_str("JSONPath must start with `$`"),
+            Self::UnexpectedChar { position } => {
+                write!(f, "unexpected character at position {position}")
+            }
+            Self::UnclosedBracket { position } => {
+                write!(f, "unclosed bracket at position {position}")
+            }
+            Self::InvalidSelector { position } => {
+                write!(f, "invalid selector at position {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonPathError {}
+
+impl Value {
+    /// Selects every node matching a JSONPath expression such as
+    /// `$.store.book[*].author`
+    ///
+    /// Supported syntax: the root `$`, child access `.key` or `['key']`,
+    /// array indices `[n]` (negative indices count from the end), unions
+    /// `[0,2]` or `['a','b']`, wildcards `*` / `[*]`, recursive descent
+    /// `..`, slices `[start:end]` (negative bounds count from the end) and
+    /// filters such as `[?(@.price < 10 && @.category == 'fiction')]`.
+    /// Filters support `<`, `<=`, `>`, `>=`, `==`, `!=`, `&&`, `||`,
+    /// parentheses and bare `@.key` existence tests.
+    ///
+    /// Object members are visited in sorted key order so results are
+    /// deterministic. An invalid expression selects nothing; use
+    /// [`Value::try_query`] to find out why.
+    pub fn query(&self, path: &str) -> Vec<&Value> {
+        self.try_query(path).unwrap_or_default()
+    }
+
+    /// Like [`Value::query`], but reports an invalid expression
+    ///
+    /// ```
+    /// use json_parser::{json, JsonPathError};
+    ///
+    /// let value = json!({"a": [1, 2, 3]});
+    /// assert_eq!(value.try_query("$.a[0,-1]"), Ok(vec![&json!(1), &json!(3)]));
+    /// assert_eq!(
+    ///     value.try_query("$.a[x]"),
+    ///     Err(JsonPathError::InvalidSelector { position: 4 })
+    /// );
+    /// ```
+    pub fn try_query(&self, path: &str) -> Result<Vec<&Value>, JsonPathError> {
+        let segments = parse_path(path)?;
+
+        let mut nodes = vec![self];
+        for segment in &segments {
+            let mut next = Vec::new();
+            match segment {
+                Segment::Child(selector) => {
+                    for node in nodes {
+                        select(node, selector, &mut next);
+                    }
+                }
+                Segment::Descendant(selector) => {
+                    for node in nodes {
+                        let mut descendants = Vec::new();
+                        collect_descendants(node, &mut descendants);
+                        for descendant in descendants {
+                            select(descendant, selector, &mut next);
+                        }
+                    }
+                }
+            }
+            nodes = next;
+        }
+        Ok(nodes)
+    }
+
+    /// Like [`Value::query`], but clones the matches so they outlive `self`
+    pub fn query_owned(&self, path: &str) -> Vec<Value> {
+        self.query(path).into_iter().cloned().collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    /// `.name`, `[...]`
+    Child(Selector),
+    /// `..name`, `..[...]`
+    Descendant(Selector),
+}
+
+#[derive(Debug, PartialEq)]
+enum Selector {
+    Name(String),
+    /// Negative indices count from the end of the array
+    Index(i64),
+    Wildcard,
+    Slice(Option<i64>, Option<i64>),
+    /// `[a,b,...]`, the matches of each selector in turn
+    Union(Vec<Selector>),
+    Filter(Box<FilterExpr>),
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, JsonPathError> {
+    let chars: Vec<char> = path.chars().collect();
+    if chars.first() != Some(&'$') {
+        return Err(JsonPathError::MissingRoot);
+    }
+
+    let mut segments = Vec::new();
+    let mut index = 1;
+    while index < chars.len() {
+        let segment = match chars[index] {
+            '.' if chars.get(index + 1) == Some(&'.') => {
+                index += 2;
+                Segment::Descendant(parse_selector(&chars, &mut index)?)
+            }
+            '.' => {
+                index += 1;
+                if chars.get(index) == Some(&'[') {
+                    return Err(JsonPathError::UnexpectedChar { position: index });
+                }
+                Segment::Child(parse_selector(&chars, &mut index)?)
+            }
+            '[' => Segment::Child(parse_selector(&chars, &mut index)?),
+            _ => return Err(JsonPathError::UnexpectedChar { position: index }),
+        };
+        segments.push(segment);
+    }
+    Ok(segments)
+}
+
+/// Parses a dotted name, `*` or a bracketed selector starting at `index`
+fn parse_selector(chars: &[char], index: &mut usize) -> Result<Selector, JsonPathError> {
+    match chars.get(*index) {
+        Some('*') => {
+            *index += 1;
+            Ok(Selector::Wildcard)
+        }
+        Some('[') => parse_bracket(chars, index),
+        _ => {
+            let start = *index;
+            while *index < chars.len() && !matches!(chars[*index], '.' | '[') {
+                *index += 1;
+            }
+            if start == *index {
+                return Err(JsonPathError::InvalidSelector { position: start });
+            }
+            Ok(Selector::Name(chars[start..*index].iter().collect()))
+        }
+    }
+}
+
+fn parse_bracket(chars: &[char], index: &mut usize) -> Result<Selector, JsonPathError> {
+    let open = *index;
+    let start = open + 1;
+    let end = start
+        + closing_bracket(&chars[start..])
+            .ok_or(JsonPathError::UnclosedBracket { position: open })?;
+    *index = end + 1;
+
+    let inner: String = chars[start..end].iter().collect();
+    let invalid = JsonPathError::InvalidSelector { position: start };
+
+    if let Some(filter) = inner.trim().strip_prefix('?') {
+        return FilterExpr::parse(filter)
+            .map(|expr| Selector::Filter(Box::new(expr)))
+            .ok_or(invalid);
+    }
+    let mut members = split_union(&inner)
+        .into_iter()
+        .map(|member| parse_bracket_member(member.trim()).ok_or(invalid.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if members.len() == 1 {
+        Ok(members.remove(0))
+    } else {
+        Ok(Selector::Union(members))
+    }
+}
+
+/// One comma separated member of a bracket: a quoted name, an index, a
+/// slice or `*`
+fn parse_bracket_member(member: &str) -> Option<Selector> {
+    if member == "*" {
+        return Some(Selector::Wildcard);
+    }
+    for quote in ['\'', '"'] {
+        if let Some(name) = member
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return Some(Selector::Name(name.to_string()));
+        }
+    }
+    if let Some((from, to)) = member.split_once(':') {
+        let bound = |s: &str| -> Option<Option<i64>> {
+            let s = s.trim();
+            if s.is_empty() {
+                Some(None)
+            } else {
+                s.parse().ok().map(Some)
+            }
+        };
+        return Some(Selector::Slice(bound(from)?, bound(to)?));
+    }
+    member.parse().ok().map(Selector::Index)
+}
+
+/// Splits the contents of a bracket on the commas outside quoted names
+fn split_union(inner: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ',') => {
+                members.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    members.push(&inner[start..]);
+    members
+}
+
+/// Position of the `]` closing a bracket whose contents start `chars`,
+/// skipping over quoted strings and nested brackets
+fn closing_bracket(chars: &[char]) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in chars.iter().enumerate() {
+        match (quote, c) {
+            (Some(q), c) if *c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(*c),
+            (None, '[') => depth += 1,
+            (None, ']') if depth == 0 => return Some(i),
+            (None, ']') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The element at `index`, counting from the end when it's negative
+fn array_element(array: &[Value], index: i64) -> Option<&Value> {
+    let index = if index < 0 {
+        array.len().checked_sub(index.unsigned_abs() as usize)?
+    } else {
+        index as usize
+    };
+    array.get(index)
+}
+
+/// Appends the children of `node` picked by `selector` to `output`
+fn select<'v>(node: &'v Value, selector: &Selector, output: &mut Vec<&'v Value>) {
+    match (selector, node) {
+        (Selector::Name(name), Value::Object(object)) => output.extend(object.get(name)),
+        (Selector::Index(i), Value::Array(array)) => output.extend(array_element(array, *i)),
+        (Selector::Wildcard, Value::Array(array)) => output.extend(array),
+        (Selector::Wildcard, Value::Object(_)) => output.extend(object_values(node)),
+        (Selector::Union(members), _) => {
+            for member in members {
+                select(node, member, output);
+            }
+        }
+        (Selector::Filter(expr), Value::Array(array)) => {
+            output.extend(array.iter().filter(|child| expr.matches(child)))
+        }
+        (Selector::Filter(expr), Value::Object(_)) => output.extend(
+            object_values(node)
+                .into_iter()
+                .filter(|child| expr.matches(child)),
+        ),
+        (Selector::Slice(from, to), Value::Array(array)) => {
+            let len = array.len() as i64;
+            let clamp = |bound: i64| {
+                let bound = if bound < 0 { len + bound } else { bound };
+                bound.clamp(0, len) as usize
+            };
+            let from = clamp(from.unwrap_or(0));
+            let to = clamp(to.unwrap_or(len));
+            if from < to {
+                output.extend(&array[from..to]);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `node` followed by all of its descendants, in document order
+fn collect_descendants<'v>(node: &'v Value, output: &mut Vec<&'v Value>) {
+    output.push(node);
+    match node {
+        Value::Array(array) => {
+            for child in array {
+                collect_descendants(child, output);
+            }
+        }
+        Value::Object(_) => {
+            for child in object_values(node) {
+                collect_descendants(child, output);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Values of an object ordered by key
+fn object_values(node: &Value) -> Vec<&Value> {
+    let Value::Object(object) = node else {
+        return Vec::new();
+    };
+    let mut entries: Vec<_> = object.iter().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries.into_iter().map(|(_, value)| value).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, JsonPathError, Value};
+
+    fn bookstore() -> Value {
+        parse(
+            r#"{ "store": {
+                "book": [
+                  { "category": "reference",
+                    "author": "Nigel Rees",
+                    "title": "Sayings of the Century",
+                    "price": 8.95
+                  },
+                  { "category": "fiction",
+                    "author": "Evelyn Waugh",
+                    "title": "Sword of Honour",
+                    "price": 12.99
+                  },
+                  { "category": "fiction",
+                    "author": "Herman Melville",
+                    "title": "Moby Dick",
+                    "isbn": "0-553-21311-3",
+                    "price": 8.99
+                  },
+                  { "category": "fiction",
+                    "author": "J. R. R. Tolkien",
+                    "title": "The Lord of the Rings",
+                    "isbn": "0-395-19395-8",
+                    "price": 22.99
+                  }
+                ],
+                "bicycle": {
+                  "color": "red",
+                  "price": 19.95
+                }
+              }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn strings(values: Vec<&Value>) -> Vec<&str> {
+        values
+            .into_iter()
+            .map(|v| match v {
+                Value::String(s) => s.as_str(),
+                other => panic!("expected a string, got {other:?}"),
+            })
+            .collect()
+    }
+
+    fn titles(values: Vec<&Value>) -> Vec<&str> {
+        values
+            .into_iter()
+            .map(|v| match v {
+                Value::Object(book) => match &book["title"] {
+                    Value::String(s) => s.as_str(),
+                    other => panic!("expected a string, got {other:?}"),
+                },
+                other => panic!("expected a book, got {other:?}"),
+            })
+            .collect()
+    }
+
+    const AUTHORS: [&str; 4] = [
+        "Nigel Rees",
+        "Evelyn Waugh",
+        "Herman Melville",
+        "J. R. R. Tolkien",
+    ];
+
+    #[test]
+    fn authors_of_all_books() {
+        let store = bookstore();
+
+        assert_eq!(strings(store.query("$.store.book[*].author")), AUTHORS);
+    }
+
+    #[test]
+    fn all_authors() {
+        let store = bookstore();
+
+        assert_eq!(strings(store.query("$..author")), AUTHORS);
+    }
+
+    #[test]
+    fn all_things_in_store() {
+        let store = bookstore();
+        let things = store.query("$.store.*");
+
+        assert_eq!(things.len(), 2);
+        assert!(matches!(things[0], Value::Object(_)));
+        assert!(matches!(things[1], Value::Array(_)));
+    }
+
+    #[test]
+    fn price_of_everything_in_store() {
+        let store = bookstore();
+        let prices = store.query("$.store..price");
+
+        assert_eq!(
+            prices,
+            [19.95, 8.95, 12.99, 8.99, 22.99]
+                .map(Value::num)
+                .iter()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn third_book() {
+        let store = bookstore();
+
+        assert_eq!(titles(store.query("$..book[2]")), ["Moby Dick"]);
+    }
+
+    #[test]
+    fn last_book_by_slice() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[-1:]")),
+            ["The Lord of the Rings"]
+        );
+    }
+
+    #[test]
+    fn first_two_books() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[:2]")),
+            ["Sayings of the Century", "Sword of Honour"]
+        );
+        assert_eq!(
+            titles(store.query("$..book[0:2]")),
+            ["Sayings of the Century", "Sword of Honour"]
+        );
+    }
+
+    #[test]
+    fn all_members_of_document() {
+        let store = bookstore();
+
+        // store, bicycle + 2 fields, book array, 4 books with 4 or 5 fields
+        assert_eq!(store.query("$..*").len(), 1 + 3 + 1 + 4 + 18);
+    }
+
+    #[test]
+    fn bracket_name_and_root() {
+        let store = bookstore();
+
+        assert_eq!(
+            store.query("$['store']['bicycle']['color']"),
+            [&Value::String("red".to_string())]
+        );
+        assert_eq!(store.query("$"), [&store]);
+    }
+
+    #[test]
+    fn filter_price_below_threshold() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[?(@.price < 10)]")),
+            ["Sayings of the Century", "Moby Dick"]
+        );
+    }
+
+    #[test]
+    fn filter_price_above_threshold() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$.store.book[?(@.price > 12.99)]")),
+            ["The Lord of the Rings"]
+        );
+        assert_eq!(
+            titles(store.query("$.store.book[?(@.price >= 12.99)]")),
+            ["Sword of Honour", "The Lord of the Rings"]
+        );
+    }
+
+    #[test]
+    fn filter_string_equality() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[?(@.category == 'reference')]")),
+            ["Sayings of the Century"]
+        );
+        assert_eq!(
+            strings(store.query(r#"$..book[?(@.author != "Nigel Rees")].author"#)),
+            &AUTHORS[1..]
+        );
+    }
+
+    #[test]
+    fn filter_logical_operators() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[?(@.category == 'fiction' && @.price < 10)]")),
+            ["Moby Dick"]
+        );
+        assert_eq!(
+            titles(store.query("$..book[?(@.price < 9 || @.price > 20)]")),
+            [
+                "Sayings of the Century",
+                "Moby Dick",
+                "The Lord of the Rings"
+            ]
+        );
+        assert_eq!(
+            titles(
+                store.query("$..book[?((@.price < 9 || @.price > 20) && @.category == 'fiction')]")
+            ),
+            ["Moby Dick", "The Lord of the Rings"]
+        );
+    }
+
+    #[test]
+    fn filter_existence() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[?(@.isbn)]")),
+            ["Moby Dick", "The Lord of the Rings"]
+        );
+    }
+
+    #[test]
+    fn filter_on_nested_path() {
+        let value = parse(r#"[{"a":{"b":[1,5]}},{"a":{"b":[9]}},{"a":1}]"#).unwrap();
+
+        assert_eq!(value.query("$[?(@.a.b[0] > 2)]").len(), 1);
+        assert_eq!(value.query("$[?(@['a']['b'][1] == 5)]").len(), 1);
+    }
+
+    #[test]
+    fn invalid_filters_select_nothing() {
+        let store = bookstore();
+
+        assert!(store.query("$..book[?(@.price <)]").is_empty());
+        assert!(store.query("$..book[?(@.price < 10]").is_empty());
+        assert!(store.query("$..book[?(10)]").is_empty());
+    }
+
+    #[test]
+    fn missing_paths_select_nothing() {
+        let store = bookstore();
+
+        assert!(store.query("$.store.book[10]").is_empty());
+        assert!(store.query("$.nothing.here").is_empty());
+        assert!(store.query("$.store.bicycle[0]").is_empty());
+    }
+
+    #[test]
+    fn invalid_expressions_select_nothing() {
+        let store = bookstore();
+
+        assert!(store.query("store.book").is_empty());
+        assert!(store.query("$.store.book[").is_empty());
+        assert!(store.query("$.store.book[x]").is_empty());
+    }
+
+    #[test]
+    fn negative_index() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[-1]")),
+            ["The Lord of the Rings"]
+        );
+        assert_eq!(
+            titles(store.query("$..book[-4]")),
+            ["Sayings of the Century"]
+        );
+        assert!(store.query("$..book[-5]").is_empty());
+    }
+
+    #[test]
+    fn union_of_indices_and_names() {
+        let store = bookstore();
+
+        assert_eq!(
+            titles(store.query("$..book[0,2]")),
+            ["Sayings of the Century", "Moby Dick"]
+        );
+        // members come back in the order the union lists them
+        assert_eq!(
+            titles(store.query("$..book[-1, 0]")),
+            ["The Lord of the Rings", "Sayings of the Century"]
+        );
+        assert_eq!(
+            strings(store.query("$.store.book[0]['author','title']")),
+            ["Nigel Rees", "Sayings of the Century"]
+        );
+    }
+
+    #[test]
+    fn owned_results_outlive_the_document() {
+        let authors = bookstore().query_owned("$..author");
+
+        assert_eq!(authors.len(), 4);
+        assert_eq!(authors[3], Value::String("J. R. R. Tolkien".to_string()));
+    }
+
+    #[test]
+    fn parse_errors_report_position() {
+        let store = bookstore();
+
+        assert_eq!(
+            store.try_query("store.book"),
+            Err(JsonPathError::MissingRoot)
+        );
+        assert_eq!(
+            store.try_query("$.store.book["),
+            Err(JsonPathError::UnclosedBracket { position: 12 })
+        );
+        assert_eq!(
+            store.try_query("$.store.book[x]"),
+            Err(JsonPathError::InvalidSelector { position: 13 })
+        );
+        assert_eq!(
+            store.try_query("$.store.book[0,]"),
+            Err(JsonPathError::InvalidSelector { position: 13 })
+        );
+        assert_eq!(
+            store.try_query("$.store.."),
+            Err(JsonPathError::InvalidSelector { position: 9 })
+        );
+        assert_eq!(
+            store.try_query("$[0]x"),
+            Err(JsonPathError::UnexpectedChar { position: 4 })
+        );
+    }
+}
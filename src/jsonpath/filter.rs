@@ -0,0 +1,251 @@
+//! Filter expressions such as `?(@.price < 10 && @.category == 'fiction')`
+
+use std::cmp::Ordering;
+
+use super::{array_element, Selector};
+use crate::{NumberRepr, Value};
+
+#[derive(Debug, PartialEq)]
+pub(super) enum FilterExpr {
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Compare(Operand, CompareOp, Operand),
+    /// A bare `@.key`, true when the path exists
+    Exists(Operand),
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum Operand {
+    /// `@` followed by `.key`, `['key']` or `[n]` steps
+    Current(Vec<Selector>),
+    Literal(Value),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(super) enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl FilterExpr {
+    /// Parses the text between `[?` and `]`
+    pub(super) fn parse(input: &str) -> Option<FilterExpr> {
+        let mut parser = FilterParser {
+            chars: input.chars().collect(),
+            index: 0,
+        };
+        let expr = parser.parse_or()?;
+        parser.skip_whitespace();
+        if parser.index != parser.chars.len() {
+            return None;
+        }
+        Some(expr)
+    }
+
+    /// Whether `node` (the `@` of the expression) passes the filter
+    ///
+    /// A comparison involving a missing path is false, as are `<`, `<=`,
+    /// `>` and `>=` between anything other than two numbers or two strings.
+    pub(super) fn matches(&self, node: &Value) -> bool {
+        match self {
+            FilterExpr::Or(a, b) => a.matches(node) || b.matches(node),
+            FilterExpr::And(a, b) => a.matches(node) && b.matches(node),
+            FilterExpr::Exists(operand) => operand.resolve(node).is_some(),
+            FilterExpr::Compare(left, op, right) => {
+                let (Some(left), Some(right)) = (left.resolve(node), right.resolve(node)) else {
+                    return false;
+                };
+                match op {
+                    CompareOp::Eq => left == right,
+                    CompareOp::Ne => left != right,
+                    _ => {
+                        let ordering = match (left, right) {
+                            (Value::Number(a), Value::Number(b)) => {
+                                a.as_f64().partial_cmp(&b.as_f64())
+                            }
+                            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                            _ => None,
+                        };
+                        match ordering {
+                            Some(Ordering::Less) => matches!(op, CompareOp::Lt | CompareOp::Le),
+                            Some(Ordering::Equal) => matches!(op, CompareOp::Le | CompareOp::Ge),
+                            Some(Ordering::Greater) => matches!(op, CompareOp::Gt | CompareOp::Ge),
+                            None => false,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Operand {
+    fn resolve<'v>(&'v self, node: &'v Value) -> Option<&'v Value> {
+        match self {
+            Operand::Literal(value) => Some(value),
+            Operand::Current(steps) => {
+                steps
+                    .iter()
+                    .try_fold(node, |node, step| match (step, node) {
+                        (Selector::Name(name), Value::Object(object)) => object.get(name),
+                        (Selector::Index(i), Value::Array(array)) => array_element(array, *i),
+                        _ => None,
+                    })
+            }
+        }
+    }
+}
+
+struct FilterParser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl FilterParser {
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .get(self.index)
+            .is_some_and(|c| c.is_whitespace())
+        {
+            self.index += 1;
+        }
+    }
+
+    /// Skips whitespace and consumes `token` if it comes next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let matches = token
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.index + i) == Some(&c));
+        if matches {
+            self.index += token.chars().count();
+        }
+        matches
+    }
+
+    fn parse_or(&mut self) -> Option<FilterExpr> {
+        let mut expr = self.parse_and()?;
+        while self.eat("||") {
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Some(expr)
+    }
+
+    fn parse_and(&mut self) -> Option<FilterExpr> {
+        let mut expr = self.parse_comparison()?;
+        while self.eat("&&") {
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_comparison()?));
+        }
+        Some(expr)
+    }
+
+    fn parse_comparison(&mut self) -> Option<FilterExpr> {
+        if self.eat("(") {
+            let expr = self.parse_or()?;
+            return self.eat(")").then_some(expr);
+        }
+
+        let left = self.parse_operand()?;
+        // two-character operators first so `<=` isn't read as `<`
+        let ops = [
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ];
+        for (token, op) in ops {
+            if self.eat(token) {
+                let right = self.parse_operand()?;
+                return Some(FilterExpr::Compare(left, op, right));
+            }
+        }
+        match left {
+            Operand::Current(_) => Some(FilterExpr::Exists(left)),
+            Operand::Literal(_) => None,
+        }
+    }
+
+    fn parse_operand(&mut self) -> Option<Operand> {
+        self.skip_whitespace();
+        match self.chars.get(self.index)? {
+            '@' => {
+                self.index += 1;
+                let mut steps = Vec::new();
+                loop {
+                    match self.chars.get(self.index) {
+                        Some('.') => {
+                            self.index += 1;
+                            let start = self.index;
+                            while self
+                                .chars
+                                .get(self.index)
+                                .is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+                            {
+                                self.index += 1;
+                            }
+                            if start == self.index {
+                                return None;
+                            }
+                            steps.push(Selector::Name(
+                                self.chars[start..self.index].iter().collect(),
+                            ));
+                        }
+                        Some('[') => {
+                            self.index += 1;
+                            let step = match self.parse_operand()? {
+                                Operand::Literal(Value::String(name)) => Selector::Name(name),
+                                Operand::Literal(Value::Number(n)) if n.as_f64().fract() == 0.0 => {
+                                    Selector::Index(n.as_f64() as i64)
+                                }
+                                _ => return None,
+                            };
+                            if !self.eat("]") {
+                                return None;
+                            }
+                            steps.push(step);
+                        }
+                        _ => return Some(Operand::Current(steps)),
+                    }
+                }
+            }
+            quote @ ('\'' | '"') => {
+                let quote = *quote;
+                self.index += 1;
+                let start = self.index;
+                let len = self.chars[start..].iter().position(|c| *c == quote)?;
+                self.index = start + len + 1;
+                let s = self.chars[start..start + len].iter().collect();
+                Some(Operand::Literal(Value::String(s)))
+            }
+            _ => {
+                for (word, value) in [
+                    ("true", Value::Boolean(true)),
+                    ("false", Value::Boolean(false)),
+                    ("null", Value::Null),
+                ] {
+                    if self.eat(word) {
+                        return Some(Operand::Literal(value));
+                    }
+                }
+                let start = self.index;
+                while self
+                    .chars
+                    .get(self.index)
+                    .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+                {
+                    self.index += 1;
+                }
+                let number: String = self.chars[start..self.index].iter().collect();
+                NumberRepr::from_literal(&number).map(|n| Operand::Literal(Value::Number(n)))
+            }
+        }
+    }
+}
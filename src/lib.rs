@@ -1,11 +1,74 @@
+// library code reports problems through its return values, never on stdout/stderr
+#![deny(clippy::print_stdout, clippy::print_stderr)]
+
+mod builder;
+mod canonical;
+mod checksum;
+mod cmp;
+mod convert;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "decimal")]
+mod decimal;
+mod flatten;
+mod gron;
+mod index;
+mod interned;
+pub mod json_patch;
+mod jsonpath;
+mod macros;
+mod merge;
+mod number;
+mod ops;
 mod parse;
+mod partial_eq;
+mod path;
+mod pointer;
+mod query;
+#[cfg(feature = "tokio")]
+mod reader;
+mod report;
+mod schema;
+mod serialize;
 mod tokenize;
-use std::collections::HashMap;
+mod trivia;
+use std::collections::{BTreeMap, HashMap};
+
+pub use builder::{ArrayBuilder, ObjectBuilder};
+pub use convert::{AccessError, ConversionError};
+#[cfg(feature = "serde")]
+pub use de::{from_str_as, DeserializeError};
+pub use flatten::{flatten, flatten_with, unflatten, unflatten_with};
+pub use gron::{from_gron, to_gron};
+pub use interned::{parse_interned, parse_interned_with_config, InternedValue};
+pub use jsonpath::JsonPathError;
+pub use merge::{merge_patch, merge_patch_diff, merged, ArrayMergeStrategy};
+pub use number::NumberRepr;
+pub use parse::{
+    from_bytes, parse, parse_array, parse_best_effort, parse_object, parse_prefix,
+    parse_recovering, parse_with_config, unescape_string, ParseConfig, ParseError, Parser,
+    TokenParseError,
+};
+pub use path::PathError;
+pub use pointer::{AsPointer, JsonPointer, PointerError, ReferenceToken};
+#[cfg(feature = "tokio")]
+pub use reader::from_async_reader;
+pub use report::parse_with_offset;
+pub use schema::ValidationError;
+pub use serialize::{
+    escape_string, to_string, to_string_colored, to_string_pretty, to_string_with_formatter,
+    to_string_with_options, to_writer, to_writer_pretty, to_writer_with_formatter,
+    to_writer_with_options, CharEscape, ColoredFormatter, CompactFormatter, Formatter,
+    NonFiniteNumbers, PrettyFormatter, SerializeError, SerializeOptions, Theme,
+};
+pub use tokenize::{Token, TokenizeError, Tokenizer};
+pub use trivia::{parse_with_trivia, Trivia};
 
 /// Representation of a Json value
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum Value {
-    /// literal characters `null`
+    /// literal characters `null`, also the default value
+    #[default]
     Null,
 
     /// literal characters `true` or `false`
@@ -14,8 +77,8 @@ pub enum Value {
     /// characters within double quotes "..."
     String(String),
 
-    /// numbers stored as a 64-bit floating point
-    Number(f64),
+    /// numbers, as an exact integer, a 64-bit float or the raw literal
+    Number(NumberRepr),
 
     /// Zero to many JSON values
     Array(Vec<Value>),
@@ -23,3 +86,1059 @@ pub enum Value {
     /// String keys with JSON values
     Object(HashMap<String, Value>),
 }
+
+impl Value {
+    /// `null`
+    ///
+    /// ```
+    /// use json_parser::Value;
+    ///
+    /// assert_eq!(Value::null(), Value::Null);
+    /// ```
+    pub fn null() -> Value {
+        Value::Null
+    }
+
+    /// A boolean
+    ///
+    /// ```
+    /// use json_parser::Value;
+    ///
+    /// assert_eq!(Value::bool(true), Value::Boolean(true));
+    /// ```
+    pub fn bool(b: bool) -> Value {
+        Value::Boolean(b)
+    }
+
+    /// A number
+    ///
+    /// ```
+    /// use json_parser::{NumberRepr, Value};
+    ///
+    /// assert_eq!(Value::num(1.5), Value::Number(NumberRepr::Float(1.5)));
+    /// ```
+    pub fn num(n: f64) -> Value {
+        Value::Number(NumberRepr::Float(n))
+    }
+
+    /// A string, from anything that converts into a `String`
+    ///
+    /// ```
+    /// use json_parser::Value;
+    ///
+    /// assert_eq!(Value::str("hello"), Value::String("hello".to_string()));
+    /// ```
+    pub fn str(s: impl Into<String>) -> Value {
+        Value::String(s.into())
+    }
+
+    /// An array of `items`
+    ///
+    /// ```
+    /// use json_parser::Value;
+    ///
+    /// assert_eq!(
+    ///     Value::array([Value::num(1.0), Value::null()]),
+    ///     Value::Array(vec![Value::num(1.0), Value::Null])
+    /// );
+    /// ```
+    pub fn array<T: Into<Value>>(items: impl IntoIterator<Item = T>) -> Value {
+        items.into_iter().collect()
+    }
+
+    /// An object of `entries`; a repeated key keeps the last value
+    ///
+    /// ```
+    /// use json_parser::Value;
+    ///
+    /// let value = Value::object([("a", Value::bool(true))]);
+    /// assert_eq!(value["a"], true);
+    /// ```
+    pub fn object<K: Into<String>, V: Into<Value>>(
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Value {
+        entries.into_iter().collect()
+    }
+
+    /// An empty array with room for `n` elements before it reallocates
+    pub fn array_with_capacity(n: usize) -> Value {
+        Value::Array(Vec::with_capacity(n))
+    }
+
+    /// An empty object with room for `n` members before it reallocates
+    pub fn object_with_capacity(n: usize) -> Value {
+        Value::Object(HashMap::with_capacity(n))
+    }
+
+    /// The string, `None` for every other variant
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The number, `None` for every other variant, including numeric
+    /// strings such as `"1"` (see `as_f64_coerced`) and booleans
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(num) => Some(num.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// The boolean, `None` for every other variant
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The elements of an array, `None` for every other variant
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// The members of an object, `None` for every other variant
+    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Whether this is an object with a member named `key`; `false` for
+    /// every other variant
+    pub fn has_key(&self, key: &str) -> bool {
+        self.as_object()
+            .is_some_and(|object| object.contains_key(key))
+    }
+
+    /// The number of members of an object or elements of an array, and 0
+    /// for scalars and `null`, strings included
+    pub fn key_count(&self) -> usize {
+        match self {
+            Value::Object(object) => object.len(),
+            Value::Array(array) => array.len(),
+            _ => 0,
+        }
+    }
+
+    /// The keys of an object in sorted order, the order they're serialized
+    /// in, or `None` for every other variant
+    pub fn keys(&self) -> Option<Vec<&str>> {
+        let mut keys: Vec<&str> = self.as_object()?.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        Some(keys)
+    }
+
+    /// The values of an object, ordered by their keys like `keys`, or
+    /// `None` for every other variant
+    pub fn values(&self) -> Option<Vec<&Value>> {
+        let mut entries: Vec<_> = self.as_object()?.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        Some(entries.into_iter().map(|(_, value)| value).collect())
+    }
+
+    /// `Some(())` for `null`, `None` for every other variant
+    pub fn as_null(&self) -> Option<()> {
+        match self {
+            Value::Null => Some(()),
+            _ => None,
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self, Value::String(_))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Number(_))
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        matches!(self, Value::Boolean(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, Value::Array(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, Value::Object(_))
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Whether both values are the same variant, without comparing their
+    /// contents: `1` and `2` match, `1` and `"1"` don't
+    pub fn same_type(&self, other: &Value) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// The member `key` of an object, `None` when it's missing or the
+    /// value isn't an object
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?.get(key)
+    }
+
+    /// The element at `index` of an array, `None` when it's out of range or
+    /// the value isn't an array
+    pub fn get_index(&self, index: usize) -> Option<&Value> {
+        self.as_array()?.get(index)
+    }
+
+    /// The member `key` of an object, or `default` when it's missing or
+    /// the value isn't an object
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a Value) -> &'a Value {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// The element at `index` of an array, or `default` when it's out of
+    /// range or the value isn't an array
+    pub fn get_index_or<'a>(&'a self, index: usize, default: &'a Value) -> &'a Value {
+        self.get_index(index).unwrap_or(default)
+    }
+
+    /// The string, mutably, `None` for every other variant
+    pub fn as_str_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The elements of an array, mutably, `None` for every other variant
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// The members of an object, mutably, `None` for every other variant
+    pub fn as_object_mut(&mut self) -> Option<&mut HashMap<String, Value>> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// The member `key` of an object, mutably, `None` when it's missing or
+    /// the value isn't an object
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.as_object_mut()?.get_mut(key)
+    }
+
+    /// The element at `index` of an array, mutably, `None` when it's out of
+    /// range or the value isn't an array
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Value> {
+        self.as_array_mut()?.get_mut(index)
+    }
+
+    /// Takes the value out, leaving `null` in its place
+    pub fn take(&mut self) -> Value {
+        std::mem::take(self)
+    }
+
+    /// Puts `new` in place of the value and returns the old one
+    pub fn replace(&mut self, new: Value) -> Value {
+        std::mem::replace(self, new)
+    }
+
+    /// Applies `f` to every node of the tree in post-order, so children
+    /// are transformed before their parent
+    pub fn walk_mut(&mut self, mut f: impl FnMut(&mut Value)) {
+        self.walk_mut_inner(&mut f);
+    }
+
+    /// Replaces every string in the tree with `f` applied to it; object
+    /// keys are left alone
+    pub fn map_strings(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.walk_mut(|value| {
+            if let Value::String(s) = value {
+                *s = f(s);
+            }
+        });
+    }
+
+    /// Cuts every string longer than `max_len` characters down to its
+    /// first `max_len` characters, followed by a marker with the original
+    /// size such as `…(1234 bytes)`, to keep logged payloads small
+    ///
+    /// Object keys are left alone.
+    pub fn truncate_strings(&mut self, max_len: usize) {
+        self.walk_mut(|value| {
+            if let Value::String(s) = value {
+                if let Some((cut, _)) = s.char_indices().nth(max_len) {
+                    let marker = format!("…({} bytes)", s.len());
+                    s.truncate(cut);
+                    s.push_str(&marker);
+                }
+            }
+        });
+    }
+
+    /// Turns every string in the tree that holds a JSON number, such as
+    /// `"12"` or `"-3.5e2"`, into that number
+    ///
+    /// Other strings, including numbers padded with whitespace, and object
+    /// keys are left alone.
+    pub fn coerce_numbers(&mut self) {
+        self.walk_mut(|value| {
+            if let Value::String(s) = value {
+                if let Some(num) = parse_number(s) {
+                    *value = Value::Number(num);
+                }
+            }
+        });
+    }
+
+    /// Takes the inner map out of an object, or gives back the original
+    /// value when it's not an object
+    pub fn into_object(self) -> Result<HashMap<String, Value>, Value> {
+        match self {
+            Value::Object(object) => Ok(object),
+            other => Err(other),
+        }
+    }
+
+    /// Moves the members of an object into a `BTreeMap`, which iterates in
+    /// key order
+    pub fn into_ordered_map(self) -> Result<BTreeMap<String, Value>, ConversionError> {
+        match self {
+            Value::Object(object) => Ok(object.into_iter().collect()),
+            other => Err(ConversionError::WrongType {
+                expected: "object",
+                found: other.type_name(),
+            }),
+        }
+    }
+
+    /// Takes the inner vector out of an array, or gives back the original
+    /// value when it's not an array
+    pub fn into_array(self) -> Result<Vec<Value>, Value> {
+        match self {
+            Value::Array(array) => Ok(array),
+            other => Err(other),
+        }
+    }
+
+    /// JavaScript-style truthiness: `null`, `false`, `0`, `NaN`, `""`, `[]`
+    /// and `{}` are falsy, everything else is truthy
+    ///
+    /// JSON itself has no notion of truthiness, this follows JS semantics
+    /// for templating and rule engines.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Boolean(b) => *b,
+            Value::Number(num) => {
+                let num = num.as_f64();
+                num != 0.0 && !num.is_nan()
+            }
+            Value::String(s) => !s.is_empty(),
+            Value::Array(array) => !array.is_empty(),
+            Value::Object(object) => !object.is_empty(),
+        }
+    }
+
+    /// The value's JavaScript truthiness as a `bool`, the same as
+    /// `is_truthy`
+    ///
+    /// Named to sit alongside the other `coerce_*`/`*_coerced` conversions.
+    pub fn coerce_bool(&self) -> bool {
+        self.is_truthy()
+    }
+
+    /// The value as text for templates: a string's own contents, anything
+    /// else as its compact JSON, the same as `Display`
+    pub fn coerce_string(&self) -> String {
+        match self {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// The number, or the number spelled out by a string such as `"42"`
+    ///
+    /// Returns `None` for strings that aren't a single JSON number and for
+    /// every other variant.
+    pub fn as_f64_coerced(&self) -> Option<f64> {
+        match self {
+            Value::Number(num) => Some(num.as_f64()),
+            Value::String(s) => parse_number(s.trim()).map(|num| num.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// The number truncated toward zero, so `2.9` gives `2` and `-2.9`
+    /// gives `-2`
+    ///
+    /// Magnitudes beyond the `i64` range saturate at `i64::MIN`/`i64::MAX`.
+    /// Returns `None` for NaN and for every other variant.
+    pub fn as_integer_lossy(&self) -> Option<i64> {
+        let num = match self {
+            Value::Number(num) => num,
+            _ => return None,
+        };
+        if let Some(int) = num.as_i64() {
+            return Some(int);
+        }
+        let num = num.as_f64();
+        (!num.is_nan()).then(|| num.trunc() as i64)
+    }
+
+    /// The number in plain decimal notation, never with an exponent, e.g.
+    /// `"0.000001"` or `"100000000000000000000"`
+    ///
+    /// Returns `None` for NaN and infinities and for every other variant.
+    pub fn as_float_string(&self) -> Option<String> {
+        match self {
+            Value::Number(NumberRepr::Integer(int)) => Some(int.to_string()),
+            // `Display` for f64 never switches to exponent notation
+            Value::Number(num) if num.as_f64().is_finite() => Some(num.as_f64().to_string()),
+            _ => None,
+        }
+    }
+
+    /// The boolean, or the boolean spelled out by the strings `"true"` and
+    /// `"false"`
+    pub fn as_bool_coerced(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            Value::String(s) => match s.as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The JSON Schema name of the variant, e.g. `"boolean"`
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    fn walk_mut_inner(&mut self, f: &mut impl FnMut(&mut Value)) {
+        match self {
+            Value::Array(array) => {
+                for value in array {
+                    value.walk_mut_inner(f);
+                }
+            }
+            Value::Object(object) => {
+                for value in object.values_mut() {
+                    value.walk_mut_inner(f);
+                }
+            }
+            _ => {}
+        }
+        f(self);
+    }
+}
+
+/// The number `s` spells out when it's exactly one JSON number, nothing
+/// around it
+fn parse_number(s: &str) -> Option<NumberRepr> {
+    // the tokenizer is more lenient than the grammar, taking `01` or `1.`
+    if !is_json_number(s) {
+        return None;
+    }
+    match tokenize::tokenize(s).ok()?.as_slice() {
+        [tokenize::Token::Number(num)] => Some(num.clone()),
+        _ => None,
+    }
+}
+
+/// Whether `s` matches the JSON number grammar,
+/// `-?(0|[1-9]\d*)(\.\d+)?([eE][+-]?\d+)?`
+fn is_json_number(s: &str) -> bool {
+    fn digits(s: &str) -> &str {
+        s.trim_start_matches(|c: char| c.is_ascii_digit())
+    }
+
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let rest = match s.strip_prefix('0') {
+        Some(rest) => rest,
+        None if s.starts_with(|c: char| c.is_ascii_digit()) => digits(s),
+        None => return false,
+    };
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) if digits(fraction).len() < fraction.len() => digits(fraction),
+        Some(_) => return false,
+        None => rest,
+    };
+    match rest.strip_prefix(['e', 'E']) {
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            !exponent.is_empty() && digits(exponent).is_empty()
+        }
+        None => rest.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{json, parse, ConversionError, NumberRepr, Value};
+
+    /// One value of each variant, in declaration order, shared with the
+    /// tests of other modules
+    ///
+    /// The number is whole so that it converts to every integer type.
+    pub(crate) fn one_of_each() -> [Value; 6] {
+        [
+            json!(null),
+            json!(true),
+            json!("s"),
+            json!(2),
+            json!([1]),
+            json!({"k": 2}),
+        ]
+    }
+
+    /// Indices into `one_of_each` of the values `f` accepts
+    fn accepted_by(f: impl Fn(&Value) -> bool) -> Vec<usize> {
+        (0..6).filter(|&i| f(&one_of_each()[i])).collect()
+    }
+
+    #[test]
+    fn array_and_object_match_verbose_constructors() {
+        assert_eq!(
+            Value::array([Value::str("a"), Value::num(2.0)]),
+            Value::Array(vec![Value::String("a".to_string()), Value::num(2.0)])
+        );
+        assert_eq!(Value::array(Vec::<Value>::new()), Value::Array(vec![]));
+        assert_eq!(
+            Value::object([("k", Value::array([1, 2])), ("n", Value::null())]),
+            Value::Object(HashMap::from([
+                (
+                    "k".to_string(),
+                    Value::Array(vec![Value::num(1.0), Value::num(2.0)])
+                ),
+                ("n".to_string(), Value::Null),
+            ]))
+        );
+        assert_eq!(Value::object([("k", 1)]), json!({"k": 1}));
+    }
+
+    #[test]
+    fn same_type_matches_only_same_variant() {
+        let other = [
+            json!(null),
+            json!(false),
+            json!(""),
+            json!(-3),
+            json!([]),
+            json!({}),
+        ];
+        for (i, a) in one_of_each().iter().enumerate() {
+            for (j, b) in other.iter().enumerate() {
+                assert_eq!(a.same_type(b), i == j, "{a} vs {b}");
+            }
+        }
+        assert!(parse("1").unwrap().same_type(&parse("2").unwrap()));
+        assert!(!parse("1").unwrap().same_type(&parse(r#""hello""#).unwrap()));
+    }
+
+    #[test]
+    fn walk_mut_uppercases_nested_strings() {
+        let mut value = Value::Array(vec![
+            Value::String("a".to_string()),
+            Value::Object(HashMap::from([(
+                "key".to_string(),
+                Value::Array(vec![Value::String("b".to_string()), Value::Null]),
+            )])),
+        ]);
+        let expected = Value::Array(vec![
+            Value::String("A".to_string()),
+            Value::Object(HashMap::from([(
+                "key".to_string(),
+                Value::Array(vec![Value::String("B".to_string()), Value::Null]),
+            )])),
+        ]);
+
+        value.walk_mut(|v| {
+            if let Value::String(s) = v {
+                *s = s.to_uppercase();
+            }
+        });
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn get_or_falls_back_to_default() {
+        let value = json!({"a": 1, "list": [true]});
+        let default = json!("fallback");
+
+        assert_eq!(value.get_or("a", &default), &json!(1));
+        assert_eq!(value.get_or("missing", &default), &default);
+        assert_eq!(value["list"].get_or("a", &default), &default);
+
+        assert_eq!(value["list"].get_index_or(0, &default), &json!(true));
+        assert_eq!(value["list"].get_index_or(1, &default), &default);
+        assert_eq!(value.get_index_or(0, &default), &default);
+    }
+
+    #[test]
+    fn has_key_only_for_object_members() {
+        assert_eq!(accepted_by(|v| v.has_key("k")), [5]);
+        assert!(!json!({"k": 2}).has_key("K"));
+        // arrays have no keys, not even their indices
+        assert!(!json!([1]).has_key("0"));
+        assert!(json!({"n": null}).has_key("n"));
+    }
+
+    #[test]
+    fn key_count_of_every_variant() {
+        let counts: Vec<usize> = one_of_each().iter().map(Value::key_count).collect();
+        assert_eq!(counts, [0, 0, 0, 0, 1, 1]);
+        assert_eq!(json!({"a": 1, "b": [1, 2, 3]}).key_count(), 2);
+        assert_eq!(json!([1, [2, 3], 4]).key_count(), 3);
+        assert_eq!(json!("a long string").key_count(), 0);
+    }
+
+    #[test]
+    fn keys_and_values_of_object() {
+        let value = json!({"b": [true], "a": 1});
+        assert_eq!(value.keys(), Some(vec!["a", "b"]));
+        assert_eq!(value.values(), Some(vec![&json!(1), &json!([true])]));
+
+        assert_eq!(json!({}).keys(), Some(vec![]));
+        assert_eq!(json!([1]).keys(), None);
+        assert_eq!(json!("a").values(), None);
+    }
+
+    #[test]
+    fn map_strings_trims_nested_strings() {
+        let mut value = json!({" key ": [" a ", {"b": "\tb\n"}, 1], "c": " c"});
+        value.map_strings(|s| s.trim().to_string());
+        assert_eq!(value, json!({" key ": ["a", {"b": "b"}, 1], "c": "c"}));
+    }
+
+    #[test]
+    fn truncate_strings_in_nested_document() {
+        let mut value = json!({
+            "id": "short",
+            "blob": "aGVsbG8gd29ybGQ=",
+            "parts": [{"data": "0123456789"}, "exactly8", "naïve café"],
+            "a key longer than eight": 1
+        });
+        value.truncate_strings(8);
+        assert_eq!(
+            value,
+            json!({
+                "id": "short",
+                "blob": "aGVsbG8g…(16 bytes)",
+                "parts": [{"data": "01234567…(10 bytes)"}, "exactly8", "naïve ca…(12 bytes)"],
+                "a key longer than eight": 1
+            })
+        );
+    }
+
+    #[test]
+    fn coerce_numbers_converts_numeric_strings() {
+        let mut value = json!({"a": "12", "b": "x", "c": ["3.5"]});
+        value.coerce_numbers();
+        assert_eq!(value, json!({"a": 12, "b": "x", "c": [3.5]}));
+        assert!(matches!(value["a"], Value::Number(NumberRepr::Integer(12))));
+
+        let mut value = json!(["9007199254740993", " 1", "1 2", "", "NaN", "-0.5e1"]);
+        value.coerce_numbers();
+        assert_eq!(value[0], Value::from(9007199254740993_i64));
+        assert_eq!(value[1], json!(" 1"));
+        assert_eq!(value[2], json!("1 2"));
+        assert_eq!(value[3], json!(""));
+        assert_eq!(value[4], json!("NaN"));
+        assert_eq!(value[5], json!(-5));
+    }
+
+    #[test]
+    fn coerce_numbers_follows_the_json_grammar() {
+        let invalid = [
+            "01", "1.", ".5", "-", "+1", "-01", "1e", "1e+", "1.e5", "0x10", "1_000", "--1",
+            "1.5.2",
+        ];
+        let mut value = Value::from(&invalid[..]);
+        value.coerce_numbers();
+        assert_eq!(value, Value::from(&invalid[..]));
+
+        let mut value = json!(["0", "-0", "10", "0.5", "-1.25E+3", "2e-2", "1E5"]);
+        value.coerce_numbers();
+        assert_eq!(value, json!([0, 0, 10, 0.5, -1250, 0.02, 100000]));
+        assert_eq!(json!("01").as_f64_coerced(), None);
+    }
+
+    #[test]
+    fn into_object_returns_inner_map() {
+        let map = HashMap::from([("a".to_string(), Value::Null)]);
+        let value = Value::Object(HashMap::from([("a".to_string(), Value::Null)]));
+
+        assert_eq!(value.into_object(), Ok(map));
+    }
+
+    #[test]
+    fn into_object_wrong_variant_returns_value() {
+        let value = Value::Array(vec![Value::Null]);
+
+        assert_eq!(value.into_object(), Err(Value::Array(vec![Value::Null])));
+    }
+
+    #[test]
+    fn into_array_returns_inner_vec() {
+        let value = Value::Array(vec![Value::Boolean(true)]);
+
+        assert_eq!(value.into_array(), Ok(vec![Value::Boolean(true)]));
+    }
+
+    #[test]
+    fn into_array_wrong_variant_returns_value() {
+        let value = Value::String("a".to_string());
+
+        assert_eq!(value.into_array(), Err(Value::String("a".to_string())));
+    }
+
+    #[test]
+    fn falsy_values() {
+        assert!(!Value::Null.is_truthy());
+        assert!(!Value::Boolean(false).is_truthy());
+        assert!(!Value::num(0.0).is_truthy());
+        assert!(!Value::num(-0.0).is_truthy());
+        assert!(!Value::num(f64::NAN).is_truthy());
+        assert!(!Value::String(String::new()).is_truthy());
+        assert!(!Value::Array(vec![]).is_truthy());
+        assert!(!Value::Object(HashMap::new()).is_truthy());
+    }
+
+    #[test]
+    fn truthy_values() {
+        assert!(Value::Boolean(true).is_truthy());
+        assert!(Value::num(-1.5).is_truthy());
+        assert!(Value::String("0".to_string()).is_truthy());
+        assert!(Value::Array(vec![Value::Null]).is_truthy());
+        assert!(Value::Object(HashMap::from([("a".to_string(), Value::Null)])).is_truthy());
+    }
+
+    #[test]
+    fn as_f64_coerced_number() {
+        assert_eq!(Value::num(1.5).as_f64_coerced(), Some(1.5));
+    }
+
+    #[test]
+    fn as_f64_coerced_numeric_string() {
+        assert_eq!(Value::String("42".to_string()).as_f64_coerced(), Some(42.0));
+        assert_eq!(Value::String(" 7 ".to_string()).as_f64_coerced(), Some(7.0));
+        assert_eq!(
+            Value::String("-2.5e2".to_string()).as_f64_coerced(),
+            Some(-250.0)
+        );
+    }
+
+    #[test]
+    fn as_f64_coerced_non_numeric() {
+        assert_eq!(Value::String("abc".to_string()).as_f64_coerced(), None);
+        assert_eq!(Value::String("1 2".to_string()).as_f64_coerced(), None);
+        assert_eq!(Value::String("NaN".to_string()).as_f64_coerced(), None);
+        assert_eq!(Value::String(String::new()).as_f64_coerced(), None);
+        assert_eq!(Value::String("  ".to_string()).as_f64_coerced(), None);
+        assert_eq!(Value::Boolean(true).as_f64_coerced(), None);
+    }
+
+    #[test]
+    fn as_bool_coerced() {
+        assert_eq!(Value::Boolean(false).as_bool_coerced(), Some(false));
+        assert_eq!(
+            Value::String("true".to_string()).as_bool_coerced(),
+            Some(true)
+        );
+        assert_eq!(
+            Value::String("false".to_string()).as_bool_coerced(),
+            Some(false)
+        );
+        assert_eq!(Value::String("yes".to_string()).as_bool_coerced(), None);
+        assert_eq!(Value::num(1.0).as_bool_coerced(), None);
+    }
+
+    #[test]
+    fn default_is_null() {
+        assert_eq!(Value::default(), Value::Null);
+    }
+
+    #[test]
+    fn walk_mut_visits_children_before_parent() {
+        let mut value = Value::Array(vec![Value::Array(vec![Value::Null])]);
+        let mut visited = Vec::new();
+
+        value.walk_mut(|v| {
+            visited.push(match v {
+                Value::Array(a) => a.len(),
+                _ => 0,
+            })
+        });
+        assert_eq!(visited, [0, 1, 1]);
+    }
+
+    #[test]
+    fn into_ordered_map_iterates_in_key_order() {
+        let value = Value::Object(HashMap::from([
+            ("pear".to_string(), Value::num(3.0)),
+            ("apple".to_string(), Value::num(1.0)),
+            ("fig".to_string(), Value::Null),
+            ("Zebra".to_string(), Value::Boolean(true)),
+        ]));
+
+        let map = value.into_ordered_map().unwrap();
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            ["Zebra", "apple", "fig", "pear"]
+        );
+        assert_eq!(map["apple"], Value::num(1.0));
+        assert_eq!(map["fig"], Value::Null);
+    }
+
+    #[test]
+    fn into_ordered_map_wrong_variant() {
+        assert_eq!(
+            Value::Array(vec![]).into_ordered_map(),
+            Err(ConversionError::WrongType {
+                expected: "object",
+                found: "array"
+            })
+        );
+    }
+
+    #[test]
+    fn with_capacity_constructors() {
+        let Value::Array(array) = Value::array_with_capacity(16) else {
+            panic!("expected an array");
+        };
+        assert!(array.is_empty());
+        assert!(array.capacity() >= 16);
+
+        let Value::Object(object) = Value::object_with_capacity(16) else {
+            panic!("expected an object");
+        };
+        assert!(object.is_empty());
+        assert!(object.capacity() >= 16);
+    }
+
+    #[test]
+    fn accessors() {
+        assert_eq!(accepted_by(|v| v.as_null().is_some()), [0]);
+        assert_eq!(accepted_by(|v| v.as_bool().is_some()), [1]);
+        assert_eq!(accepted_by(|v| v.as_str().is_some()), [2]);
+        assert_eq!(accepted_by(|v| v.as_f64().is_some()), [3]);
+        assert_eq!(accepted_by(|v| v.as_array().is_some()), [4]);
+        assert_eq!(accepted_by(|v| v.as_object().is_some()), [5]);
+
+        let [null, boolean, string, number, array, object] = one_of_each();
+        assert_eq!(null.as_null(), Some(()));
+        assert_eq!(boolean.as_bool(), Some(true));
+        assert_eq!(string.as_str(), Some("s"));
+        assert_eq!(number.as_f64(), Some(2.0));
+        assert_eq!(array.as_array(), Some(&vec![json!(1)]));
+        assert_eq!(
+            object.as_object(),
+            Some(&HashMap::from([("k".to_string(), json!(2))]))
+        );
+    }
+
+    #[test]
+    fn as_f64_does_not_coerce() {
+        assert_eq!(json!("1").as_f64(), None);
+        assert_eq!(json!(true).as_f64(), None);
+        assert_eq!(json!(f64::NAN).as_f64().map(f64::is_nan), Some(true));
+    }
+
+    #[test]
+    fn predicates() {
+        let predicates: [fn(&Value) -> bool; 6] = [
+            Value::is_null,
+            Value::is_boolean,
+            Value::is_string,
+            Value::is_number,
+            Value::is_array,
+            Value::is_object,
+        ];
+        for (i, predicate) in predicates.iter().enumerate() {
+            for (j, value) in one_of_each().iter().enumerate() {
+                assert_eq!(predicate(value), i == j, "predicate {i} on {value:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn get_and_get_index() {
+        assert_eq!(accepted_by(|v| v.get("k").is_some()), [5]);
+        assert_eq!(accepted_by(|v| v.get_index(0).is_some()), [4]);
+
+        let [.., array, object] = one_of_each();
+        assert_eq!(object.get("k"), Some(&json!(2)));
+        assert_eq!(object.get("missing"), None);
+        assert_eq!(array.get_index(0), Some(&json!(1)));
+        assert_eq!(array.get_index(1), None);
+    }
+
+    #[test]
+    fn as_integer_lossy_truncates_toward_zero() {
+        assert_eq!(json!(2.9).as_integer_lossy(), Some(2));
+        assert_eq!(json!(-2.9).as_integer_lossy(), Some(-2));
+        assert_eq!(json!(7).as_integer_lossy(), Some(7));
+        assert_eq!(json!(1e300).as_integer_lossy(), Some(i64::MAX));
+        assert_eq!(json!(f64::NAN).as_integer_lossy(), None);
+        assert_eq!(json!("2").as_integer_lossy(), None);
+    }
+
+    #[test]
+    fn as_float_string_has_no_exponent() {
+        assert_eq!(json!(2.5).as_float_string().as_deref(), Some("2.5"));
+        assert_eq!(json!(3).as_float_string().as_deref(), Some("3"));
+        assert_eq!(json!(1e-6).as_float_string().as_deref(), Some("0.000001"));
+        assert_eq!(
+            json!(123456789012345.0).as_float_string().as_deref(),
+            Some("123456789012345")
+        );
+        assert_eq!(
+            json!(1e21).as_float_string().as_deref(),
+            Some("1000000000000000000000")
+        );
+        for exponent in -6..15 {
+            let s = json!(10f64.powi(exponent) * 1.5).as_float_string().unwrap();
+            assert!(!s.contains('e'), "{s}");
+        }
+    }
+
+    #[test]
+    fn as_float_string_non_numbers() {
+        assert_eq!(json!(f64::INFINITY).as_float_string(), None);
+        assert_eq!(json!("1").as_float_string(), None);
+        assert_eq!(Value::Null.as_float_string(), None);
+    }
+
+    #[test]
+    fn mutable_accessors_edit_nested_structure() {
+        let mut value = json!({"items": [1], "meta": {"name": "old"}});
+
+        if let Some(items) = value.get_mut("items").and_then(Value::as_array_mut) {
+            items.push(json!(2));
+        }
+        if let Some(name) = value.get_mut("meta").and_then(|m| m.get_mut("name")) {
+            name.as_str_mut().unwrap().push_str("er");
+        }
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("added".to_string(), json!(true));
+        *value
+            .get_mut("items")
+            .and_then(|items| items.get_index_mut(0))
+            .unwrap() = json!(0);
+
+        assert_eq!(
+            value,
+            json!({"items": [0, 2], "meta": {"name": "older"}, "added": true})
+        );
+    }
+
+    #[test]
+    fn mutable_accessors_wrong_variant() {
+        let mut value = json!([1]);
+
+        assert_eq!(value.as_str_mut(), None);
+        assert_eq!(value.as_object_mut(), None);
+        assert_eq!(value.get_mut("a"), None);
+        assert_eq!(value.get_index_mut(1), None);
+        assert_eq!(json!("s").as_array_mut(), None);
+    }
+
+    #[test]
+    fn take_leaves_null_behind() {
+        let mut value = json!({"big": [1, 2, 3]});
+
+        let taken = value.get_mut("big").unwrap().take();
+        assert_eq!(taken, json!([1, 2, 3]));
+        assert_eq!(value, json!({"big": null}));
+    }
+
+    #[test]
+    fn replace_returns_old_value() {
+        let mut value = json!([1, "two"]);
+
+        let old = value.get_index_mut(1).unwrap().replace(json!(2));
+        assert_eq!(old, json!("two"));
+        assert_eq!(value, json!([1, 2]));
+    }
+
+    #[test]
+    fn coerce_bool_every_variant() {
+        let falsy = [
+            json!(null),
+            json!(false),
+            json!(0),
+            json!(-0.0),
+            json!(f64::NAN),
+            json!(""),
+            json!([]),
+            json!({}),
+        ];
+        let truthy = [
+            json!(true),
+            json!(1),
+            json!(-0.5),
+            json!(f64::INFINITY),
+            json!("false"),
+            json!(" "),
+            json!([null]),
+            json!({"": null}),
+        ];
+
+        for value in falsy {
+            assert!(!value.coerce_bool(), "{value:?}");
+        }
+        for value in truthy {
+            assert!(value.coerce_bool(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn coerce_string_every_variant() {
+        assert_eq!(json!(null).coerce_string(), "null");
+        assert_eq!(json!(true).coerce_string(), "true");
+        assert_eq!(json!(false).coerce_string(), "false");
+        assert_eq!(json!(3).coerce_string(), "3");
+        assert_eq!(json!(-2.5e-7).coerce_string(), "-2.5e-7");
+        assert_eq!(json!("say \"hi\"").coerce_string(), "say \"hi\"");
+        assert_eq!(json!([1, "a", null]).coerce_string(), r#"[1,"a",null]"#);
+        assert_eq!(
+            json!({"b": {"c": []}, "a": 1}).coerce_string(),
+            r#"{"a":1,"b":{"c":[]}}"#
+        );
+    }
+
+    #[test]
+    fn coerce_string_matches_display_except_strings() {
+        for value in one_of_each() {
+            if value.is_string() {
+                assert_ne!(value.coerce_string(), value.to_string());
+            } else {
+                assert_eq!(value.coerce_string(), value.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+/// Builds a `Value` from JSON-like syntax
+///
+/// ```
+/// use json_parser::{json, Value};
+///
+/// let name = "Ada";
+/// let tags = vec!["math", "engines"];
+/// let value = json!({
+///     "name": name,
+///     "age": 36,
+///     "tags": tags,
+///     "nested": {"ok": true, "missing": null, "list": [1, 2.5, "three"]},
+/// });
+///
+/// assert_eq!(value.pointer("/nested/list/1"), Some(&Value::num(2.5)));
+/// ```
+///
+/// Any expression can be interpolated as a value, converted with
+/// `Value::from`. Keys are string literals or expressions converted with
+/// `String::from`, wrapped in parentheses when they're more than a single
+/// token. Trailing commas are allowed.
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::Value::Null
+    };
+    (true) => {
+        $crate::Value::Boolean(true)
+    };
+    (false) => {
+        $crate::Value::Boolean(false)
+    };
+    ([]) => {
+        $crate::Value::Array(::std::vec::Vec::new())
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::json_internal!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::Value::Object(::std::collections::HashMap::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Object({
+            let mut object = ::std::collections::HashMap::new();
+            $crate::json_internal!(@object object () ($($tt)+) ($($tt)+));
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+}
+
+/// Token muncher behind `json!`, splitting array elements and object
+/// members on their commas
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // arrays: elements are collected as expressions in the brackets
+
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        ::std::vec![$($elems),*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!(true)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!(false)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!({$($object)*})] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!($last)])
+    };
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // objects: `(key tokens) (remaining tokens) (copy of remaining tokens)`,
+    // then `[key] (value)` once a whole member has been read
+
+    (@object $object:ident () () ()) => {};
+    (@object $object:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        let _ = $object.insert(::std::string::String::from($($key)+), $value);
+        $crate::json_internal!(@object $object () ($($rest)*) ($($rest)*));
+    };
+    (@object $object:ident [$($key:tt)+] ($value:expr)) => {
+        let _ = $object.insert(::std::string::String::from($($key)+), $value);
+    };
+    (@object $object:ident ($($key:tt)+) (: null $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!(null)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: true $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!(true)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: false $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!(false)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!([$($array)*])) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!({$($map)*})) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!($value)) , $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: $value:expr) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json!($value)));
+    };
+    // a parenthesized key is a single expression
+    (@object $object:ident () (($key:expr) : $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object ($key) (: $($rest)*) (: $($rest)*));
+    };
+    // move the next token into the key
+    (@object $object:ident ($($key:tt)*) ($tt:tt $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object ($($key)* $tt) ($($rest)*) ($($rest)*));
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{parse, Value};
+
+    #[test]
+    fn literals() {
+        assert_eq!(json!(null), Value::Null);
+        assert_eq!(json!(true), Value::Boolean(true));
+        assert_eq!(json!(false), Value::Boolean(false));
+        assert_eq!(json!(42), Value::num(42.0));
+        assert_eq!(json!(-1.5), Value::num(-1.5));
+        assert_eq!(json!("s"), Value::String("s".to_string()));
+        assert_eq!(json!([]), Value::Array(vec![]));
+        assert_eq!(json!({}), Value::Object(HashMap::new()));
+    }
+
+    #[test]
+    fn nested_literals_match_parsed_json() {
+        let value = json!({
+            "name": "widget",
+            "count": 3,
+            "price": 9.99,
+            "flags": [true, false, null],
+            "dims": {"w": 1, "h": [2, [3, {}]], "empty": []},
+        });
+
+        assert_eq!(
+            value,
+            parse(
+                r#"{"name": "widget", "count": 3, "price": 9.99,
+                    "flags": [true, false, null],
+                    "dims": {"w": 1, "h": [2, [3, {}]], "empty": []}}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn trailing_commas() {
+        assert_eq!(json!([1, 2,]), json!([1, 2]));
+        assert_eq!(
+            json!({"a": [1,], "b": {"c": null,},}),
+            json!({"a": [1], "b": {"c": null}})
+        );
+    }
+
+    #[test]
+    fn interpolated_values() {
+        struct User {
+            name: String,
+            age: u32,
+        }
+        let user = User {
+            name: "Ada".to_string(),
+            age: 36,
+        };
+        let tags = vec!["math", "engines"];
+        let score = 2.5 * 2.0;
+
+        assert_eq!(
+            json!({"name": user.name, "age": user.age, "tags": tags, "score": score}),
+            parse(r#"{"name": "Ada", "age": 36, "tags": ["math", "engines"], "score": 5}"#)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn interpolated_options() {
+        let some: Option<&str> = Some("x");
+        let none: Option<i32> = None;
+
+        assert_eq!(json!([some, none]), json!(["x", null]));
+        assert_eq!(json!({"a": none}), json!({"a": null}));
+    }
+
+    #[test]
+    fn interpolated_values_in_arrays() {
+        let inner = json!({"k": 1});
+        let n = 7;
+
+        assert_eq!(
+            json!([inner, n + 1, -n, "s".repeat(2)]),
+            json!([{"k": 1}, 8, -7, "ss"])
+        );
+    }
+
+    #[test]
+    fn computed_keys() {
+        let key = "dynamic";
+        let prefix = "p";
+
+        assert_eq!(
+            json!({key: 1, (format!("{prefix}_{}", 2)): true, (key.to_uppercase()): null}),
+            parse(r#"{"dynamic": 1, "p_2": true, "DYNAMIC": null}"#).unwrap()
+        );
+    }
+}
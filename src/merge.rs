@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+
+use crate::Value;
+
+/// Applies a JSON Merge Patch (RFC 7386) to `target`
+///
+/// A patch that isn't an object replaces the target. Otherwise each
+/// member of the patch is merged in: `null` removes the key, objects are
+/// merged recursively and anything else, arrays included, replaces the
+/// existing value. A target that isn't an object is treated as `{}`.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(HashMap::new());
+    }
+    let Value::Object(object) = target else {
+        unreachable!("target was just made an object");
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            object.remove(key);
+        } else {
+            merge_patch(object.entry(key.clone()).or_default(), value);
+        }
+    }
+}
+
+/// Builds the smallest merge patch that turns `original` into `updated`,
+/// so `merge_patch(&mut original, &merge_patch_diff(&original, &updated))`
+/// gives `updated`
+///
+/// Removed keys become `null`, unchanged keys are left out and nested
+/// objects are diffed recursively. Anything else that changed, arrays
+/// included, is copied whole.
+///
+/// Merge patch has no way to set a member to `null`, since `null` means
+/// "remove". An object member whose new value is `null` is therefore
+/// treated as removed: the result of applying the patch lacks that key
+/// instead of holding `null`, unless it already held `null` in
+/// `original`. Nulls inside arrays are kept.
+pub fn merge_patch_diff(original: &Value, updated: &Value) -> Value {
+    let Value::Object(updated) = updated else {
+        return updated.clone();
+    };
+    let empty = HashMap::new();
+    let original = match original {
+        Value::Object(object) => object,
+        _ => &empty,
+    };
+
+    let mut patch = HashMap::new();
+    for key in original.keys() {
+        let removed = match updated.get(key) {
+            None => true,
+            Some(value) => value.is_null() && !original[key].is_null(),
+        };
+        if removed {
+            patch.insert(key.clone(), Value::Null);
+        }
+    }
+    for (key, value) in updated {
+        if value.is_null() || original.get(key) == Some(value) {
+            continue;
+        }
+        let old = original.get(key).unwrap_or(&Value::Null);
+        patch.insert(key.clone(), merge_patch_diff(old, value));
+    }
+    Value::Object(patch)
+}
+
+/// How `Value::merge` combines two arrays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayMergeStrategy {
+    /// The other array replaces this one
+    Replace,
+
+    /// The other array's elements are appended to this one
+    Concat,
+
+    /// Elements at the same index are merged, and the longer array's tail
+    /// is kept
+    MergeByIndex,
+}
+
+impl Value {
+    /// Deep merges `other` into this value, for layering configuration
+    ///
+    /// Objects are merged key by key, recursing into members both sides
+    /// have; arrays are combined according to `strategy`. In every other
+    /// case, including mismatched types such as an object merged into an
+    /// array, `other` wins and replaces this value. Unlike `merge_patch`,
+    /// `null` in `other` is an ordinary value and replaces rather than
+    /// removes.
+    pub fn merge(&mut self, other: &Value, strategy: ArrayMergeStrategy) {
+        match (self, other) {
+            (Value::Object(object), Value::Object(other)) => {
+                for (key, value) in other {
+                    match object.get_mut(key) {
+                        Some(existing) => existing.merge(value, strategy),
+                        None => {
+                            object.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (Value::Array(array), Value::Array(other)) => match strategy {
+                ArrayMergeStrategy::Replace => array.clone_from(other),
+                ArrayMergeStrategy::Concat => array.extend(other.iter().cloned()),
+                ArrayMergeStrategy::MergeByIndex => {
+                    for (existing, value) in array.iter_mut().zip(other) {
+                        existing.merge(value, strategy);
+                    }
+                    array.extend(other.iter().skip(array.len()).cloned());
+                }
+            },
+            (this, other) => this.clone_from(other),
+        }
+    }
+}
+
+/// `base` with `other` deep merged into it, leaving both untouched; see
+/// `Value::merge`
+pub fn merged(base: &Value, other: &Value, strategy: ArrayMergeStrategy) -> Value {
+    let mut result = base.clone();
+    result.merge(other, strategy);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_patch, merge_patch_diff, merged, ArrayMergeStrategy};
+    use crate::{json, Value};
+
+    fn check(target: Value, patch: Value, expected: Value) {
+        let mut result = target.clone();
+        merge_patch(&mut result, &patch);
+        assert_eq!(result, expected, "{target} patched with {patch}");
+    }
+
+    /// The examples from RFC 7386 appendix A
+    #[test]
+    fn rfc_7386_examples() {
+        check(json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"}));
+        check(
+            json!({"a": "b"}),
+            json!({"b": "c"}),
+            json!({"a": "b", "b": "c"}),
+        );
+        check(json!({"a": "b"}), json!({"a": null}), json!({}));
+        check(
+            json!({"a": "b", "b": "c"}),
+            json!({"a": null}),
+            json!({"b": "c"}),
+        );
+        check(json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"}));
+        check(json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]}));
+        check(
+            json!({"a": {"b": "c"}}),
+            json!({"a": {"b": "d", "c": null}}),
+            json!({"a": {"b": "d"}}),
+        );
+        check(
+            json!({"a": [{"b": "c"}]}),
+            json!({"a": [1]}),
+            json!({"a": [1]}),
+        );
+        check(json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"]));
+        check(json!({"a": "b"}), json!(["c"]), json!(["c"]));
+        check(json!({"a": "foo"}), json!(null), json!(null));
+        check(json!({"a": "foo"}), json!("bar"), json!("bar"));
+        check(
+            json!({"e": null}),
+            json!({"a": 1}),
+            json!({"e": null, "a": 1}),
+        );
+        check(
+            json!([1, 2]),
+            json!({"a": "b", "c": null}),
+            json!({"a": "b"}),
+        );
+        check(
+            json!({}),
+            json!({"a": {"bb": {"ccc": null}}}),
+            json!({"a": {"bb": {}}}),
+        );
+    }
+
+    #[test]
+    fn nested_patch_on_missing_or_scalar_key() {
+        check(
+            json!({"a": 1}),
+            json!({"a": {"b": {"c": 2, "d": null}}, "x": {"y": null}}),
+            json!({"a": {"b": {"c": 2}}, "x": {}}),
+        );
+        check(json!("scalar"), json!({}), json!({}));
+    }
+
+    #[test]
+    fn arrays_are_replaced_not_merged() {
+        check(
+            json!({"tags": [1, 2, 3]}),
+            json!({"tags": [{"x": null}]}),
+            json!({"tags": [{"x": null}]}),
+        );
+    }
+
+    /// `value` with every `null` object member outside of arrays dropped,
+    /// which is what a merge patch can express
+    fn without_null_members(value: &Value) -> Value {
+        match value {
+            Value::Object(object) => object
+                .iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k.clone(), without_null_members(v)))
+                .collect(),
+            other => other.clone(),
+        }
+    }
+
+    #[test]
+    fn diff_is_minimal() {
+        let original = json!({
+            "title": "Hello",
+            "author": {"givenName": "John", "familyName": "Doe"},
+            "tags": ["example", "sample"],
+            "content": "This will be unchanged"
+        });
+        let updated = json!({
+            "title": "Hello!",
+            "author": {"givenName": "John"},
+            "tags": ["example"],
+            "content": "This will be unchanged",
+            "phoneNumber": "+01-123-456-7890"
+        });
+        assert_eq!(
+            merge_patch_diff(&original, &updated),
+            json!({
+                "title": "Hello!",
+                "author": {"familyName": null},
+                "tags": ["example"],
+                "phoneNumber": "+01-123-456-7890"
+            })
+        );
+        assert_eq!(merge_patch_diff(&original, &original), json!({}));
+        assert_eq!(merge_patch_diff(&original, &json!([1])), json!([1]));
+    }
+
+    #[test]
+    fn null_members_are_treated_as_removed() {
+        // setting a member to null and removing it give the same patch
+        let original = json!({"a": 1, "b": 2});
+        assert_eq!(
+            merge_patch_diff(&original, &json!({"a": null, "b": 2})),
+            json!({"a": null})
+        );
+        assert_eq!(
+            merge_patch_diff(&original, &json!({"b": 2})),
+            json!({"a": null})
+        );
+        // a new null member has nothing to express
+        assert_eq!(merge_patch_diff(&json!({}), &json!({"a": null})), json!({}));
+        // an existing null member is left as is, or removed when it's gone
+        assert_eq!(
+            merge_patch_diff(&json!({"a": null}), &json!({"a": null})),
+            json!({})
+        );
+        assert_eq!(
+            merge_patch_diff(&json!({"a": null}), &json!({})),
+            json!({"a": null})
+        );
+    }
+
+    #[test]
+    fn diff_round_trips() {
+        let documents = [
+            json!(null),
+            json!(1),
+            json!("s"),
+            json!([1, null, {"a": null}]),
+            json!({}),
+            json!({"a": 1}),
+            json!({"a": null, "b": [1, 2]}),
+            json!({"a": {"b": {"c": 1, "d": null}}, "e": "f"}),
+            json!({"a": {"b": 2}, "e": ["f"], "g": {}}),
+            json!({"a": "scalar", "x": {"y": {"z": null}}}),
+        ];
+        for original in &documents {
+            for updated in &documents {
+                let patch = merge_patch_diff(original, updated);
+                let mut result = original.clone();
+                merge_patch(&mut result, &patch);
+                if without_null_members(updated) == *updated {
+                    assert_eq!(result, *updated, "{original} -> {updated} via {patch}");
+                } else {
+                    assert_eq!(
+                        without_null_members(&result),
+                        without_null_members(updated),
+                        "{original} -> {updated} via {patch}"
+                    );
+                }
+            }
+        }
+    }
+
+    fn layers() -> (Value, Value) {
+        let base = json!({
+            "name": "app",
+            "server": {"port": 80, "hosts": ["a", "b"], "tls": {"enabled": false}},
+            "plugins": [{"id": 1, "on": true}, {"id": 2}]
+        });
+        let overlay = json!({
+            "server": {"port": 8080, "hosts": ["c"], "tls": {"cert": "x.pem"}},
+            "plugins": [{"on": false}],
+            "debug": true
+        });
+        (base, overlay)
+    }
+
+    #[test]
+    fn merge_replacing_arrays() {
+        let (base, overlay) = layers();
+        assert_eq!(
+            merged(&base, &overlay, ArrayMergeStrategy::Replace),
+            json!({
+                "name": "app",
+                "server": {
+                    "port": 8080,
+                    "hosts": ["c"],
+                    "tls": {"enabled": false, "cert": "x.pem"}
+                },
+                "plugins": [{"on": false}],
+                "debug": true
+            })
+        );
+    }
+
+    #[test]
+    fn merge_concatenating_arrays() {
+        let (base, overlay) = layers();
+        let result = merged(&base, &overlay, ArrayMergeStrategy::Concat);
+        assert_eq!(result["server"]["hosts"], json!(["a", "b", "c"]));
+        assert_eq!(
+            result["plugins"],
+            json!([{"id": 1, "on": true}, {"id": 2}, {"on": false}])
+        );
+        assert_eq!(result["server"]["port"], json!(8080));
+    }
+
+    #[test]
+    fn merge_arrays_by_index() {
+        let (base, overlay) = layers();
+        let result = merged(&base, &overlay, ArrayMergeStrategy::MergeByIndex);
+        assert_eq!(result["server"]["hosts"], json!(["c", "b"]));
+        assert_eq!(
+            result["plugins"],
+            json!([{"id": 1, "on": false}, {"id": 2}])
+        );
+
+        // the longer side's tail is kept either way
+        let mut short = json!([[1], 2]);
+        short.merge(&json!([[5, 6], 7, 8]), ArrayMergeStrategy::MergeByIndex);
+        assert_eq!(short, json!([[5, 6], 7, 8]));
+    }
+
+    #[test]
+    fn merge_mismatched_types_other_wins() {
+        for strategy in [
+            ArrayMergeStrategy::Replace,
+            ArrayMergeStrategy::Concat,
+            ArrayMergeStrategy::MergeByIndex,
+        ] {
+            assert_eq!(
+                merged(&json!([1]), &json!({"a": 1}), strategy),
+                json!({"a": 1})
+            );
+            assert_eq!(merged(&json!({"a": 1}), &json!(2), strategy), json!(2));
+            assert_eq!(merged(&json!("s"), &json!([1]), strategy), json!([1]));
+            assert_eq!(
+                merged(&json!({"a": {"b": 1}}), &json!({"a": null}), strategy),
+                json!({"a": null})
+            );
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// How a `Value::Number` holds its number
+///
+/// Integers keep their exact value beyond the 2^53 limit of `f64`, and
+/// `Raw` keeps the literal exactly as it appeared in the input. Equality
+/// is by numeric value whatever the representation, so `Integer(1)`,
+/// `Float(1.0)` and `Raw("1.0")` are all equal, and NaN equals NaN.
+#[derive(Debug, Clone)]
+pub enum NumberRepr {
+    /// A literal without `.` or exponent that fits in an `i64`
+    Integer(i64),
+
+    /// Any other number
+    Float(f64),
+
+    /// The literal as written, produced when
+    /// `ParseConfig::preserve_raw_numbers` is set
+    Raw(String),
+}
+
+impl NumberRepr {
+    /// Reads a JSON number literal: `Integer` when it has no `.` or
+    /// exponent and fits in an `i64`, `Float` otherwise
+    pub(crate) fn from_literal(literal: &str) -> Option<NumberRepr> {
+        if !literal.contains(['.', 'e', 'E']) {
+            if let Ok(int) = literal.parse() {
+                return Some(NumberRepr::Integer(int));
+            }
+        }
+        literal.parse().ok().map(NumberRepr::Float)
+    }
+
+    /// The number as an `f64`, rounding integers beyond 2^53
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            NumberRepr::Integer(int) => *int as f64,
+            NumberRepr::Float(num) => *num,
+            // raw literals come from the tokenizer and always parse
+            NumberRepr::Raw(literal) => literal.parse().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// The exact integer for `Integer` and raw integer literals, `None`
+    /// for floats even when they're whole
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            NumberRepr::Integer(int) => Some(*int),
+            NumberRepr::Float(_) => None,
+            NumberRepr::Raw(literal) => literal.parse().ok(),
+        }
+    }
+
+    /// Orders two numbers by their exact value, with NaN after everything
+    /// else and equal to itself, and `-0 == 0`
+    ///
+    /// Integers are compared with floats exactly rather than through a
+    /// rounded `f64`, so the order stays transitive beyond 2^53:
+    /// `Integer(2^53 + 1)` is greater than `Float(2^53)`.
+    pub(crate) fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self.as_i64(), other.as_i64()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(a), None) => compare_integer_float(a, other.as_f64()),
+            (None, Some(b)) => compare_integer_float(b, self.as_f64()).reverse(),
+            (None, None) => {
+                let (a, b) = (self.as_f64(), other.as_f64());
+                match (a.is_nan(), b.is_nan()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) => a.partial_cmp(&b).unwrap(),
+                }
+            }
+        }
+    }
+}
+
+/// Compares `int` with `float` exactly, NaN being greater than any integer
+fn compare_integer_float(int: i64, float: f64) -> Ordering {
+    // `i64` covers [-2^63, 2^63), both ends exact as `f64`
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if float.is_nan() || float >= LIMIT {
+        return Ordering::Less;
+    }
+    if float < -LIMIT {
+        return Ordering::Greater;
+    }
+    // in range, so the whole part converts to `i64` without rounding
+    let whole = float.trunc();
+    int.cmp(&(whole as i64)).then_with(|| {
+        let fraction = float - whole;
+        if fraction > 0.0 {
+            Ordering::Less
+        } else if fraction < 0.0 {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    })
+}
+
+/// Equal when `total_cmp` says so, which unlike `f64` makes NaN equal to
+/// NaN, keeping `==` consistent with `Value`'s `Ord`
+impl PartialEq for NumberRepr {
+    fn eq(&self, other: &Self) -> bool {
+        self.total_cmp(other) == Ordering::Equal
+    }
+}
+
+impl From<f64> for NumberRepr {
+    fn from(value: f64) -> Self {
+        NumberRepr::Float(value)
+    }
+}
+
+impl From<i64> for NumberRepr {
+    fn from(value: i64) -> Self {
+        NumberRepr::Integer(value)
+    }
+}
+
+/// Integers and raw literals as written, floats the way Rust's `Display`
+/// writes `f64`
+impl fmt::Display for NumberRepr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberRepr::Integer(int) => int.fmt(f),
+            NumberRepr::Float(num) => num.fmt(f),
+            NumberRepr::Raw(literal) => f.write_str(literal),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::NumberRepr;
+
+    #[test]
+    fn literals_pick_a_representation() {
+        assert!(matches!(
+            NumberRepr::from_literal("-42"),
+            Some(NumberRepr::Integer(-42))
+        ));
+        assert!(matches!(
+            NumberRepr::from_literal("1.0"),
+            Some(NumberRepr::Float(n)) if n == 1.0
+        ));
+        assert!(matches!(
+            NumberRepr::from_literal("1e3"),
+            Some(NumberRepr::Float(n)) if n == 1000.0
+        ));
+        // too big for i64
+        assert!(matches!(
+            NumberRepr::from_literal("9223372036854775808"),
+            Some(NumberRepr::Float(_))
+        ));
+        assert!(NumberRepr::from_literal("1.2.3").is_none());
+    }
+
+    #[test]
+    fn equality_is_by_value() {
+        assert_eq!(NumberRepr::Integer(1), NumberRepr::Float(1.0));
+        assert_eq!(NumberRepr::Raw("1.0".into()), NumberRepr::Integer(1));
+        assert_ne!(
+            NumberRepr::Integer(9007199254740993),
+            NumberRepr::Integer(9007199254740992)
+        );
+        assert_eq!(NumberRepr::Float(f64::NAN), NumberRepr::Float(f64::NAN));
+        assert_ne!(
+            NumberRepr::Float(f64::NAN),
+            NumberRepr::Float(f64::INFINITY)
+        );
+    }
+
+    #[test]
+    fn integers_stay_exact() {
+        let big = NumberRepr::Integer(i64::MAX);
+        assert_eq!(big.as_i64(), Some(i64::MAX));
+        assert_eq!(big.to_string(), "9223372036854775807");
+        assert_eq!(NumberRepr::Raw("-7".into()).as_i64(), Some(-7));
+        assert_eq!(NumberRepr::Float(3.0).as_i64(), None);
+    }
+
+    #[test]
+    fn comparison_is_exact_near_2_pow_53() {
+        let a = NumberRepr::Integer(9007199254740993);
+        let b = NumberRepr::Float(9007199254740992.0);
+        let c = NumberRepr::Integer(9007199254740992);
+
+        assert_ne!(a, b);
+        assert_eq!(b, c);
+        assert_ne!(a, c);
+        assert_eq!(a.total_cmp(&b), Ordering::Greater);
+        assert_eq!(b.total_cmp(&c), Ordering::Equal);
+        assert_eq!(a.total_cmp(&c), Ordering::Greater);
+        assert_eq!(b.total_cmp(&a), Ordering::Less);
+    }
+
+    #[test]
+    fn integers_against_fractions_and_extremes() {
+        let cases = [
+            (
+                NumberRepr::Integer(1),
+                NumberRepr::Float(1.5),
+                Ordering::Less,
+            ),
+            (
+                NumberRepr::Integer(-1),
+                NumberRepr::Float(-1.5),
+                Ordering::Greater,
+            ),
+            (
+                NumberRepr::Integer(0),
+                NumberRepr::Float(-0.0),
+                Ordering::Equal,
+            ),
+            (
+                NumberRepr::Integer(0),
+                NumberRepr::Float(-0.5),
+                Ordering::Greater,
+            ),
+            (
+                NumberRepr::Integer(i64::MAX),
+                NumberRepr::Float(9.3e18),
+                Ordering::Less,
+            ),
+            (
+                NumberRepr::Integer(i64::MIN),
+                NumberRepr::Float(i64::MIN as f64),
+                Ordering::Equal,
+            ),
+            (
+                NumberRepr::Integer(i64::MIN),
+                NumberRepr::Float(f64::NEG_INFINITY),
+                Ordering::Greater,
+            ),
+            (
+                NumberRepr::Integer(i64::MAX),
+                NumberRepr::Float(f64::INFINITY),
+                Ordering::Less,
+            ),
+            (
+                NumberRepr::Integer(i64::MAX),
+                NumberRepr::Float(f64::NAN),
+                Ordering::Less,
+            ),
+            (
+                NumberRepr::Raw("7".into()),
+                NumberRepr::Float(7.0),
+                Ordering::Equal,
+            ),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(a.total_cmp(&b), expected, "{a} vs {b}");
+            assert_eq!(b.total_cmp(&a), expected.reverse(), "{b} vs {a}");
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::ops::{Add, AddAssign};
+
+use crate::{NumberRepr, Value};
+
+/// `+` for the additive cases: arrays and strings concatenate and numbers
+/// add, as integers when both are and the sum fits in an `i64`
+///
+/// Any other pairing, including mismatched variants, gives `Null`.
+impl Add for Value {
+    type Output = Value;
+
+    fn add(mut self, rhs: Value) -> Value {
+        self += rhs;
+        self
+    }
+}
+
+/// In-place `+`, with the same rules as `Add`
+impl AddAssign for Value {
+    fn add_assign(&mut self, rhs: Value) {
+        match (&mut *self, rhs) {
+            (Value::Array(a), Value::Array(b)) => a.extend(b),
+            (Value::String(a), Value::String(b)) => a.push_str(&b),
+            (Value::Number(a), Value::Number(b)) => {
+                // integers stay exact until they'd overflow
+                *a = match (a.as_i64(), b.as_i64()) {
+                    (Some(x), Some(y)) => x
+                        .checked_add(y)
+                        .map_or(NumberRepr::Float(x as f64 + y as f64), NumberRepr::Integer),
+                    _ => NumberRepr::Float(a.as_f64() + b.as_f64()),
+                };
+            }
+            _ => *self = Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{json, Value};
+
+    #[test]
+    fn adds_compatible_values() {
+        assert_eq!(json!([1, 2]) + json!([3]), json!([1, 2, 3]));
+        assert_eq!(json!("foo") + json!("bar"), json!("foobar"));
+        assert_eq!(json!(1.5) + json!(2), json!(3.5));
+    }
+
+    #[test]
+    fn mismatched_types_give_null() {
+        assert_eq!(json!([1]) + json!("x"), Value::Null);
+        assert_eq!(json!({"a": 1}) + json!({"b": 2}), Value::Null);
+    }
+
+    #[test]
+    fn add_assign_in_place() {
+        let mut v = json!("a");
+        v += json!("b");
+        assert_eq!(v, "ab");
+
+        let mut n = json!(1);
+        n += json!(true);
+        assert_eq!(n, Value::Null);
+    }
+}
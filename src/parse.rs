@@ -1,22 +1,420 @@
-use std::collections::HashMap;
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    io,
+    iter::Peekable,
+    mem, slice, str,
+};
 
 use crate::{
-    tokenize::{tokenize, Token, TokenizeError},
-    Value,
+    tokenize::{
+        make_token, tokenize_into, tokenize_recovering, tokenize_with, Token, TokenizeError,
+        TokenizeOptions,
+    },
+    NumberRepr, Value,
 };
 
-type ParseResult = Result<Value, TokenParseError>;
+type ParseResult<D = Value> = Result<D, TokenParseError>;
+
+type Tokens<'t, 'a> = Peekable<slice::Iter<'t, Token<'a>>>;
+
+/// Options controlling which documents `parse_with_config` accepts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseConfig {
+    /// Only accept an object or array as the top-level value (the pre-RFC 7159 rule)
+    pub require_container: bool,
+
+    /// Have `parse_recovering` carry on past errors and report all of them,
+    /// rather than stopping at the first
+    pub error_recovery: bool,
+
+    /// Read empty or whitespace-only input as `null` instead of failing
+    /// with `ParseError::EmptyInput`
+    pub empty_as_null: bool,
+
+    /// Reject input that starts or ends with whitespace, for framing
+    /// protocols where the document must fill the frame exactly
+    pub no_surrounding_whitespace: bool,
+
+    /// Keep every number literal exactly as written, as
+    /// `NumberRepr::Raw`, instead of reading it into an `i64` or `f64`
+    pub preserve_raw_numbers: bool,
+
+    /// Accept the bare words `Infinity`, `-Infinity` and `NaN`, as written
+    /// by Python's `json` module, as numbers
+    pub allow_special_floats: bool,
+
+    /// Fail with `TokenParseError::DuplicateKey` when an object repeats a
+    /// key, instead of keeping the last value (RFC 8259 §4 says keys
+    /// SHOULD be unique)
+    pub reject_duplicate_keys: bool,
+}
+
+impl ParseConfig {
+    pub(crate) fn tokenize_options(&self) -> TokenizeOptions {
+        TokenizeOptions {
+            raw_numbers: self.preserve_raw_numbers,
+            special_floats: self.allow_special_floats,
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Value, ParseError> {
+    parse_with_config(input, &ParseConfig::default())
+}
+
+/// `"[1, 2]".parse::<Value>()`, the same as [`parse`]
+impl str::FromStr for Value {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+pub fn parse_with_config(input: &str, config: &ParseConfig) -> Result<Value, ParseError> {
+    parse_document(input, config)
+}
+
+/// `parse_with_config` for any kind of document
+pub(crate) fn parse_document<D: Document>(
+    input: &str,
+    config: &ParseConfig,
+) -> Result<D, ParseError> {
+    check_surrounding_whitespace(input, config)?;
+    let tokens = tokenize_with(input, config.tokenize_options())?;
+    parse_token_list(&mut tokens.iter().peekable(), config)
+}
 
-fn parse(input: String) -> Result<Value, ParseError> {
-    let tokens = tokenize(input)?;
-    let value = parse_tokens(&tokens, &mut 0)?;
+/// Parses the first value in `input`, returning it with the byte offset
+/// just past its last character
+///
+/// Whatever follows the value is left alone, so it needn't be JSON: a
+/// caller reading values from a stream carries on at the returned offset.
+/// Leading whitespace is skipped and counted in the offset.
+///
+/// ```
+/// use json_parser::{parse_prefix, Value};
+///
+/// assert_eq!(parse_prefix("true rest"), Ok((Value::Boolean(true), 4)));
+/// ```
+pub fn parse_prefix(input: &str) -> Result<(Value, usize), ParseError> {
+    let config = ParseConfig::default();
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < input.len() {
+        if input.as_bytes()[index].is_ascii_whitespace() {
+            index += 1;
+            continue;
+        }
+        let token = make_token(input, &mut index, config.tokenize_options())?;
+        index += 1;
+        match token {
+            Token::LeftBracket | Token::LeftBrace => depth += 1,
+            Token::RightBracket | Token::RightBrace => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let is_separator = matches!(token, Token::Comma | Token::Colon);
+        tokens.push(token);
+        // a lone separator or closing bracket is left for the parser to
+        // reject; anything else at depth zero completes the value
+        if depth == 0 && !is_separator {
+            break;
+        }
+    }
+
+    let value = parse_token_list(&mut tokens.iter().peekable(), &config)?;
+    Ok((value, index))
+}
+
+/// Parses many documents with the same configuration, reusing one token
+/// buffer instead of allocating a new one for every document
+///
+/// ```
+/// use json_parser::{json, Parser};
+///
+/// let mut parser = Parser::new();
+/// for message in [r#"{"id": 1}"#, r#"{"id": 2}"#] {
+///     let value = parser.parse_reuse(message).unwrap();
+///     assert!(value["id"].is_number());
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Parser {
+    config: ParseConfig,
+    /// Always empty between calls, only its capacity is kept
+    scratch: Vec<Token<'static>>,
+}
+
+impl Parser {
+    /// A parser using the default `ParseConfig`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A parser using `config` for every document
+    pub fn with_config(config: ParseConfig) -> Self {
+        Self {
+            config,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Parses `input` like `parse_with_config`, tokenizing into the
+    /// buffer left by the previous call
+    pub fn parse_reuse(&mut self, input: &str) -> Result<Value, ParseError> {
+        check_surrounding_whitespace(input, &self.config)?;
+        let mut tokens = recycle(mem::take(&mut self.scratch));
+        let result = tokenize_into(input, self.config.tokenize_options(), &mut tokens)
+            .map_err(ParseError::from)
+            .and_then(|()| parse_token_list(&mut tokens.iter().peekable(), &self.config));
+        self.scratch = recycle(tokens);
+        result
+    }
+}
+
+/// Empties `tokens` and hands back its allocation with a new lifetime
+///
+/// `Token<'a>` has the same layout for every `'a`, so collecting the empty
+/// iterator reuses the buffer in place.
+fn recycle<'b>(mut tokens: Vec<Token<'_>>) -> Vec<Token<'b>> {
+    tokens.clear();
+    tokens.into_iter().map(|_| unreachable!()).collect()
+}
+
+/// Parses a whole document from its tokens
+///
+/// On error the last token taken from `tokens`, if any, is the one that
+/// was rejected.
+pub(crate) fn parse_token_list<D: Document>(
+    tokens: &mut Tokens,
+    config: &ParseConfig,
+) -> Result<D, ParseError> {
+    if tokens.peek().is_none() {
+        return empty_input(config);
+    }
+    let value: D = parse_top_level(tokens, config, &mut D::Keys::default())?;
+    if tokens.next().is_some() {
+        return Err(TokenParseError::TrailingTokens.into());
+    }
+    if config.require_container && !value.is_container() {
+        return Err(TokenParseError::TopLevelMustBeContainer.into());
+    }
     Ok(value)
 }
 
+/// Parses `input`, reporting every error found when `error_recovery` is
+/// set and only the first one otherwise
+pub fn parse_recovering(input: &str, config: &ParseConfig) -> Result<Value, Vec<ParseError>> {
+    if !config.error_recovery {
+        return parse_with_config(input, config).map_err(|e| vec![e]);
+    }
+    match parse_best_effort(input, config) {
+        (value, errors) if errors.is_empty() => Ok(value),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Parses as much of `input` as possible, returning the value along with
+/// every error found on the way
+///
+/// Input the tokenizer doesn't recognise is read as `null` and tokenizing
+/// resumes at the next whitespace or punctuation, so `[1, @, 3]` gives
+/// `[1, null, 3]`. Structural errors such as a missing comma still end
+/// the parse, with `null` as the value.
+pub fn parse_best_effort(input: &str, config: &ParseConfig) -> (Value, Vec<ParseError>) {
+    let (tokens, errors) = tokenize_recovering(input, config.tokenize_options());
+    let mut errors: Vec<ParseError> = errors.into_iter().map(ParseError::from).collect();
+    if let Err(e) = check_surrounding_whitespace(input, config) {
+        errors.insert(0, e);
+    }
+    if tokens.is_empty() && errors.is_empty() {
+        return match empty_input(config) {
+            Ok(value) => (value, errors),
+            Err(e) => (Value::Null, vec![e]),
+        };
+    }
+
+    let mut tokens = tokens.iter().peekable();
+    let value = match parse_top_level(&mut tokens, config, &mut ()) {
+        Ok(value) => {
+            if tokens.next().is_some() {
+                errors.push(TokenParseError::TrailingTokens.into());
+            }
+            value
+        }
+        Err(e) => {
+            errors.push(e.into());
+            Value::Null
+        }
+    };
+    if config.require_container && !matches!(value, Value::Array(_) | Value::Object(_)) {
+        errors.push(TokenParseError::TopLevelMustBeContainer.into());
+    }
+    (value, errors)
+}
+
+pub(crate) fn check_surrounding_whitespace(
+    input: &str,
+    config: &ParseConfig,
+) -> Result<(), ParseError> {
+    let is_whitespace = |b: &u8| matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+    let bytes = input.as_bytes();
+    if config.no_surrounding_whitespace
+        && (bytes.first().is_some_and(is_whitespace) || bytes.last().is_some_and(is_whitespace))
+    {
+        return Err(ParseError::SurroundingWhitespace);
+    }
+    Ok(())
+}
+
+fn empty_input<D: Document>(config: &ParseConfig) -> Result<D, ParseError> {
+    if config.empty_as_null {
+        Ok(D::null())
+    } else {
+        Err(ParseError::EmptyInput)
+    }
+}
+
+/// Parses a document given as raw bytes
+///
+/// The input is read as UTF-8 unless it starts with a UTF-32 or UTF-16
+/// byte order mark, in which case it's transcoded first. A UTF-8 byte
+/// order mark is skipped.
+pub fn from_bytes(bytes: &[u8]) -> Result<Value, ParseError> {
+    let input = decode(bytes)?;
+    parse(&input)
+}
+
+fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, ParseError> {
+    // the UTF-32LE mark starts with the UTF-16LE one, so it goes first
+    let transcoded = match bytes {
+        [0xff, 0xfe, 0, 0, rest @ ..] => decode_utf32(rest, u32::from_le_bytes),
+        [0, 0, 0xfe, 0xff, rest @ ..] => decode_utf32(rest, u32::from_be_bytes),
+        [0xff, 0xfe, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+        [0xfe, 0xff, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+        [0xef, 0xbb, 0xbf, rest @ ..] => {
+            return str::from_utf8(rest)
+                .map(Cow::Borrowed)
+                .map_err(|e| ParseError::InvalidUtf8 {
+                    valid_up_to: 3 + e.valid_up_to(),
+                })
+        }
+        _ => return Ok(Cow::Borrowed(str::from_utf8(bytes)?)),
+    };
+    transcoded.ok_or(ParseError::UnsupportedEncoding)
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Option<Cow<'_, str>> {
+    let (chunks, []) = bytes.as_chunks::<2>() else {
+        return None;
+    };
+    let units = chunks.iter().map(|chunk| unit(*chunk));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .ok()
+        .map(Cow::Owned)
+}
+
+fn decode_utf32(bytes: &[u8], unit: fn([u8; 4]) -> u32) -> Option<Cow<'_, str>> {
+    let (chunks, []) = bytes.as_chunks::<4>() else {
+        return None;
+    };
+    chunks
+        .iter()
+        .map(|chunk| char::from_u32(unit(*chunk)))
+        .collect::<Option<String>>()
+        .map(Cow::Owned)
+}
+
+/// Parses a document whose root must be an object
+pub fn parse_object(input: &str) -> Result<HashMap<String, Value>, ParseError> {
+    parse(input)?
+        .into_object()
+        .map_err(|_| ParseError::WrongType)
+}
+
+/// Parses a document whose root must be an array
+pub fn parse_array(input: &str) -> Result<Vec<Value>, ParseError> {
+    parse(input)?
+        .into_array()
+        .map_err(|_| ParseError::WrongType)
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
     TokenizeError(TokenizeError),
     ParseError(TokenParseError),
+
+    /// Reading the input failed
+    Io(io::ErrorKind),
+
+    /// The input was empty or only whitespace
+    EmptyInput,
+
+    /// The input started or ended with whitespace while
+    /// `no_surrounding_whitespace` was set
+    SurroundingWhitespace,
+
+    /// The root value was valid JSON but not the kind that was asked for
+    WrongType,
+
+    /// Bytes without a UTF-16/UTF-32 byte order mark weren't valid UTF-8;
+    /// the bytes before `valid_up_to` were
+    InvalidUtf8 {
+        valid_up_to: usize,
+    },
+
+    /// The bytes after a UTF-16/UTF-32 byte order mark were malformed in
+    /// the encoding the mark announced
+    UnsupportedEncoding,
+
+    /// A query string had a malformed `%XX` escape or wasn't UTF-8
+    InvalidQueryString,
+
+    /// A line of `gron` input wasn't a valid assignment; `line` counts
+    /// from 1
+    InvalidGron {
+        line: usize,
+    },
+
+    /// The JSON parsed but couldn't be turned into the requested Rust type
+    #[cfg(feature = "serde")]
+    Deserialize(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TokenizeError(e) => e.fmt(f),
+            Self::ParseError(e) => e.fmt(f),
+            Self::Io(kind) => write!(f, "failed to read input: {kind}"),
+            Self::EmptyInput => f.write_str("input is empty"),
+            Self::SurroundingWhitespace => f.write_str("input has leading or trailing whitespace"),
+            Self::WrongType => f.write_str("root value has the wrong type"),
+            Self::InvalidUtf8 { valid_up_to } => {
+                write!(f, "input is not valid UTF-8 after byte {valid_up_to}")
+            }
+            Self::UnsupportedEncoding => f.write_str("input is not valid UTF-16 or UTF-32"),
+            Self::InvalidQueryString => f.write_str("malformed query string"),
+            Self::InvalidGron { line } => write!(f, "invalid gron assignment on line {line}"),
+            #[cfg(feature = "serde")]
+            Self::Deserialize(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::TokenizeError(e) => Some(e),
+            Self::ParseError(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<TokenizeError> for ParseError {
@@ -25,49 +423,184 @@ impl From<TokenizeError> for ParseError {
     }
 }
 
+impl From<str::Utf8Error> for ParseError {
+    fn from(value: str::Utf8Error) -> Self {
+        Self::InvalidUtf8 {
+            valid_up_to: value.valid_up_to(),
+        }
+    }
+}
+
 impl From<TokenParseError> for ParseError {
     fn from(value: TokenParseError) -> Self {
         Self::ParseError(value)
     }
 }
 
-fn parse_tokens(tokens: &[Token], index: &mut usize) -> ParseResult {
-    let token = &tokens[*index];
-    if matches!(
-        token,
-        Token::Null | Token::False | Token::True | Token::Number(_) | Token::String(_)
-    ) {
-        *index += 1;
+/// The tree the parser builds, so the same grammar can produce a `Value`
+/// or an [`InternedValue`](crate::InternedValue)
+pub(crate) trait Document: Sized {
+    type Key: Eq + Hash + Borrow<str>;
+
+    /// Kept for a whole document while building its keys
+    type Keys: Default;
+
+    fn key(keys: &mut Self::Keys, key: Cow<str>) -> Self::Key;
+    fn null() -> Self;
+    fn boolean(value: bool) -> Self;
+    fn number(num: NumberRepr) -> Self;
+    fn string(s: String) -> Self;
+    fn array(array: Vec<Self>) -> Self;
+    fn object(object: HashMap<Self::Key, Self>) -> Self;
+    fn is_container(&self) -> bool;
+}
+
+impl Document for Value {
+    type Key = String;
+    type Keys = ();
+
+    fn key(_keys: &mut (), key: Cow<str>) -> String {
+        key.into_owned()
+    }
+
+    fn null() -> Self {
+        Value::Null
+    }
+
+    fn boolean(value: bool) -> Self {
+        Value::Boolean(value)
     }
-    match token {
-        Token::Null => Ok(Value::Null),
-        Token::False => Ok(Value::Boolean(false)),
-        Token::True => Ok(Value::Boolean(true)),
-        Token::Number(num) => Ok(Value::Number(*num)),
-        Token::String(s) => parse_string(s),
-        Token::LeftBracket => parse_array(tokens, index),
-        Token::LeftBrace => parse_object(tokens, index),
-        _ => todo!(),
+
+    fn number(num: NumberRepr) -> Self {
+        Value::Number(num)
+    }
+
+    fn string(s: String) -> Self {
+        Value::String(s)
     }
+
+    fn array(array: Vec<Self>) -> Self {
+        Value::Array(array)
+    }
+
+    fn object(object: HashMap<String, Self>) -> Self {
+        Value::Object(object)
+    }
+
+    fn is_container(&self) -> bool {
+        matches!(self, Value::Array(_) | Value::Object(_))
+    }
+}
+
+fn parse_tokens<D: Document>(
+    tokens: &mut Tokens,
+    config: &ParseConfig,
+    keys: &mut D::Keys,
+) -> ParseResult<D> {
+    match tokens.next() {
+        Some(Token::Null) => Ok(D::null()),
+        Some(Token::False) => Ok(D::boolean(false)),
+        Some(Token::True) => Ok(D::boolean(true)),
+        Some(Token::Number(num)) => Ok(D::number(num.clone())),
+        Some(Token::String(s)) => unescape_string(s).map(D::string),
+        Some(Token::StringRef(s)) => Ok(D::string(s.to_string())),
+        Some(Token::LeftBracket) => parse_array_tokens(tokens, config, keys),
+        Some(Token::LeftBrace) => parse_object_tokens(tokens, config, keys),
+        Some(Token::RightBrace | Token::RightBracket | Token::Comma | Token::Colon) => {
+            Err(TokenParseError::ExpectedValue)
+        }
+        None => Err(TokenParseError::UnexpectedEof),
+    }
+}
+
+/// Parses the top-level value, where stray punctuation is reported as
+/// `UnexpectedToken` rather than a missing value
+fn parse_top_level<D: Document>(
+    tokens: &mut Tokens,
+    config: &ParseConfig,
+    keys: &mut D::Keys,
+) -> ParseResult<D> {
+    if let Some(token @ (Token::RightBrace | Token::RightBracket | Token::Comma | Token::Colon)) =
+        tokens.peek()
+    {
+        return Err(TokenParseError::UnexpectedToken(
+            (*token).clone().into_owned(),
+        ));
+    }
+    parse_tokens(tokens, config, keys)
 }
 
 #[derive(Debug, PartialEq)]
 pub enum TokenParseError {
-    /// An escape sequence was started without 4 hexadecimaldigits afterwards
+    /// An escape sequence was cut short: `\u` without 4 hexadecimal digits
+    /// afterwards, or a `\` ending the string
     UnfinishedEscape,
 
+    /// A `\` followed by a character that doesn't start an escape
+    InvalidEscape(char),
+
     /// A character in an escape sequence was not valid hexadecimal
     InvalidHexValue,
 
     /// Invalid unicode value
     InvalidCodePointValue,
 
+    /// A `\u` escape of half a UTF-16 surrogate pair without the other
+    /// half, such as `\uD83D` alone
+    LoneSurrogate,
+
     ExpectedComma,
     ExpectedProperty,
     ExpectedColon,
+
+    /// The input ended in the middle of a value, e.g. `{"a":`, unlike
+    /// `ExpectedValue` where a token was found but isn't a value
+    UnexpectedEof,
+
+    /// A value was expected but punctuation such as `}` or `,` was found
+    ExpectedValue,
+
+    /// Punctuation such as `}` or `,` where the top-level value belongs
+    UnexpectedToken(Token<'static>),
+
+    /// The top-level value was a scalar while `require_container` was set
+    TopLevelMustBeContainer,
+
+    /// More tokens followed the complete top-level value
+    TrailingTokens,
+
+    /// An object repeated this key while `reject_duplicate_keys` was set
+    DuplicateKey(String),
 }
 
-fn parse_string(input: &str) -> ParseResult {
+impl fmt::Display for TokenParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnfinishedEscape => "unfinished escape sequence",
+            Self::InvalidEscape(c) => return write!(f, "invalid escape sequence \\{c}"),
+            Self::InvalidHexValue => "invalid hex digit in \\u escape",
+            Self::InvalidCodePointValue => "escape is not a valid unicode code point",
+            Self::LoneSurrogate => "\\u escape of an unpaired surrogate",
+            Self::ExpectedComma => "expected `,`",
+            Self::ExpectedProperty => "expected an object key",
+            Self::ExpectedColon => "expected `:`",
+            Self::UnexpectedEof => "unexpected end of input",
+            Self::ExpectedValue => "expected a value",
+            Self::UnexpectedToken(token) => return write!(f, "unexpected `{token}`"),
+            Self::TopLevelMustBeContainer => "top-level value must be an object or array",
+            Self::TrailingTokens => "unexpected data after the top-level value",
+            Self::DuplicateKey(key) => return write!(f, "duplicate object key {key:?}"),
+        })
+    }
+}
+
+impl std::error::Error for TokenParseError {}
+
+/// Resolves the escape sequences in the content of a JSON string, given
+/// without its surrounding quotes
+///
+/// The inverse of [`escape_string`](crate::escape_string).
+pub fn unescape_string(input: &str) -> Result<String, TokenParseError> {
     let mut output = String::new();
 
     let mut is_escaping = false;
@@ -78,24 +611,35 @@ fn parse_string(input: &str) -> ParseResult {
                 '"' => output.push('"'),
                 '\\' => output.push('\\'),
                 'b' => output.push('\u{8}'),
-                'f' => output.push('\u{12}'),
+                'f' => output.push('\u{c}'),
                 'n' => output.push('\n'),
                 'r' => output.push('\r'),
                 't' => output.push('\t'),
+                '/' => output.push('/'),
                 'u' => {
-                    let mut sum = 0;
-                    for i in 0..4 {
-                        let next_char = chars.next().ok_or(TokenParseError::UnfinishedEscape)?;
-                        let digit = next_char
-                            .to_digit(16)
-                            .ok_or(TokenParseError::InvalidHexValue)?;
-                        sum += (16u32).pow(3 - i) * digit;
-                    }
+                    let unit = read_hex_escape(&mut chars)?;
+                    let code_point = match unit {
+                        // a high surrogate has to be followed by the
+                        // `\u` escape of a low one
+                        0xD800..=0xDBFF => {
+                            let Some(rest) = chars.as_str().strip_prefix("\\u") else {
+                                return Err(TokenParseError::LoneSurrogate);
+                            };
+                            chars = rest.chars();
+                            let low = read_hex_escape(&mut chars)?;
+                            if !(0xDC00..=0xDFFF).contains(&low) {
+                                return Err(TokenParseError::LoneSurrogate);
+                            }
+                            0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                        }
+                        0xDC00..=0xDFFF => return Err(TokenParseError::LoneSurrogate),
+                        _ => unit,
+                    };
                     let unescaped_char =
-                        char::from_u32(sum).ok_or(TokenParseError::InvalidCodePointValue)?;
+                        char::from_u32(code_point).ok_or(TokenParseError::InvalidCodePointValue)?;
                     output.push(unescaped_char);
                 }
-                _ => output.push(next_char),
+                c => return Err(TokenParseError::InvalidEscape(c)),
             }
             is_escaping = false;
         } else if next_char == '\\' {
@@ -104,44 +648,59 @@ fn parse_string(input: &str) -> ParseResult {
             output.push(next_char);
         }
     }
-    Ok(Value::String(output))
+    if is_escaping {
+        return Err(TokenParseError::UnfinishedEscape);
+    }
+    Ok(output)
+}
+
+/// Reads the 4 hexadecimal digits after a `\u`
+fn read_hex_escape(chars: &mut std::str::Chars) -> Result<u32, TokenParseError> {
+    let mut sum = 0;
+    for _ in 0..4 {
+        let next_char = chars.next().ok_or(TokenParseError::UnfinishedEscape)?;
+        let digit = next_char
+            .to_digit(16)
+            .ok_or(TokenParseError::InvalidHexValue)?;
+        sum = sum * 16 + digit;
+    }
+    Ok(sum)
 }
 
 // [null, [null]]
-fn parse_array(tokens: &[Token], index: &mut usize) -> ParseResult {
+fn parse_array_tokens<D: Document>(
+    tokens: &mut Tokens,
+    config: &ParseConfig,
+    keys: &mut D::Keys,
+) -> ParseResult<D> {
     let mut array = Vec::new();
-    println!("token= {:?}, index= {index}", tokens[*index]);
 
     loop {
-        // if *index == tokens.len() {
-        //     break;
-        // }
-        *index += 1;
-        if tokens[*index] == Token::RightBracket {
+        if tokens.next_if_eq(&&Token::RightBracket).is_some() {
             break;
         }
-        // println!("token= {:?}, index= {index}", tokens[*index]);
 
-        let value = parse_tokens(tokens, index)?;
+        let value = parse_tokens(tokens, config, keys)?;
         array.push(value);
 
-        // *index += 1;
-        let token = &tokens[*index];
-
-        match token {
+        match tokens.next() {
             // ','就继续解析下一个token
-            Token::Comma => {}
+            Some(Token::Comma) => {}
             // ']'表示结束
-            Token::RightBracket => break,
+            Some(Token::RightBracket) => break,
+            None => return Err(TokenParseError::UnexpectedEof),
             _ => return Err(TokenParseError::ExpectedComma),
         }
     }
-    *index += 1;
 
-    Ok(Value::Array(array))
+    Ok(D::array(array))
 }
 
-fn parse_object(tokens: &[Token], index: &mut usize) -> ParseResult {
+fn parse_object_tokens<D: Document>(
+    tokens: &mut Tokens,
+    config: &ParseConfig,
+    keys: &mut D::Keys,
+) -> ParseResult<D> {
     // OK cases
     // LeftBrace -> RightBrace
     // LeftBrace -> String -> Colon -> Value -> RightBrace
@@ -149,48 +708,271 @@ fn parse_object(tokens: &[Token], index: &mut usize) -> ParseResult {
     let mut object = HashMap::new();
 
     loop {
-        *index += 1;
-
-        if tokens[*index] == Token::RightBrace {
+        if tokens.next_if_eq(&&Token::RightBrace).is_some() {
             break;
         }
         // { string1 : value1, string2 : value2, string3 : value3 }
-        if let Token::String(s) = &tokens[*index] {
-            *index += 1;
-            if let Token::Colon = tokens[*index] {
-                *index += 1;
-                let key = s.clone();
-                let vlaue = parse_tokens(tokens, index)?;
-                object.insert(key, vlaue);
-
-                match &tokens[*index] {
-                    Token::Comma => {}
-                    Token::RightBrace => break,
-                    _ => return Err(TokenParseError::ExpectedComma),
-                }
-            } else {
-                return Err(TokenParseError::ExpectedColon);
-            }
-        } else {
-            return Err(TokenParseError::ExpectedProperty);
+        let key = match tokens.next() {
+            Some(Token::String(s)) => Cow::Owned(unescape_string(s)?),
+            Some(Token::StringRef(s)) => Cow::Borrowed(*s),
+            None => return Err(TokenParseError::UnexpectedEof),
+            _ => return Err(TokenParseError::ExpectedProperty),
+        };
+        match tokens.next() {
+            Some(Token::Colon) => {}
+            None => return Err(TokenParseError::UnexpectedEof),
+            _ => return Err(TokenParseError::ExpectedColon),
+        }
+        if config.reject_duplicate_keys && object.contains_key(&*key) {
+            return Err(TokenParseError::DuplicateKey(key.into_owned()));
+        }
+        let value = parse_tokens(tokens, config, keys)?;
+        object.insert(D::key(keys, key), value);
+
+        match tokens.next() {
+            Some(Token::Comma) => {}
+            Some(Token::RightBrace) => break,
+            None => return Err(TokenParseError::UnexpectedEof),
+            _ => return Err(TokenParseError::ExpectedComma),
         }
     }
 
-    *index += 1;
-    Ok(Value::Object(object))
+    Ok(D::object(object))
 }
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use super::parse_tokens;
-    use crate::tokenize::Token;
-    use crate::Value;
+    use super::{
+        from_bytes, parse, parse_array, parse_best_effort, parse_object, parse_prefix,
+        parse_recovering, parse_tokens, parse_with_config, unescape_string, ParseConfig,
+        ParseError, Parser, TokenParseError,
+    };
+    use crate::tokenize::{Token, TokenizeError};
+    use crate::NumberRepr;
+    use crate::{json, Value};
 
     fn check(input: &[Token], expected: Value) {
-        let actual = parse_tokens(input, &mut 0).unwrap();
+        let actual: Value = parse_tokens(
+            &mut input.iter().peekable(),
+            &ParseConfig::default(),
+            &mut (),
+        )
+        .unwrap();
         assert_eq!(actual, expected);
     }
+    #[test]
+    fn from_str_parses_every_kind() {
+        assert_eq!("null".parse::<Value>(), Ok(Value::Null));
+        assert_eq!("true".parse::<Value>(), Ok(Value::Boolean(true)));
+        assert_eq!("-1.5".parse::<Value>(), Ok(Value::num(-1.5)));
+        assert_eq!(r#""s""#.parse::<Value>(), Ok(Value::String("s".into())));
+        assert_eq!("[1, 2]".parse::<Value>(), Ok(json!([1, 2])));
+        assert_eq!(r#"{"a":1}"#.parse::<Value>(), Ok(json!({"a": 1})));
+    }
+
+    #[test]
+    fn from_str_rejects_trailing_garbage() {
+        let err = "[1] @".parse::<Value>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::TokenizeError(TokenizeError::CharNotRecognized('@'))
+        );
+        assert_eq!(err.to_string(), "unexpected character '@'");
+
+        let err: Box<dyn std::error::Error> = Box::new(err);
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn special_floats_rejected_by_default() {
+        assert_eq!(
+            parse("Infinity"),
+            Err(ParseError::TokenizeError(TokenizeError::CharNotRecognized(
+                'I'
+            )))
+        );
+        assert_eq!(
+            parse("[NaN]"),
+            Err(ParseError::TokenizeError(TokenizeError::CharNotRecognized(
+                'N'
+            )))
+        );
+        assert_eq!(
+            parse("-Infinity"),
+            Err(ParseError::TokenizeError(TokenizeError::ParseNumberError))
+        );
+    }
+
+    #[test]
+    fn special_floats_accepted_when_allowed() {
+        let config = ParseConfig {
+            allow_special_floats: true,
+            ..ParseConfig::default()
+        };
+        let value = parse_with_config("[Infinity, -Infinity, NaN, -1]", &config).unwrap();
+        assert!(matches!(
+            value[0],
+            Value::Number(NumberRepr::Float(f64::INFINITY))
+        ));
+        assert!(matches!(
+            value[1],
+            Value::Number(NumberRepr::Float(f64::NEG_INFINITY))
+        ));
+        assert!(value[2].as_f64().unwrap().is_nan());
+        assert_eq!(value[3], json!(-1));
+
+        assert_eq!(
+            parse_with_config("Inf", &config),
+            Err(ParseError::TokenizeError(TokenizeError::CharNotRecognized(
+                'I'
+            )))
+        );
+
+        let raw = ParseConfig {
+            preserve_raw_numbers: true,
+            ..config
+        };
+        let value = parse_with_config("-Infinity", &raw).unwrap();
+        assert!(matches!(&value, Value::Number(NumberRepr::Raw(s)) if s == "-Infinity"));
+        assert_eq!(value.as_f64(), Some(f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn parser_reuses_its_token_buffer() {
+        let mut parser = Parser::new();
+        assert_eq!(parser.parse_reuse("[1, 2, 3, 4]"), Ok(json!([1, 2, 3, 4])));
+        let (capacity, buffer) = (parser.scratch.capacity(), parser.scratch.as_ptr());
+        assert!(capacity >= 9);
+
+        assert_eq!(parser.parse_reuse(r#"{"a": true}"#), Ok(json!({"a": true})));
+        assert_eq!(
+            parser.parse_reuse("[1,"),
+            Err(ParseError::ParseError(TokenParseError::UnexpectedEof))
+        );
+        assert_eq!(
+            parser.parse_reuse("[@]"),
+            Err(ParseError::TokenizeError(TokenizeError::CharNotRecognized(
+                '@'
+            )))
+        );
+        assert_eq!(parser.parse_reuse("null"), Ok(Value::Null));
+        assert!(parser.scratch.is_empty());
+        assert_eq!(parser.scratch.capacity(), capacity);
+        assert_eq!(parser.scratch.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn parser_applies_its_config() {
+        let mut parser = Parser::with_config(ParseConfig {
+            empty_as_null: true,
+            require_container: true,
+            ..ParseConfig::default()
+        });
+        assert_eq!(parser.parse_reuse(" "), Ok(Value::Null));
+        assert_eq!(
+            parser.parse_reuse("1"),
+            Err(ParseError::ParseError(
+                TokenParseError::TopLevelMustBeContainer
+            ))
+        );
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let strict = ParseConfig {
+            reject_duplicate_keys: true,
+            ..ParseConfig::default()
+        };
+        let input = r#"{"a":1,"a":2}"#;
+        assert_eq!(
+            parse_with_config(input, &strict),
+            Err(ParseError::ParseError(TokenParseError::DuplicateKey(
+                "a".to_string()
+            )))
+        );
+        assert_eq!(parse(input), Ok(json!({"a": 2})));
+
+        // keys are compared once their escapes are resolved
+        let escaped = r#"{"a":1,"\u0061":2}"#;
+        assert_eq!(
+            parse_with_config(escaped, &strict),
+            Err(ParseError::ParseError(TokenParseError::DuplicateKey(
+                "a".to_string()
+            )))
+        );
+        assert_eq!(parse(escaped), Ok(json!({"a": 2})));
+
+        // nested objects are checked, and the same key in sibling objects
+        // is fine
+        assert_eq!(
+            parse_with_config(r#"[{"k":1},{"k":2,"x":{"y":0,"y":0}}]"#, &strict),
+            Err(ParseError::ParseError(TokenParseError::DuplicateKey(
+                "y".to_string()
+            )))
+        );
+        assert_eq!(
+            parse_with_config(r#"{"a":{"a":1},"b":{"a":2}}"#, &strict),
+            Ok(json!({"a": {"a": 1}, "b": {"a": 2}}))
+        );
+        assert_eq!(
+            TokenParseError::DuplicateKey("a".to_string()).to_string(),
+            r#"duplicate object key "a""#
+        );
+    }
+
+    #[test]
+    fn trailing_tokens_are_rejected() {
+        for input in ["true false", "1 2", "{}[]", "1]", r#""a":"b""#] {
+            assert_eq!(
+                parse(input),
+                Err(ParseError::ParseError(TokenParseError::TrailingTokens)),
+                "{input}"
+            );
+            assert_eq!(
+                input.parse::<Value>(),
+                Err(ParseError::ParseError(TokenParseError::TrailingTokens))
+            );
+        }
+        assert_eq!(parse(" [1] \n"), Ok(json!([1])));
+    }
+
+    #[test]
+    fn parse_prefix_stops_after_the_first_value() {
+        assert_eq!(parse_prefix("true rest"), Ok((Value::Boolean(true), 4)));
+        assert_eq!(
+            parse_prefix(r#"  {"a": [1, "]"]}{"b": 2}"#),
+            Ok((json!({"a": [1, "]"]}), 17))
+        );
+        assert_eq!(parse_prefix("12\n13"), Ok((json!(12), 2)));
+        assert_eq!(parse_prefix("[]"), Ok((json!([]), 2)));
+
+        let stream = r#"{"id": 1} {"id": 2} "#;
+        let (first, used) = parse_prefix(stream).unwrap();
+        let (second, _) = parse_prefix(&stream[used..]).unwrap();
+        assert_eq!([first, second], [json!({"id": 1}), json!({"id": 2})]);
+    }
+
+    #[test]
+    fn parse_prefix_errors() {
+        assert_eq!(parse_prefix(" "), Err(ParseError::EmptyInput));
+        assert_eq!(
+            parse_prefix("[1, 2"),
+            Err(ParseError::ParseError(TokenParseError::UnexpectedEof))
+        );
+        assert!(matches!(
+            parse_prefix("]"),
+            Err(ParseError::ParseError(TokenParseError::UnexpectedToken(_)))
+        ));
+        assert!(matches!(
+            parse_prefix("@"),
+            Err(ParseError::TokenizeError(TokenizeError::CharNotRecognized(
+                '@'
+            )))
+        ));
+    }
+
     #[test]
     fn parses_null() {
         let input = [Token::Null];
@@ -226,7 +1008,7 @@ mod tests {
     #[test]
     fn parses_string_unescape_backslash() {
         let input = [Token::String(r#"hello\\world"#.into())];
-        let expected = Value::String(r#"hello\\world"#.into());
+        let expected = Value::String(r#"hello\world"#.into());
 
         check(&input, expected);
     }
@@ -245,10 +1027,10 @@ mod tests {
             Token::LeftBracket,
             Token::Null,
             Token::Comma,
-            Token::Number(16.0),
+            Token::Number(NumberRepr::Integer(16)),
             Token::RightBracket,
         ];
-        let expected = Value::Array(vec![Value::Null, Value::Number(16.0)]);
+        let expected = Value::Array(vec![Value::Null, Value::num(16.0)]);
 
         check(&input, expected);
     }
@@ -315,7 +1097,7 @@ mod tests {
         ];
         let expected = Value::Object(
             [(
-                "\\u540D\\u524D".to_string(),
+                "\u{540D}\u{524D}".to_string(),
                 Value::String("davimiku".to_string()),
             )]
             .into(),
@@ -323,4 +1105,453 @@ mod tests {
 
         check(&input, expected);
     }
+
+    #[test]
+    fn bare_number_allowed_by_default() {
+        assert_eq!(parse("42"), Ok(Value::num(42.0)));
+    }
+
+    #[test]
+    fn bare_number_rejected_when_container_required() {
+        let config = ParseConfig {
+            require_container: true,
+            ..ParseConfig::default()
+        };
+
+        assert_eq!(
+            parse_with_config("42", &config),
+            Err(TokenParseError::TopLevelMustBeContainer.into())
+        );
+        assert_eq!(
+            parse_with_config(r#""hi""#, &config),
+            Err(TokenParseError::TopLevelMustBeContainer.into())
+        );
+    }
+
+    #[test]
+    fn containers_allowed_when_container_required() {
+        let config = ParseConfig {
+            require_container: true,
+            ..ParseConfig::default()
+        };
+
+        assert_eq!(
+            parse_with_config("[42]", &config),
+            Ok(Value::Array(vec![Value::num(42.0)]))
+        );
+        assert_eq!(
+            parse_with_config("{}", &config),
+            Ok(Value::Object(HashMap::new()))
+        );
+    }
+
+    #[test]
+    fn parse_object_returns_map() {
+        assert_eq!(
+            parse_object(r#"{"a": null}"#),
+            Ok(HashMap::from([("a".to_string(), Value::Null)]))
+        );
+    }
+
+    #[test]
+    fn parse_object_wrong_type() {
+        assert_eq!(parse_object("[]"), Err(ParseError::WrongType));
+    }
+
+    #[test]
+    fn parse_array_returns_vec() {
+        assert_eq!(
+            parse_array("[1, true]"),
+            Ok(vec![Value::num(1.0), Value::Boolean(true)])
+        );
+    }
+
+    #[test]
+    fn parse_array_wrong_type() {
+        assert_eq!(parse_array(r#""a""#), Err(ParseError::WrongType));
+    }
+
+    #[test]
+    fn parse_array_propagates_syntax_errors() {
+        assert_eq!(
+            parse_array("[1 2]"),
+            Err(TokenParseError::ExpectedComma.into())
+        );
+    }
+
+    #[test]
+    fn unescape_string_resolves_every_escape() {
+        assert_eq!(
+            unescape_string(r#"\"\\\/\b\f\n\r\té"#),
+            Ok("\"\\/\u{8}\u{c}\n\r\té".to_string())
+        );
+    }
+
+    #[test]
+    fn unescape_string_bad_hex() {
+        assert_eq!(
+            unescape_string(r"\u00zz"),
+            Err(TokenParseError::InvalidHexValue)
+        );
+    }
+
+    #[test]
+    fn unescape_string_rejects_unknown_escapes() {
+        for (input, c) in [(r"\a", 'a'), (r"a\'", '\''), (r"\x41", 'x'), ("\\é", 'é')] {
+            assert_eq!(
+                unescape_string(input),
+                Err(TokenParseError::InvalidEscape(c)),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            unescape_string("a\\"),
+            Err(TokenParseError::UnfinishedEscape)
+        );
+        assert_eq!(
+            unescape_string(r"\u12"),
+            Err(TokenParseError::UnfinishedEscape)
+        );
+    }
+
+    #[test]
+    fn unescape_string_joins_surrogate_pairs() {
+        assert_eq!(
+            unescape_string(r"\uD83D\uDE00"),
+            Ok("\u{1f600}".to_string())
+        );
+        assert_eq!(
+            unescape_string(r"a\ud834\udd1eb\u00e9"),
+            Ok("a\u{1d11e}b\u{e9}".to_string())
+        );
+        assert_eq!(
+            parse(r#"["\uD83D\uDE00"]"#),
+            Ok(Value::Array(vec![Value::String("\u{1f600}".to_string())]))
+        );
+    }
+
+    #[test]
+    fn unescape_string_rejects_lone_surrogates() {
+        for input in [
+            r"\uD83D",
+            r"\uD83Dx",
+            r"\uD83D\n",
+            r"\uD83D\uD83D",
+            r"\uD83D\u0041",
+            r"\uDE00",
+            r"\uDE00\uD83D",
+        ] {
+            assert_eq!(
+                unescape_string(input),
+                Err(TokenParseError::LoneSurrogate),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            unescape_string(r"\uD83D\u12"),
+            Err(TokenParseError::UnfinishedEscape)
+        );
+    }
+
+    fn encode_utf16(input: &str, bom: [u8; 2], unit: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        bom.into_iter()
+            .chain(input.encode_utf16().flat_map(unit))
+            .collect()
+    }
+
+    fn encode_utf32(input: &str, bom: [u8; 4], unit: fn(u32) -> [u8; 4]) -> Vec<u8> {
+        bom.into_iter()
+            .chain(input.chars().flat_map(|c| unit(c as u32)))
+            .collect()
+    }
+
+    fn object_a1() -> Value {
+        Value::Object(HashMap::from([("a".to_string(), Value::num(1.0))]))
+    }
+
+    #[test]
+    fn from_bytes_utf16le_with_bom() {
+        let bytes = encode_utf16(r#"{"a":1}"#, [0xff, 0xfe], u16::to_le_bytes);
+
+        assert_eq!(from_bytes(&bytes), Ok(object_a1()));
+    }
+
+    #[test]
+    fn from_bytes_utf16be_and_utf32() {
+        let input = r#"{"a":1}"#;
+
+        let bytes = encode_utf16(input, [0xfe, 0xff], u16::to_be_bytes);
+        assert_eq!(from_bytes(&bytes), Ok(object_a1()));
+        let bytes = encode_utf32(input, [0xff, 0xfe, 0, 0], u32::to_le_bytes);
+        assert_eq!(from_bytes(&bytes), Ok(object_a1()));
+        let bytes = encode_utf32(input, [0, 0, 0xfe, 0xff], u32::to_be_bytes);
+        assert_eq!(from_bytes(&bytes), Ok(object_a1()));
+    }
+
+    #[test]
+    fn from_bytes_utf16_surrogate_pair() {
+        let bytes = encode_utf16("[\"\u{1f600}\"]", [0xff, 0xfe], u16::to_le_bytes);
+
+        assert_eq!(
+            from_bytes(&bytes),
+            Ok(Value::Array(vec![Value::String("\u{1f600}".to_string())]))
+        );
+    }
+
+    #[test]
+    fn from_bytes_utf8_with_and_without_bom() {
+        assert_eq!(from_bytes(br#"{"a":1}"#), Ok(object_a1()));
+        assert_eq!(from_bytes(b"\xef\xbb\xbf{\"a\":1}"), Ok(object_a1()));
+    }
+
+    #[test]
+    fn from_bytes_invalid_utf8() {
+        // no byte order mark and not UTF-8
+        assert_eq!(
+            from_bytes(b"[\"\xff\"]"),
+            Err(ParseError::InvalidUtf8 { valid_up_to: 2 })
+        );
+        assert_eq!(
+            from_bytes(&[0xfe, 0xfe]),
+            Err(ParseError::InvalidUtf8 { valid_up_to: 0 })
+        );
+        // the offset counts the UTF-8 byte order mark
+        assert_eq!(
+            from_bytes(b"\xef\xbb\xbf[\xc3]"),
+            Err(ParseError::InvalidUtf8 { valid_up_to: 4 })
+        );
+        assert_eq!(
+            ParseError::InvalidUtf8 { valid_up_to: 4 }.to_string(),
+            "input is not valid UTF-8 after byte 4"
+        );
+        // `ff fe` alone is a UTF-16LE byte order mark with nothing after it
+        assert_eq!(from_bytes(&[0xff, 0xfe]), Err(ParseError::EmptyInput));
+    }
+
+    #[test]
+    fn from_bytes_unsupported_encoding() {
+        // odd number of bytes after a UTF-16 mark
+        assert_eq!(
+            from_bytes(b"\xff\xfe[\x00]"),
+            Err(ParseError::UnsupportedEncoding)
+        );
+    }
+
+    fn recovering() -> ParseConfig {
+        ParseConfig {
+            error_recovery: true,
+            ..ParseConfig::default()
+        }
+    }
+
+    #[test]
+    fn recovery_reports_every_error() {
+        assert_eq!(
+            parse_recovering("[1, @, tru, 3]", &recovering()),
+            Err(vec![
+                ParseError::TokenizeError(TokenizeError::CharNotRecognized('@')),
+                ParseError::TokenizeError(TokenizeError::UnfinishedLiteralValue),
+            ])
+        );
+    }
+
+    #[test]
+    fn without_recovery_only_first_error() {
+        assert_eq!(
+            parse_recovering("[1, @, tru, 3]", &ParseConfig::default()),
+            Err(vec![ParseError::TokenizeError(
+                TokenizeError::CharNotRecognized('@')
+            )])
+        );
+    }
+
+    #[test]
+    fn best_effort_reports_trailing_tokens() {
+        let (value, errors) = parse_best_effort("[1, @] 2", &recovering());
+        assert_eq!(value, json!([1, null]));
+        assert_eq!(
+            errors,
+            [
+                ParseError::TokenizeError(TokenizeError::CharNotRecognized('@')),
+                ParseError::ParseError(TokenParseError::TrailingTokens),
+            ]
+        );
+    }
+
+    #[test]
+    fn best_effort_replaces_unrecognised_input_with_null() {
+        let (value, errors) = parse_best_effort(r#"{"a": ?x?, "b": [2, #]}"#, &recovering());
+
+        assert_eq!(
+            value,
+            Value::Object(HashMap::from([
+                ("a".to_string(), Value::Null),
+                (
+                    "b".to_string(),
+                    Value::Array(vec![Value::num(2.0), Value::Null])
+                ),
+            ]))
+        );
+        assert_eq!(
+            errors,
+            [
+                ParseError::TokenizeError(TokenizeError::CharNotRecognized('?')),
+                ParseError::TokenizeError(TokenizeError::CharNotRecognized('#')),
+            ]
+        );
+    }
+
+    #[test]
+    fn recovery_of_valid_input() {
+        assert_eq!(
+            parse_recovering("[true]", &recovering()),
+            Ok(Value::Array(vec![Value::Boolean(true)]))
+        );
+    }
+
+    #[test]
+    fn recovery_stops_at_structural_errors() {
+        let (value, errors) = parse_best_effort("[1 2] @", &recovering());
+
+        assert_eq!(value, Value::Null);
+        assert_eq!(
+            errors,
+            [
+                ParseError::TokenizeError(TokenizeError::CharNotRecognized('@')),
+                ParseError::ParseError(TokenParseError::ExpectedComma),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_value_in_object() {
+        assert_eq!(
+            parse(r#"{"a":}"#),
+            Err(ParseError::ParseError(TokenParseError::ExpectedValue))
+        );
+        assert_eq!(
+            parse(r#"{"a":,"b":1}"#),
+            Err(ParseError::ParseError(TokenParseError::ExpectedValue))
+        );
+    }
+
+    #[test]
+    fn missing_colon_is_still_expected_colon() {
+        assert_eq!(
+            parse(r#"{"a" 1}"#),
+            Err(ParseError::ParseError(TokenParseError::ExpectedColon))
+        );
+    }
+
+    #[test]
+    fn punctuation_where_a_value_belongs() {
+        for input in ["[,1]", "[:]", r#"{"a"::1}"#] {
+            assert_eq!(
+                parse(input),
+                Err(ParseError::ParseError(TokenParseError::ExpectedValue)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn unexpected_token_at_top_level() {
+        let cases = [
+            ("]", Token::RightBracket),
+            ("}", Token::RightBrace),
+            (",", Token::Comma),
+            (":", Token::Colon),
+        ];
+        for (input, token) in cases {
+            let err = parse(input).unwrap_err();
+            assert_eq!(err.to_string(), format!("unexpected `{input}`"));
+            assert_eq!(
+                err,
+                ParseError::ParseError(TokenParseError::UnexpectedToken(token))
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_input_is_unexpected_eof() {
+        let inputs = [
+            "[1,",
+            "[1",
+            "[",
+            "{",
+            r#"{"a""#,
+            r#"{"a":"#,
+            r#"{"a":1"#,
+            r#"{"a":1,"#,
+            r#"[{"a": [1, {"b":"#,
+            r#"{"a": [[true, {}"#,
+        ];
+        for input in inputs {
+            assert_eq!(
+                parse(input),
+                Err(ParseError::ParseError(TokenParseError::UnexpectedEof)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncation_and_missing_values_are_told_apart() {
+        let cases = [
+            (r#"{"a":"#, TokenParseError::UnexpectedEof),
+            (r#"{"a":}"#, TokenParseError::ExpectedValue),
+            ("[1,", TokenParseError::UnexpectedEof),
+            ("[1,,]", TokenParseError::ExpectedValue),
+        ];
+        for (input, error) in cases {
+            assert_eq!(parse(input), Err(ParseError::ParseError(error)), "{input}");
+        }
+    }
+
+    #[test]
+    fn empty_input_is_an_error_by_default() {
+        for input in ["", "  \n\t "] {
+            assert_eq!(parse(input), Err(ParseError::EmptyInput), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn surrounding_whitespace_rejected_when_configured() {
+        let config = ParseConfig {
+            no_surrounding_whitespace: true,
+            ..ParseConfig::default()
+        };
+
+        assert_eq!(parse_with_config("null", &config), Ok(Value::Null));
+        assert_eq!(
+            parse_with_config("[1, 2]", &config),
+            Ok(json!([1, 2])),
+            "inner whitespace is fine"
+        );
+        for input in [" null ", " null", "null\n", "\t{}"] {
+            assert_eq!(
+                parse_with_config(input, &config),
+                Err(ParseError::SurroundingWhitespace),
+                "{input:?}"
+            );
+        }
+        assert_eq!(parse(" null "), Ok(Value::Null));
+    }
+
+    #[test]
+    fn empty_input_as_null() {
+        let config = ParseConfig {
+            empty_as_null: true,
+            ..ParseConfig::default()
+        };
+
+        for input in ["", "  \n\t "] {
+            assert_eq!(
+                parse_with_config(input, &config),
+                Ok(Value::Null),
+                "{input:?}"
+            );
+        }
+        assert_eq!(parse_with_config(" 1 ", &config), Ok(Value::num(1.0)));
+    }
 }
@@ -0,0 +1,142 @@
+use crate::Value;
+
+impl PartialEq<str> for Value {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for Value {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl PartialEq<String> for Value {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == Some(other.as_str())
+    }
+}
+
+impl PartialEq<Value> for str {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<Value> for &str {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<Value> for String {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<bool> for Value {
+    fn eq(&self, other: &bool) -> bool {
+        self.as_bool() == Some(*other)
+    }
+}
+
+impl PartialEq<Value> for bool {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+/// Only `null` equals `()`
+impl PartialEq<()> for Value {
+    fn eq(&self, _: &()) -> bool {
+        self.is_null()
+    }
+}
+
+impl PartialEq<Value> for () {
+    fn eq(&self, other: &Value) -> bool {
+        other.is_null()
+    }
+}
+
+// numbers compare by value the way `NumberRepr` does: integers exactly,
+// and `3 == Value::num(3.0)`
+macro_rules! partial_eq_number {
+    ($($ty:ty)*) => {
+        $(
+            impl PartialEq<$ty> for Value {
+                fn eq(&self, other: &$ty) -> bool {
+                    match (self, Value::from(*other)) {
+                        (Value::Number(a), Value::Number(b)) => *a == b,
+                        _ => false,
+                    }
+                }
+            }
+
+            impl PartialEq<Value> for $ty {
+                fn eq(&self, other: &Value) -> bool {
+                    other == self
+                }
+            }
+        )*
+    };
+}
+
+partial_eq_number!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize f32 f64);
+
+#[cfg(test)]
+mod tests {
+    use crate::{json, Value};
+
+    #[test]
+    fn strings() {
+        let value = json!({"status": "ok"});
+
+        assert_eq!(value["status"], "ok");
+        assert_eq!("ok", value["status"]);
+        assert_eq!(value["status"], "ok".to_string());
+        assert_eq!("ok".to_string(), value["status"]);
+        assert_eq!(value["status"], *"ok");
+        assert_eq!(*"ok", value["status"]);
+        assert_ne!(value["status"], "OK");
+        assert_ne!(value["missing"], "ok");
+        assert_ne!(json!(1), "1");
+    }
+
+    #[test]
+    fn booleans() {
+        assert_eq!(json!(true), true);
+        assert_eq!(false, json!(false));
+        assert_ne!(json!(true), false);
+        assert_ne!(json!(1), true);
+        assert_ne!(Value::Null, false);
+    }
+
+    #[test]
+    fn numbers() {
+        let value = json!({"count": 3, "ratio": 0.5});
+
+        assert_eq!(value["count"], 3.0);
+        assert_eq!(3.0, value["count"]);
+        assert_eq!(value["count"], 3);
+        assert_eq!(3u8, value["count"]);
+        assert_eq!(value["count"], 3usize);
+        assert_eq!(-3i64, json!(-3));
+        assert_eq!(value["ratio"], 0.5f32);
+        assert_ne!(value["ratio"], 0);
+        assert_ne!(json!("3"), 3);
+        assert_ne!(Value::Null, 0);
+        // NaN equals NaN here too, as it does between two `Value`s
+        assert_eq!(json!(f64::NAN), f64::NAN);
+    }
+
+    #[test]
+    fn unit_matches_only_null() {
+        assert_eq!(Value::Null, ());
+        assert_eq!((), Value::Null);
+        assert_ne!(json!(false), ());
+        assert_ne!((), json!([]));
+    }
+}
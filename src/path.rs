@@ -0,0 +1,592 @@
+//! Dot paths such as `server.listeners[0].port`, a friendlier alternative
+//! to JSON Pointers for configuration
+
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+
+use crate::Value;
+
+/// Why a dot path couldn't be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// The path isn't valid syntax; `position` is the byte offset where
+    /// reading it failed
+    Malformed { position: usize },
+
+    /// A key or index along the path doesn't exist, or names the wrong
+    /// kind of container
+    NotFound,
+
+    /// `set_path_create` met a value that isn't the container the path
+    /// needs there, such as an index into an object or a key into a string
+    TypeConflict,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { position } => write!(f, "malformed path at byte {position}"),
+            Self::NotFound => f.write_str("path refers to a missing value"),
+            Self::TypeConflict => f.write_str("path runs into a value of the wrong type"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// One step of a dot path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl Value {
+    /// Looks up a dot path: keys separated by `.`, array indices in
+    /// brackets and keys containing `.`, `[` or `]` quoted in brackets,
+    /// e.g. `a.b[2]["weird.key"]`
+    ///
+    /// The empty path is the whole document and a path may start with an
+    /// index when the root is an array. `Ok(None)` when the path doesn't
+    /// match the document; an index never matches an object member, even
+    /// one named like a number.
+    ///
+    /// ```
+    /// use json_parser::json;
+    ///
+    /// let config = json!({"server": {"listeners": [{"port": 80}]}});
+    /// assert_eq!(
+    ///     config.get_path("server.listeners[0].port"),
+    ///     Ok(Some(&json!(80)))
+    /// );
+    /// ```
+    pub fn get_path(&self, path: &str) -> Result<Option<&Value>, PathError> {
+        let mut current = self;
+        for segment in &parse_path(path)? {
+            match current.child(segment) {
+                Some(child) => current = child,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(current))
+    }
+
+    /// Mutable access to the value at a dot path, following the same rules
+    /// as `get_path`
+    pub fn get_path_mut(&mut self, path: &str) -> Result<Option<&mut Value>, PathError> {
+        let mut current = self;
+        for segment in &parse_path(path)? {
+            match current.child_mut(segment) {
+                Some(child) => current = child,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(current))
+    }
+
+    /// Sets the value at a dot path, returning the value it replaced
+    ///
+    /// Everything up to the last segment must already exist. A final key
+    /// adds or replaces an object member; a final index must name an
+    /// existing array element. The empty path replaces the whole document.
+    pub fn set_path(&mut self, path: &str, value: Value) -> Result<Option<Value>, PathError> {
+        let segments = parse_path(path)?;
+        let Some((last, parents)) = segments.split_last() else {
+            return Ok(Some(mem::replace(self, value)));
+        };
+
+        let mut current = self;
+        for segment in parents {
+            current = current.child_mut(segment).ok_or(PathError::NotFound)?;
+        }
+        match (current, last) {
+            (Value::Object(object), PathSegment::Key(key)) => Ok(object.insert(key.clone(), value)),
+            (Value::Array(array), PathSegment::Index(index)) => {
+                let slot = array.get_mut(*index).ok_or(PathError::NotFound)?;
+                Ok(Some(mem::replace(slot, value)))
+            }
+            _ => Err(PathError::NotFound),
+        }
+    }
+
+    /// Sets the value at a dot path like `set_path`, creating whatever is
+    /// missing on the way, and returns the value it replaced, `None` when
+    /// the slot was missing or `null`
+    ///
+    /// A missing or `null` slot becomes an empty object when the next
+    /// segment is a key and an empty array when it's an index, and arrays
+    /// too short for an index are padded with `null`. A value that exists
+    /// but is the wrong kind of container, or a scalar, is an error;
+    /// `set_path_overwrite` replaces it instead. Containers created before
+    /// such an error stay in place.
+    ///
+    /// ```
+    /// use json_parser::{json, Value};
+    ///
+    /// let mut value = Value::Null;
+    /// value.set_path_create("a.b[2].c", json!(1)).unwrap();
+    /// assert_eq!(value, json!({"a": {"b": [null, null, {"c": 1}]}}));
+    /// ```
+    pub fn set_path_create(
+        &mut self,
+        path: &str,
+        value: Value,
+    ) -> Result<Option<Value>, PathError> {
+        self.set_path_creating(path, value, false)
+    }
+
+    /// `set_path_create`, but a value of the wrong type along the path is
+    /// replaced by the container the path needs rather than reported
+    pub fn set_path_overwrite(
+        &mut self,
+        path: &str,
+        value: Value,
+    ) -> Result<Option<Value>, PathError> {
+        self.set_path_creating(path, value, true)
+    }
+
+    /// Removes and returns the value at a dot path, shifting later array
+    /// elements down
+    ///
+    /// The empty path takes the whole document, leaving `null` behind.
+    pub fn delete_path(&mut self, path: &str) -> Result<Value, PathError> {
+        let segments = parse_path(path)?;
+        let Some((last, parents)) = segments.split_last() else {
+            return Ok(self.take());
+        };
+
+        let mut current = self;
+        for segment in parents {
+            current = current.child_mut(segment).ok_or(PathError::NotFound)?;
+        }
+        match (current, last) {
+            (Value::Object(object), PathSegment::Key(key)) => {
+                object.remove(key).ok_or(PathError::NotFound)
+            }
+            (Value::Array(array), PathSegment::Index(index)) if *index < array.len() => {
+                Ok(array.remove(*index))
+            }
+            _ => Err(PathError::NotFound),
+        }
+    }
+
+    fn set_path_creating(
+        &mut self,
+        path: &str,
+        value: Value,
+        overwrite: bool,
+    ) -> Result<Option<Value>, PathError> {
+        let segments = parse_path(path)?;
+        let Some((last, parents)) = segments.split_last() else {
+            return Ok(Some(mem::replace(self, value)));
+        };
+
+        let mut current = self;
+        for segment in parents {
+            current = current.slot(segment, overwrite)?;
+        }
+        let slot = current.slot(last, overwrite)?;
+        Ok(Some(mem::replace(slot, value)).filter(|old| !old.is_null()))
+    }
+
+    /// The child at `segment`, created (as `null`) if missing, after
+    /// turning a `null` value, or any value when `overwrite` is set, into
+    /// the container `segment` needs
+    fn slot(&mut self, segment: &PathSegment, overwrite: bool) -> Result<&mut Value, PathError> {
+        let fits = matches!(
+            (&*self, segment),
+            (Value::Object(_), PathSegment::Key(_)) | (Value::Array(_), PathSegment::Index(_))
+        );
+        if !fits {
+            if !self.is_null() && !overwrite {
+                return Err(PathError::TypeConflict);
+            }
+            *self = match segment {
+                PathSegment::Key(_) => Value::Object(HashMap::new()),
+                PathSegment::Index(_) => Value::Array(Vec::new()),
+            };
+        }
+        match (self, segment) {
+            (Value::Object(object), PathSegment::Key(key)) => {
+                Ok(object.entry(key.clone()).or_default())
+            }
+            (Value::Array(array), PathSegment::Index(index)) => {
+                if *index >= array.len() {
+                    array.resize(index + 1, Value::Null);
+                }
+                Ok(&mut array[*index])
+            }
+            _ => unreachable!("the container was just made to fit"),
+        }
+    }
+
+    fn child(&self, segment: &PathSegment) -> Option<&Value> {
+        match (self, segment) {
+            (Value::Object(object), PathSegment::Key(key)) => object.get(key),
+            (Value::Array(array), PathSegment::Index(index)) => array.get(*index),
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, segment: &PathSegment) -> Option<&mut Value> {
+        match (self, segment) {
+            (Value::Object(object), PathSegment::Key(key)) => object.get_mut(key),
+            (Value::Array(array), PathSegment::Index(index)) => array.get_mut(*index),
+            _ => None,
+        }
+    }
+}
+
+/// Splits a dot path into its segments
+pub(crate) fn parse_path(path: &str) -> Result<Vec<PathSegment>, PathError> {
+    let bytes = path.as_bytes();
+    let malformed = |position| PathError::Malformed { position };
+
+    let mut segments = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'[' if bytes.get(index + 1) == Some(&b'"') => {
+                let (key, end) = quoted_key(path, index + 2)?;
+                if bytes.get(end) != Some(&b']') {
+                    return Err(malformed(end));
+                }
+                segments.push(PathSegment::Key(key));
+                index = end + 1;
+            }
+            b'[' => {
+                let digits = bytes[index + 1..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+                let end = index + 1 + digits;
+                if digits == 0 || bytes.get(end) != Some(&b']') {
+                    return Err(malformed(end));
+                }
+                let position = path[index + 1..end]
+                    .parse()
+                    .map_err(|_| malformed(index + 1))?;
+                segments.push(PathSegment::Index(position));
+                index = end + 1;
+            }
+            // a key follows a `.`, or starts the path
+            b'.' if !segments.is_empty() => {
+                let end = bare_key_end(bytes, index + 1);
+                if end == index + 1 {
+                    return Err(malformed(end));
+                }
+                segments.push(PathSegment::Key(path[index + 1..end].to_string()));
+                index = end;
+            }
+            _ if segments.is_empty() => {
+                let end = bare_key_end(bytes, index);
+                if end == index {
+                    return Err(malformed(index));
+                }
+                segments.push(PathSegment::Key(path[index..end].to_string()));
+                index = end;
+            }
+            _ => return Err(malformed(index)),
+        }
+    }
+    Ok(segments)
+}
+
+/// Where an unquoted key starting at `start` ends
+fn bare_key_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|b| matches!(b, b'.' | b'[' | b']'))
+        .map_or(bytes.len(), |offset| start + offset)
+}
+
+/// Reads a quoted key whose content starts at `start`, resolving `\"`
+/// and `\\`, and returns it with the index just past the closing quote
+fn quoted_key(path: &str, start: usize) -> Result<(String, usize), PathError> {
+    let mut key = String::new();
+    let mut chars = path[start..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' => return Ok((key, start + offset + 1)),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => key.push(escaped),
+                _ => {
+                    return Err(PathError::Malformed {
+                        position: start + offset,
+                    })
+                }
+            },
+            c => key.push(c),
+        }
+    }
+    Err(PathError::Malformed {
+        position: path.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_path, PathError, PathSegment};
+    use crate::{json, Value};
+
+    fn key(k: &str) -> PathSegment {
+        PathSegment::Key(k.to_string())
+    }
+
+    #[test]
+    fn parses_segments() {
+        assert_eq!(parse_path(""), Ok(vec![]));
+        assert_eq!(
+            parse_path("server.listeners[0].port"),
+            Ok(vec![
+                key("server"),
+                key("listeners"),
+                PathSegment::Index(0),
+                key("port")
+            ])
+        );
+        assert_eq!(
+            parse_path("a[0][12]"),
+            Ok(vec![
+                key("a"),
+                PathSegment::Index(0),
+                PathSegment::Index(12)
+            ])
+        );
+        assert_eq!(
+            parse_path(r#"[3]["weird.key"]["q\"[]\\"].x"#),
+            Ok(vec![
+                PathSegment::Index(3),
+                key("weird.key"),
+                key("q\"[]\\"),
+                key("x")
+            ])
+        );
+    }
+
+    #[test]
+    fn malformed_paths() {
+        let cases = [
+            ("a.", 2),
+            (".a", 0),
+            ("a..b", 2),
+            ("a[", 2),
+            ("a[]", 2),
+            ("a[x]", 2),
+            ("a[1", 3),
+            ("a[1]b", 4),
+            ("a]", 1),
+            (r#"a["b"#, 4),
+            (r#"a["b"x"#, 5),
+            (r#"a["\n"]"#, 3),
+            ("a[99999999999999999999999]", 2),
+        ];
+        for (path, position) in cases {
+            assert_eq!(
+                parse_path(path),
+                Err(PathError::Malformed { position }),
+                "{path}"
+            );
+        }
+    }
+
+    fn document() -> Value {
+        json!({
+            "server": {"listeners": [{"port": 80}, {"port": 443}]},
+            "matrix": [[1, 2], [3, 4]],
+            "weird.key": {"[x]": true},
+            "0": "zero"
+        })
+    }
+
+    fn document_after() -> Value {
+        let mut value = document();
+        value["server"]["listeners"][0]["port"] = json!(8080);
+        value["server"]["host"] = json!("h");
+        value["weird.key"]["y"] = json!(1);
+        value
+    }
+
+    #[test]
+    fn get_path_walks_document() {
+        let value = document();
+        assert_eq!(value.get_path(""), Ok(Some(&value)));
+        assert_eq!(
+            value.get_path("server.listeners[1].port"),
+            Ok(Some(&json!(443)))
+        );
+        assert_eq!(value.get_path("matrix[1][0]"), Ok(Some(&json!(3))));
+        assert_eq!(
+            value.get_path(r#"["weird.key"]["[x]"]"#),
+            Ok(Some(&json!(true)))
+        );
+        assert_eq!(value.get_path("0"), Ok(Some(&json!("zero"))));
+
+        assert_eq!(value.get_path("server.missing"), Ok(None));
+        assert_eq!(value.get_path("matrix[2]"), Ok(None));
+        assert_eq!(value.get_path("[0]"), Ok(None));
+        assert_eq!(value.get_path("server.listeners.port"), Ok(None));
+        assert_eq!(
+            value.get_path("server..port"),
+            Err(PathError::Malformed { position: 7 })
+        );
+
+        let array = json!([{"a": [true]}]);
+        assert_eq!(array.get_path("[0].a[0]"), Ok(Some(&json!(true))));
+    }
+
+    #[test]
+    fn get_path_mut_edits_in_place() {
+        let mut value = document();
+        *value.get_path_mut("matrix[0][1]").unwrap().unwrap() = json!(20);
+        assert_eq!(value["matrix"], json!([[1, 20], [3, 4]]));
+        assert_eq!(value.get_path_mut("nope.x"), Ok(None));
+    }
+
+    #[test]
+    fn set_path_replaces_and_adds() {
+        let mut value = document();
+        assert_eq!(
+            value.set_path("server.listeners[0].port", json!(8080)),
+            Ok(Some(json!(80)))
+        );
+        assert_eq!(value.set_path("server.host", json!("h")), Ok(None));
+        assert_eq!(value.set_path(r#"["weird.key"].y"#, json!(1)), Ok(None));
+        assert_eq!(value["server"]["listeners"][0]["port"], json!(8080));
+        assert_eq!(value["server"]["host"], json!("h"));
+        assert_eq!(value["weird.key"]["y"], json!(1));
+
+        assert_eq!(value.set_path("", json!([])), Ok(Some(document_after())));
+        assert_eq!(value, json!([]));
+    }
+
+    #[test]
+    fn set_path_does_not_create_missing_parents() {
+        let mut value = document();
+        let before = value.clone();
+        assert_eq!(
+            value.set_path("missing.key", json!(1)),
+            Err(PathError::NotFound)
+        );
+        assert_eq!(
+            value.set_path("matrix[2]", json!(1)),
+            Err(PathError::NotFound)
+        );
+        assert_eq!(
+            value.set_path("matrix.key", json!(1)),
+            Err(PathError::NotFound)
+        );
+        assert_eq!(
+            value.set_path("server[0]", json!(1)),
+            Err(PathError::NotFound)
+        );
+        assert_eq!(
+            value.set_path("a[", json!(1)),
+            Err(PathError::Malformed { position: 2 })
+        );
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn build_and_take_apart_with_paths() {
+        let mut value = Value::Null;
+        let sets = [
+            ("name", json!("svc")),
+            ("server.host", json!("localhost")),
+            ("server.listeners[1].port", json!(443)),
+            ("server.listeners[0].port", json!(80)),
+            ("server.listeners[1].tls", json!(true)),
+            (r#"labels["app.kubernetes.io/name"]"#, json!("svc")),
+            ("matrix[1][2]", json!(5)),
+        ];
+        for (path, v) in sets {
+            assert_eq!(value.set_path_create(path, v), Ok(None), "{path}");
+        }
+        assert_eq!(
+            value,
+            json!({
+                "name": "svc",
+                "server": {
+                    "host": "localhost",
+                    "listeners": [{"port": 80}, {"port": 443, "tls": true}]
+                },
+                "labels": {"app.kubernetes.io/name": "svc"},
+                "matrix": [null, [null, null, 5]]
+            })
+        );
+        assert_eq!(
+            value.set_path_create("server.host", json!("example.com")),
+            Ok(Some(json!("localhost")))
+        );
+
+        assert_eq!(
+            value.delete_path("server.listeners[0]"),
+            Ok(json!({"port": 80}))
+        );
+        assert_eq!(
+            value.delete_path("server.listeners[0].tls"),
+            Ok(json!(true))
+        );
+        assert_eq!(
+            value.get_path("server.listeners"),
+            Ok(Some(&json!([{"port": 443}])))
+        );
+        assert_eq!(value.delete_path("matrix[1][2]"), Ok(json!(5)));
+        assert_eq!(value.delete_path("matrix[0]"), Ok(json!(null)));
+        assert_eq!(value.delete_path("matrix"), Ok(json!([[null, null]])));
+        assert_eq!(
+            value.delete_path(r#"labels["app.kubernetes.io/name"]"#),
+            Ok(json!("svc"))
+        );
+        assert_eq!(value.delete_path("labels"), Ok(json!({})));
+        assert_eq!(
+            value.delete_path("server"),
+            Ok(json!({"host": "example.com", "listeners": [{"port": 443}]}))
+        );
+        assert_eq!(value.delete_path("name"), Ok(json!("svc")));
+        assert_eq!(value, json!({}));
+        assert_eq!(value.delete_path(""), Ok(json!({})));
+        assert_eq!(value, Value::Null);
+    }
+
+    #[test]
+    fn delete_path_missing() {
+        let mut value = document();
+        let before = value.clone();
+        for path in ["nope", "matrix[2]", "matrix.key", "server[0]", "0.x"] {
+            assert_eq!(value.delete_path(path), Err(PathError::NotFound), "{path}");
+        }
+        assert_eq!(
+            value.delete_path("a[b]"),
+            Err(PathError::Malformed { position: 2 })
+        );
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn set_path_create_type_conflicts() {
+        let mut value = json!({"list": [1], "map": {"a": 1}, "n": 3});
+        let before = value.clone();
+        for path in ["list.key", "map[0]", "n.x", "n[0]", "list[0].x", "[0]"] {
+            assert_eq!(
+                value.set_path_create(path, json!(true)),
+                Err(PathError::TypeConflict),
+                "{path}"
+            );
+        }
+        assert_eq!(value, before);
+
+        assert_eq!(value.set_path_overwrite("map[1]", json!(true)), Ok(None));
+        assert_eq!(value.set_path_overwrite("n.x", json!(true)), Ok(None));
+        assert_eq!(value.set_path_overwrite("list[0].x", json!(true)), Ok(None));
+        assert_eq!(
+            value,
+            json!({"list": [{"x": true}], "map": [null, true], "n": {"x": true}})
+        );
+        // null is always replaced
+        let mut value = json!({"a": null});
+        assert_eq!(value.set_path_create("a[0]", json!(1)), Ok(None));
+        assert_eq!(value, json!({"a": [1]}));
+    }
+}
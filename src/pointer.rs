@@ -0,0 +1,746 @@
+mod json_pointer;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::{fmt, mem};
+
+use crate::Value;
+
+pub use json_pointer::{AsPointer, JsonPointer, ReferenceToken};
+
+/// Why `pointer_insert` or `pointer_remove` couldn't modify the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerError {
+    /// The pointer doesn't start with `/` or has a `~` not followed by `0`
+    /// or `1`
+    Malformed,
+
+    /// A token addressing an array isn't `-` or a number without leading
+    /// zeros
+    InvalidIndex,
+
+    /// The key or index doesn't exist, or the index is past the end
+    NotFound,
+
+    /// The pointer continues through a string, number or boolean
+    NotAContainer,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Malformed => "malformed JSON pointer",
+            Self::InvalidIndex => "invalid array index in JSON pointer",
+            Self::NotFound => "JSON pointer refers to a missing value",
+            Self::NotAContainer => "JSON pointer descends into a scalar",
+        })
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+impl Value {
+    /// Looks up the value a JSON Pointer (RFC 6901) such as `/a/0` refers
+    /// to, the empty pointer being the whole document
+    ///
+    /// `None` when the pointer is malformed or doesn't match the document:
+    /// a key into an array, an index into a scalar, an out of range index,
+    /// or an index with leading zeros. The `-` index names the slot past
+    /// the end of an array, which never holds a value.
+    pub fn pointer<P: AsPointer + ?Sized>(&self, pointer: &P) -> Option<&Value> {
+        let mut current = self;
+        for token in pointer.reference_tokens().ok()?.iter() {
+            current = match current {
+                Value::Object(object) => object.get(token)?,
+                Value::Array(array) => array.get(array_index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Mutable access to the value `pointer` refers to, following the same
+    /// rules as `pointer`
+    pub fn pointer_mut<P: AsPointer + ?Sized>(&mut self, pointer: &P) -> Option<&mut Value> {
+        let mut current = self;
+        for token in pointer.reference_tokens().ok()?.iter() {
+            current = match current {
+                Value::Object(object) => object.get_mut(token)?,
+                Value::Array(array) => array.get_mut(array_index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Sets the value at `pointer`, returning the object member it
+    /// replaced
+    ///
+    /// Missing intermediate keys are created as empty objects, even when
+    /// the key looks like an array index: `/a/0` on `{}` gives
+    /// `{"a": {"0": ...}}`. `null` along the way is treated as missing.
+    /// In an array the final token inserts before that index, shifting
+    /// later elements, and `-` appends; intermediate indices must exist.
+    /// The empty pointer replaces the whole document.
+    pub fn pointer_insert<P: AsPointer + ?Sized>(
+        &mut self,
+        pointer: &P,
+        value: Value,
+    ) -> Result<Option<Value>, PointerError> {
+        let tokens = pointer.reference_tokens()?;
+        let Some((last, parents)) = tokens.split_last() else {
+            return Ok(Some(mem::replace(self, value)));
+        };
+
+        let mut current = self;
+        for token in parents {
+            if current.is_null() {
+                *current = Value::Object(HashMap::new());
+            }
+            current = match current {
+                Value::Object(object) => object
+                    .entry(token.clone())
+                    .or_insert_with(|| Value::Object(HashMap::new())),
+                Value::Array(array) => {
+                    let index = array_index(token).ok_or(PointerError::InvalidIndex)?;
+                    array.get_mut(index).ok_or(PointerError::NotFound)?
+                }
+                _ => return Err(PointerError::NotAContainer),
+            };
+        }
+
+        if current.is_null() {
+            *current = Value::Object(HashMap::new());
+        }
+        match current {
+            Value::Object(object) => Ok(object.insert(last.clone(), value)),
+            Value::Array(array) => {
+                let index = match last.as_str() {
+                    "-" => array.len(),
+                    _ => array_index(last).ok_or(PointerError::InvalidIndex)?,
+                };
+                if index > array.len() {
+                    return Err(PointerError::NotFound);
+                }
+                array.insert(index, value);
+                Ok(None)
+            }
+            _ => Err(PointerError::NotAContainer),
+        }
+    }
+
+    /// Removes and returns the value at `pointer`, shifting later array
+    /// elements down
+    ///
+    /// The empty pointer takes the whole document, leaving `null` behind.
+    pub fn pointer_remove<P: AsPointer + ?Sized>(
+        &mut self,
+        pointer: &P,
+    ) -> Result<Value, PointerError> {
+        let tokens = pointer.reference_tokens()?;
+        let Some((last, parents)) = tokens.split_last() else {
+            return Ok(self.take());
+        };
+
+        let mut current = self;
+        for token in parents {
+            current = match current {
+                Value::Object(object) => object.get_mut(token).ok_or(PointerError::NotFound)?,
+                Value::Array(array) => {
+                    let index = array_index(token).ok_or(PointerError::InvalidIndex)?;
+                    array.get_mut(index).ok_or(PointerError::NotFound)?
+                }
+                _ => return Err(PointerError::NotAContainer),
+            };
+        }
+
+        match current {
+            Value::Object(object) => object.remove(last).ok_or(PointerError::NotFound),
+            Value::Array(array) => {
+                let index = match last.as_str() {
+                    "-" => return Err(PointerError::NotFound),
+                    _ => array_index(last).ok_or(PointerError::InvalidIndex)?,
+                };
+                if index >= array.len() {
+                    return Err(PointerError::NotFound);
+                }
+                Ok(array.remove(index))
+            }
+            _ => Err(PointerError::NotAContainer),
+        }
+    }
+
+    /// Replaces the value at `pointer` with `f` applied to it, the value
+    /// being moved out rather than cloned
+    ///
+    /// The pointer must refer to an existing value; `-` never does.
+    pub fn update_at<P: AsPointer + ?Sized>(
+        &mut self,
+        pointer: &P,
+        f: impl FnOnce(Value) -> Value,
+    ) -> Result<(), PointerError> {
+        let mut current = self;
+        for token in pointer.reference_tokens()?.iter() {
+            current = match current {
+                Value::Object(object) => object.get_mut(token).ok_or(PointerError::NotFound)?,
+                Value::Array(_) if token == "-" => return Err(PointerError::NotFound),
+                Value::Array(array) => {
+                    let index = array_index(token).ok_or(PointerError::InvalidIndex)?;
+                    array.get_mut(index).ok_or(PointerError::NotFound)?
+                }
+                _ => return Err(PointerError::NotAContainer),
+            };
+        }
+        *current = f(current.take());
+        Ok(())
+    }
+
+    /// Every node for which `f` returns `true`, paired with its JSON
+    /// Pointer, in pre-order with object keys visited in sorted order
+    ///
+    /// The document itself is included, at the empty pointer.
+    pub fn find_all(&self, f: impl Fn(&Value) -> bool) -> Vec<(String, &Value)> {
+        let mut found = Vec::new();
+        let mut stack = vec![(String::new(), self)];
+        while let Some((path, value)) = stack.pop() {
+            if f(value) {
+                found.push((path.clone(), value));
+            }
+            // children are pushed in reverse so they pop in order
+            match value {
+                Value::Array(array) => {
+                    for (i, child) in array.iter().enumerate().rev() {
+                        stack.push((format!("{path}/{i}"), child));
+                    }
+                }
+                Value::Object(object) => {
+                    let mut entries: Vec<_> = object.iter().collect();
+                    entries.sort_unstable_by_key(|(key, _)| *key);
+                    for (key, child) in entries.into_iter().rev() {
+                        stack.push((format!("{path}/{}", escape(key)), child));
+                    }
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Every node in the document, containers and leaves alike, paired
+    /// with its pointer, in pre-order with object keys visited in sorted
+    /// order
+    ///
+    /// The document itself comes first, at the empty pointer. The walk
+    /// keeps an explicit stack rather than recursing, so arbitrarily deep
+    /// documents are fine.
+    ///
+    /// ```
+    /// use json_parser::json;
+    ///
+    /// let doc = json!({"a": [true]});
+    /// let pointers: Vec<_> = doc.iter_all().map(|(p, _)| p.to_string()).collect();
+    /// assert_eq!(pointers, ["", "/a", "/a/0"]);
+    /// ```
+    pub fn iter_all(&self) -> impl Iterator<Item = (JsonPointer, &Value)> {
+        IterAll {
+            stack: vec![(JsonPointer::new(), self)],
+        }
+    }
+
+    /// The nodes of `iter_all` that have no children: scalars, empty
+    /// arrays and empty objects
+    pub fn iter_leaves(&self) -> impl Iterator<Item = (JsonPointer, &Value)> {
+        self.iter_all().filter(|(_, value)| match value {
+            Value::Array(array) => array.is_empty(),
+            Value::Object(object) => object.is_empty(),
+            _ => true,
+        })
+    }
+
+    /// Whether `pointer` refers to a value, exactly when `pointer` returns
+    /// `Some`
+    pub fn path_exists<P: AsPointer + ?Sized>(&self, pointer: &P) -> bool {
+        self.pointer(pointer).is_some()
+    }
+
+    /// A deep copy of the sub-tree at `pointer`, for keeping it after
+    /// `self` is gone
+    pub fn select<P: AsPointer + ?Sized>(&self, pointer: &P) -> Option<Value> {
+        self.pointer(pointer).cloned()
+    }
+
+    /// The string at `pointer`, `None` when it's missing or not a string
+    pub fn pointer_str<P: AsPointer + ?Sized>(&self, pointer: &P) -> Option<&str> {
+        self.pointer(pointer)?.as_str()
+    }
+
+    /// The number at `pointer`, `None` when it's missing or not a number
+    pub fn pointer_f64<P: AsPointer + ?Sized>(&self, pointer: &P) -> Option<f64> {
+        self.pointer(pointer)?.as_f64()
+    }
+
+    /// The boolean at `pointer`, `None` when it's missing or not a boolean
+    pub fn pointer_bool<P: AsPointer + ?Sized>(&self, pointer: &P) -> Option<bool> {
+        self.pointer(pointer)?.as_bool()
+    }
+}
+
+/// The iterator behind `Value::iter_all`, holding the nodes still to visit
+/// with the next one on top
+struct IterAll<'a> {
+    stack: Vec<(JsonPointer, &'a Value)>,
+}
+
+impl<'a> Iterator for IterAll<'a> {
+    type Item = (JsonPointer, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (pointer, value) = self.stack.pop()?;
+        // children are pushed in reverse so they pop in order
+        match value {
+            Value::Array(array) => {
+                for (i, child) in array.iter().enumerate().rev() {
+                    self.stack.push((pointer.clone().push_index(i), child));
+                }
+            }
+            Value::Object(object) => {
+                let mut entries: Vec<_> = object.iter().collect();
+                entries.sort_unstable_by_key(|(key, _)| *key);
+                for (key, child) in entries.into_iter().rev() {
+                    self.stack
+                        .push((pointer.clone().push_key(key.as_str()), child));
+                }
+            }
+            _ => {}
+        }
+        Some((pointer, value))
+    }
+}
+
+/// The unescaped reference tokens of `pointer`
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/').ok_or(PointerError::Malformed)?;
+    rest.split('/')
+        .map(|token| unescape(token).ok_or(PointerError::Malformed))
+        .collect()
+}
+
+/// `token` with `~` and `/` escaped as `~0` and `~1`
+fn escape(token: &str) -> Cow<'_, str> {
+    if token.contains(['~', '/']) {
+        Cow::Owned(token.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// `token` with `~1` and `~0` escapes resolved, `None` when it has a `~`
+/// not followed by `0` or `1`
+fn unescape(token: &str) -> Option<String> {
+    let mut output = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next()? {
+                '0' => output.push('~'),
+                '1' => output.push('/'),
+                _ => return None,
+            },
+            c => output.push(c),
+        }
+    }
+    Some(output)
+}
+
+/// An array index token: `0` or digits without a leading zero
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let valid = token == "0"
+        || (!token.starts_with('0')
+            && !token.is_empty()
+            && token.bytes().all(|b| b.is_ascii_digit()));
+    if valid {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PointerError;
+    use crate::{json, parse, Value};
+
+    fn document() -> Value {
+        parse(r#"{"a": ["x", 2.5, true], "b/c": {"~d": "tilde"}}"#).unwrap()
+    }
+
+    #[test]
+    fn pointer_walks_objects_and_arrays() {
+        let value = document();
+
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(value.pointer("/a/1"), Some(&Value::num(2.5)));
+        assert_eq!(value.pointer("/a/3"), None);
+        assert_eq!(value.pointer("/a/x"), None);
+        assert_eq!(value.pointer("/missing"), None);
+        assert_eq!(value.pointer("a"), None);
+    }
+
+    #[test]
+    fn pointer_unescapes_segments() {
+        assert_eq!(
+            document().pointer("/b~1c/~0d"),
+            Some(&Value::String("tilde".to_string()))
+        );
+    }
+
+    /// The document from RFC 6901 section 5
+    fn rfc_document() -> Value {
+        json!({
+            "foo": ["bar", "baz"],
+            "": 0,
+            "a/b": 1,
+            "c%d": 2,
+            "e^f": 3,
+            "g|h": 4,
+            "i\\j": 5,
+            "k\"l": 6,
+            " ": 7,
+            "m~n": 8
+        })
+    }
+
+    #[test]
+    fn rfc_6901_examples() {
+        let doc = rfc_document();
+
+        assert_eq!(doc.pointer(""), Some(&doc));
+        assert_eq!(doc.pointer("/foo"), Some(&json!(["bar", "baz"])));
+        assert_eq!(doc.pointer("/foo/0"), Some(&json!("bar")));
+        assert_eq!(doc.pointer("/"), Some(&json!(0)));
+        assert_eq!(doc.pointer("/a~1b"), Some(&json!(1)));
+        assert_eq!(doc.pointer("/c%d"), Some(&json!(2)));
+        assert_eq!(doc.pointer("/e^f"), Some(&json!(3)));
+        assert_eq!(doc.pointer("/g|h"), Some(&json!(4)));
+        assert_eq!(doc.pointer("/i\\j"), Some(&json!(5)));
+        assert_eq!(doc.pointer("/k\"l"), Some(&json!(6)));
+        assert_eq!(doc.pointer("/ "), Some(&json!(7)));
+        assert_eq!(doc.pointer("/m~0n"), Some(&json!(8)));
+    }
+
+    #[test]
+    fn pointer_rejects_malformed_tokens() {
+        let doc = rfc_document();
+
+        assert_eq!(doc.pointer("/foo/01"), None);
+        assert_eq!(doc.pointer("/foo/-"), None);
+        assert_eq!(doc.pointer("/foo/+1"), None);
+        assert_eq!(doc.pointer("/foo/"), None);
+        assert_eq!(doc.pointer("/m~2n"), None);
+        assert_eq!(doc.pointer("/m~"), None);
+        assert_eq!(doc.pointer("foo"), None);
+        assert_eq!(doc.pointer("/foo/0/x"), None);
+        assert_eq!(doc.pointer("/foo/bar"), None);
+    }
+
+    #[test]
+    fn pointer_mut_allows_mutation() {
+        let mut doc = rfc_document();
+
+        *doc.pointer_mut("/foo/1").unwrap() = json!("qux");
+        *doc.pointer_mut("/").unwrap() = json!(null);
+        doc.pointer_mut("/a~1b").unwrap().take();
+        assert_eq!(doc.pointer("/foo"), Some(&json!(["bar", "qux"])));
+        assert_eq!(doc.pointer("/"), Some(&Value::Null));
+        assert_eq!(doc.pointer("/a~1b"), Some(&Value::Null));
+
+        assert!(doc.pointer_mut("/foo/2").is_none());
+        assert!(doc.pointer_mut("/foo/-").is_none());
+        assert!(doc.pointer_mut("").unwrap().is_object());
+    }
+
+    #[test]
+    fn insert_creates_missing_intermediates() {
+        let mut value = json!({});
+        assert_eq!(
+            value.pointer_insert("/settings/theme", json!("dark")),
+            Ok(None)
+        );
+        assert_eq!(value.pointer_insert("/a/0/b", json!(1)), Ok(None));
+        assert_eq!(
+            value,
+            json!({"settings": {"theme": "dark"}, "a": {"0": {"b": 1}}})
+        );
+
+        let mut null = Value::Null;
+        null.pointer_insert("/x", json!(true)).unwrap();
+        assert_eq!(null, json!({"x": true}));
+    }
+
+    #[test]
+    fn insert_replaces_object_members() {
+        let mut value = json!({"a": {"b": 1}});
+        assert_eq!(value.pointer_insert("/a/b", json!(2)), Ok(Some(json!(1))));
+        assert_eq!(
+            value.pointer_insert("", json!([])),
+            Ok(Some(json!({"a": {"b": 2}})))
+        );
+        assert_eq!(value, json!([]));
+    }
+
+    #[test]
+    fn insert_into_arrays_shifts_and_appends() {
+        let mut value = json!({"list": [1, 3]});
+        value.pointer_insert("/list/1", json!(2)).unwrap();
+        value.pointer_insert("/list/-", json!(4)).unwrap();
+        value.pointer_insert("/list/4", json!(5)).unwrap();
+        value.pointer_insert("/list/0", json!(0)).unwrap();
+        assert_eq!(value, json!({"list": [0, 1, 2, 3, 4, 5]}));
+
+        assert_eq!(
+            value.pointer_insert("/list/7", json!(null)),
+            Err(PointerError::NotFound)
+        );
+        assert_eq!(
+            value.pointer_insert("/list/01", json!(null)),
+            Err(PointerError::InvalidIndex)
+        );
+        assert_eq!(
+            value.pointer_insert("/list/-/x", json!(null)),
+            Err(PointerError::InvalidIndex)
+        );
+    }
+
+    #[test]
+    fn insert_through_scalar_is_an_error() {
+        let mut value = json!({"a": "text", "n": [1]});
+        assert_eq!(
+            value.pointer_insert("/a/b", json!(1)),
+            Err(PointerError::NotAContainer)
+        );
+        assert_eq!(
+            value.pointer_insert("/n/0/x", json!(1)),
+            Err(PointerError::NotAContainer)
+        );
+        assert_eq!(
+            value.pointer_insert("a", json!(1)),
+            Err(PointerError::Malformed)
+        );
+        assert_eq!(value, json!({"a": "text", "n": [1]}));
+    }
+
+    #[test]
+    fn remove_returns_value_and_shifts() {
+        let mut value = json!({"users": ["a", "b", "c", "d"], "k": {"x": 1}});
+        assert_eq!(value.pointer_remove("/users/2"), Ok(json!("c")));
+        assert_eq!(value.pointer_remove("/users/0"), Ok(json!("a")));
+        assert_eq!(value.pointer("/users"), Some(&json!(["b", "d"])));
+
+        assert_eq!(
+            value.pointer_remove("/users/2"),
+            Err(PointerError::NotFound)
+        );
+        assert_eq!(
+            value.pointer_remove("/users/-"),
+            Err(PointerError::NotFound)
+        );
+        assert_eq!(value.pointer_remove("/k/y"), Err(PointerError::NotFound));
+        assert_eq!(
+            value.pointer_remove("/k/x/y"),
+            Err(PointerError::NotAContainer)
+        );
+    }
+
+    #[test]
+    fn remove_last_element_leaves_empty_container() {
+        let mut value = json!({"a": [1], "o": {"k": null}});
+        assert_eq!(value.pointer_remove("/a/0"), Ok(json!(1)));
+        assert_eq!(value.pointer_remove("/o/k"), Ok(Value::Null));
+        assert_eq!(value, json!({"a": [], "o": {}}));
+
+        assert_eq!(value.pointer_remove(""), Ok(json!({"a": [], "o": {}})));
+        assert_eq!(value, Value::Null);
+    }
+
+    #[test]
+    fn find_all_numbers_greater_than_ten() {
+        let value = json!({
+            "a": [5, 11, {"deep": 100}],
+            "b/c": 42,
+            "d": {"e": 10, "f": "20"}
+        });
+        let found = value.find_all(|v| v.as_f64().is_some_and(|n| n > 10.0));
+
+        assert_eq!(
+            found,
+            [
+                ("/a/1".to_string(), &json!(11)),
+                ("/a/2/deep".to_string(), &json!(100)),
+                ("/b~1c".to_string(), &json!(42)),
+            ]
+        );
+        for (path, node) in found {
+            assert_eq!(value.pointer(&path), Some(node));
+        }
+    }
+
+    #[test]
+    fn find_all_includes_root() {
+        let value = json!([null, [null]]);
+        let nulls: Vec<_> = value
+            .find_all(Value::is_null)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(nulls, ["/0", "/1/0"]);
+        assert_eq!(value.find_all(Value::is_array).len(), 2);
+        assert_eq!(value.find_all(Value::is_array)[0].0, "");
+    }
+
+    #[test]
+    fn typed_pointer_helpers() {
+        let value = document();
+
+        assert_eq!(value.pointer_str("/a/0"), Some("x"));
+        assert_eq!(value.pointer_f64("/a/1"), Some(2.5));
+        assert_eq!(value.pointer_bool("/a/2"), Some(true));
+    }
+
+    #[test]
+    fn typed_pointer_helpers_wrong_type_or_missing() {
+        let value = document();
+
+        assert_eq!(value.pointer_str("/a/1"), None);
+        assert_eq!(value.pointer_f64("/a/0"), None);
+        assert_eq!(value.pointer_bool("/a/9"), None);
+    }
+
+    #[test]
+    fn path_exists_matches_pointer() {
+        let value = document();
+        let pointers = [
+            "",
+            "/a",
+            "/a/0",
+            "/a/2",
+            "/a/3",
+            "/a/-1",
+            "/a/x",
+            "/b~1c",
+            "/b~1c/~0d",
+            "/b/c",
+            "/missing",
+            "/a/0/deeper",
+            "a",
+        ];
+
+        for pointer in pointers {
+            assert_eq!(
+                value.path_exists(pointer),
+                value.pointer(pointer).is_some(),
+                "{pointer}"
+            );
+        }
+        assert!(value.path_exists("/b~1c/~0d"));
+        assert!(!value.path_exists("/a/3"));
+    }
+
+    #[test]
+    fn select_clones_the_sub_tree() {
+        let mut doc = json!({"a": {"b": [1, 2]}, "c": true});
+        let selected = doc.select("/a").unwrap();
+
+        assert_eq!(&selected, doc.pointer("/a").unwrap());
+
+        *doc.pointer_mut("/a/b/0").unwrap() = json!(10);
+        assert_eq!(selected, json!({"b": [1, 2]}));
+        assert_eq!(doc.select("/a/b/0"), Some(json!(10)));
+        assert_eq!(doc.select("/missing"), None);
+    }
+
+    #[test]
+    fn update_at_transforms_in_place() {
+        let mut doc = json!({"stats": {"score": 21, "name": "x"}, "list": [1, 2]});
+        doc.update_at("/stats/score", |score| json!(score.as_f64().unwrap() * 2.0))
+            .unwrap();
+
+        assert_eq!(
+            doc,
+            json!({"stats": {"score": 42, "name": "x"}, "list": [1, 2]})
+        );
+
+        doc.update_at("", |root| json!([root])).unwrap();
+        assert_eq!(doc.pointer("/0/list/1"), Some(&json!(2)));
+    }
+
+    #[test]
+    fn update_at_errors() {
+        let mut doc = json!({"a": [1], "s": "text"});
+        let original = doc.clone();
+
+        assert_eq!(doc.update_at("/b", |v| v), Err(PointerError::NotFound));
+        assert_eq!(doc.update_at("/a/1", |v| v), Err(PointerError::NotFound));
+        assert_eq!(doc.update_at("/a/-", |v| v), Err(PointerError::NotFound));
+        assert_eq!(
+            doc.update_at("/a/01", |v| v),
+            Err(PointerError::InvalidIndex)
+        );
+        assert_eq!(
+            doc.update_at("/s/0", |v| v),
+            Err(PointerError::NotAContainer)
+        );
+        assert_eq!(doc.update_at("a", |v| v), Err(PointerError::Malformed));
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn iter_all_visits_every_node_in_order() {
+        let doc = json!({"b": [1, {"c": null}], "a/x": {"~": true}, "e": []});
+        let pointers: Vec<_> = doc.iter_all().map(|(p, _)| p.to_string()).collect();
+
+        assert_eq!(
+            pointers,
+            ["", "/a~1x", "/a~1x/~0", "/b", "/b/0", "/b/1", "/b/1/c", "/e"]
+        );
+        for (pointer, value) in doc.iter_all() {
+            assert_eq!(doc.pointer(&pointer), Some(value));
+            assert_eq!(doc.pointer(&pointer.to_string()), Some(value));
+        }
+    }
+
+    #[test]
+    fn iter_leaves_skips_non_empty_containers() {
+        let doc = json!({"b": [1, {"c": null}], "a/x": {"~": true}, "e": []});
+        let leaves: Vec<_> = doc
+            .iter_leaves()
+            .map(|(p, v)| (p.to_string(), v.clone()))
+            .collect();
+
+        assert_eq!(
+            leaves,
+            [
+                ("/a~1x/~0".to_string(), json!(true)),
+                ("/b/0".to_string(), json!(1)),
+                ("/b/1/c".to_string(), json!(null)),
+                ("/e".to_string(), json!([])),
+            ]
+        );
+        assert_eq!(json!(3).iter_leaves().count(), 1);
+    }
+
+    #[test]
+    fn iter_all_handles_deep_documents() {
+        let mut doc = json!(0);
+        for _ in 0..5_000 {
+            doc = Value::Array(vec![doc]);
+        }
+
+        assert_eq!(doc.iter_all().count(), 5_001);
+        let (deepest, value) = doc.iter_all().last().unwrap();
+        assert_eq!(deepest.len(), 5_000);
+        assert_eq!(value, &json!(0));
+    }
+}
@@ -0,0 +1,240 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{array_index, escape, parse_pointer, PointerError};
+
+/// A parsed JSON Pointer (RFC 6901), held as its unescaped reference
+/// tokens so keys containing `/` or `~` need no manual escaping
+///
+/// Parse one from a string with `str::parse`, or build it up:
+///
+/// ```
+/// use json_parser::{json, JsonPointer};
+///
+/// let pointer = JsonPointer::new().push_key("a/b").push_index(1);
+/// assert_eq!(pointer.to_string(), "/a~1b/1");
+///
+/// let value = json!({"a/b": [1, 2]});
+/// assert_eq!(value.pointer(&pointer), Some(&json!(2)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+/// One segment of a `JsonPointer`
+///
+/// A token made only of digits without leading zeros is an `Index`,
+/// whether it was pushed as a key or an index; the pointer alone can't
+/// tell, and lookups treat it as a key when they meet an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceToken<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl JsonPointer {
+    /// The empty pointer, referring to the whole document
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The pointer with an object key appended
+    pub fn push_key(mut self, key: impl Into<String>) -> Self {
+        self.tokens.push(key.into());
+        self
+    }
+
+    /// The pointer with an array index appended
+    pub fn push_index(mut self, index: usize) -> Self {
+        self.tokens.push(index.to_string());
+        self
+    }
+
+    /// The segments of the pointer, from the root down
+    pub fn tokens(&self) -> impl Iterator<Item = ReferenceToken<'_>> {
+        self.tokens.iter().map(|token| match array_index(token) {
+            Some(index) => ReferenceToken::Index(index),
+            None => ReferenceToken::Key(token),
+        })
+    }
+
+    /// The number of segments, 0 for the whole document
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Whether this is the empty pointer, referring to the whole document
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// The pointer to the enclosing container, `None` for the whole
+    /// document
+    pub fn parent(&self) -> Option<JsonPointer> {
+        let (_, parent) = self.tokens.split_last()?;
+        Some(JsonPointer {
+            tokens: parent.to_vec(),
+        })
+    }
+
+    /// Removes the last segment, returning it unescaped
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+
+    /// `other` resolved relative to this pointer
+    pub fn join(&self, other: &JsonPointer) -> JsonPointer {
+        JsonPointer {
+            tokens: self.tokens.iter().chain(&other.tokens).cloned().collect(),
+        }
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = PointerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_pointer(s).map(|tokens| JsonPointer { tokens })
+    }
+}
+
+/// The escaped string form, e.g. `/a~1b/~0c`
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            f.write_str("/")?;
+            f.write_str(&escape(token))?;
+        }
+        Ok(())
+    }
+}
+
+/// A JSON Pointer in either form `Value::pointer` and the other pointer
+/// methods accept: a string such as `"/a/0"` or a `JsonPointer`
+pub trait AsPointer {
+    /// The unescaped reference tokens, or why the pointer is malformed
+    fn reference_tokens(&self) -> Result<Cow<'_, [String]>, PointerError>;
+}
+
+impl AsPointer for str {
+    fn reference_tokens(&self) -> Result<Cow<'_, [String]>, PointerError> {
+        parse_pointer(self).map(Cow::Owned)
+    }
+}
+
+impl AsPointer for String {
+    fn reference_tokens(&self) -> Result<Cow<'_, [String]>, PointerError> {
+        self.as_str().reference_tokens()
+    }
+}
+
+impl AsPointer for JsonPointer {
+    fn reference_tokens(&self) -> Result<Cow<'_, [String]>, PointerError> {
+        Ok(Cow::Borrowed(&self.tokens))
+    }
+}
+
+impl<T: AsPointer + ?Sized> AsPointer for &T {
+    fn reference_tokens(&self) -> Result<Cow<'_, [String]>, PointerError> {
+        (**self).reference_tokens()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonPointer, ReferenceToken};
+    use crate::{json, PointerError, Value};
+
+    fn document() -> Value {
+        json!({
+            "a/b": {"c~d": [10, 20]},
+            "ключ": {"値": true},
+            "": {"": "empty"},
+            "7": "seven"
+        })
+    }
+
+    #[test]
+    fn round_trips_escaped_and_unicode_keys() {
+        for s in ["", "/a~1b/c~0d/1", "/ключ/値", "/", "//", "/~0~1~01", "/7"] {
+            let pointer: JsonPointer = s.parse().unwrap();
+            assert_eq!(pointer.to_string(), s);
+        }
+
+        let built = JsonPointer::new()
+            .push_key("a/b")
+            .push_key("c~d")
+            .push_index(1);
+        assert_eq!(built.to_string(), "/a~1b/c~0d/1");
+        assert_eq!(built, "/a~1b/c~0d/1".parse().unwrap());
+    }
+
+    #[test]
+    fn parse_validates_syntax() {
+        assert_eq!("a/b".parse::<JsonPointer>(), Err(PointerError::Malformed));
+        assert_eq!("/a~2".parse::<JsonPointer>(), Err(PointerError::Malformed));
+        assert_eq!("/a~".parse::<JsonPointer>(), Err(PointerError::Malformed));
+    }
+
+    #[test]
+    fn tokens_distinguish_keys_and_indices() {
+        let pointer: JsonPointer = "/a~1b/0/01/-/12".parse().unwrap();
+        assert_eq!(
+            pointer.tokens().collect::<Vec<_>>(),
+            [
+                ReferenceToken::Key("a/b"),
+                ReferenceToken::Index(0),
+                ReferenceToken::Key("01"),
+                ReferenceToken::Key("-"),
+                ReferenceToken::Index(12),
+            ]
+        );
+    }
+
+    #[test]
+    fn typed_and_string_lookups_agree() {
+        let mut value = document();
+        for s in [
+            "",
+            "/a~1b",
+            "/a~1b/c~0d/1",
+            "/a~1b/c~0d/2",
+            "/ключ/値",
+            "/",
+            "//",
+            "/7",
+            "/missing/x",
+        ] {
+            let pointer: JsonPointer = s.parse().unwrap();
+            assert_eq!(value.pointer(&pointer), value.pointer(s), "{s}");
+            assert_eq!(value.pointer(&pointer.to_string()), value.pointer(s), "{s}");
+        }
+
+        let pointer = JsonPointer::new().push_key("ключ").push_key("new");
+        value.pointer_insert(&pointer, json!(1)).unwrap();
+        *value.pointer_mut(&pointer).unwrap() = json!(2);
+        assert_eq!(value.pointer("/ключ/new"), Some(&json!(2)));
+        assert_eq!(value.pointer_remove(&pointer), Ok(json!(2)));
+    }
+
+    #[test]
+    fn parent_pop_and_join() {
+        let base = JsonPointer::new().push_key("a");
+        let rest = JsonPointer::new().push_key("b").push_index(0);
+        let mut joined = base.join(&rest);
+        assert_eq!(joined.to_string(), "/a/b/0");
+        assert_eq!(joined.len(), 3);
+
+        assert_eq!(joined.parent(), Some("/a/b".parse().unwrap()));
+        assert_eq!(joined.pop(), Some("0".to_string()));
+        assert_eq!(joined.pop(), Some("b".to_string()));
+        assert_eq!(joined, base);
+
+        let root = JsonPointer::new();
+        assert!(root.is_empty());
+        assert_eq!(root.parent(), None);
+        assert_eq!(root.join(&root), root);
+    }
+}
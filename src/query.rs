@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{parse, to_string, ParseError, SerializeError, Value};
+
+impl Value {
+    /// Writes a top-level object as a URL query string such as
+    /// `key=val&other=2`, without the leading `?`
+    ///
+    /// Keys and values are percent-encoded. Numbers, booleans and `null`
+    /// are written as their JSON text and arrays and objects as compact
+    /// JSON. Strings are written as-is unless they would read back as
+    /// another JSON value (`"2"`, `"true"`), in which case they're quoted,
+    /// so `from_query_string` restores them exactly. Keys come out sorted.
+    pub fn to_query_string(&self) -> Result<String, SerializeError> {
+        let Value::Object(object) = self else {
+            return Err(SerializeError::ExpectedObject);
+        };
+        let mut entries: Vec<_> = object.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+
+        let mut output = String::new();
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            if i > 0 {
+                output.push('&');
+            }
+            percent_encode(key, &mut output);
+            output.push('=');
+            match value {
+                Value::String(s) if parse(s).is_err() => percent_encode(s, &mut output),
+                value => percent_encode(&to_string(value)?, &mut output),
+            }
+        }
+        Ok(output)
+    }
+
+    /// Flattens a top-level object into query parameter pairs, `None` for
+    /// every other variant
+    ///
+    /// Nested keys use the bracket convention of PHP and Rails form
+    /// encoders: `{"b": {"c": "x"}}` gives `b[c]=x`, and array elements
+    /// are bracketed by index, so `{"l": [1, 2]}` gives `l[0]=1` and
+    /// `l[1]=2`. Strings are taken as-is, numbers and booleans as their
+    /// JSON text and `null` as the empty string; empty arrays and objects
+    /// produce no pair. Pairs come out in key order and aren't
+    /// percent-encoded, that's left to the URL builder.
+    pub fn to_query_params(&self) -> Option<Vec<(String, String)>> {
+        let object = self.as_object()?;
+        let mut entries: Vec<_> = object.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+
+        let mut params = Vec::new();
+        for (key, value) in entries {
+            push_query_params(key.clone(), value, &mut params);
+        }
+        Some(params)
+    }
+
+    /// Reads a URL query string back into an object, the inverse of
+    /// `to_query_string`
+    ///
+    /// A leading `?` is skipped and `+` decodes to a space. Values that are
+    /// valid JSON become that value, anything else becomes a string. When
+    /// a key repeats, the last value wins.
+    pub fn from_query_string(qs: &str) -> Result<Value, ParseError> {
+        let qs = qs.strip_prefix('?').unwrap_or(qs);
+        let mut object = HashMap::new();
+        for pair in qs.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value)?;
+            let value = parse(&value).unwrap_or(Value::String(value));
+            object.insert(percent_decode(key)?, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// Adds the pairs for `value` found under the bracketed key `prefix`
+fn push_query_params(prefix: String, value: &Value, params: &mut Vec<(String, String)>) {
+    match value {
+        Value::Null => params.push((prefix, String::new())),
+        Value::Boolean(b) => params.push((prefix, b.to_string())),
+        Value::Number(num) => params.push((prefix, num.to_string())),
+        Value::String(s) => params.push((prefix, s.clone())),
+        Value::Array(array) => {
+            for (i, element) in array.iter().enumerate() {
+                push_query_params(format!("{prefix}[{i}]"), element, params);
+            }
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            for (key, member) in entries {
+                push_query_params(format!("{prefix}[{key}]"), member, params);
+            }
+        }
+    }
+}
+
+/// Percent-encodes every byte outside the RFC 3986 unreserved set
+fn percent_encode(input: &str, output: &mut String) {
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                output.push(byte as char)
+            }
+            _ => write!(output, "%{byte:02X}").unwrap(),
+        }
+    }
+}
+
+fn percent_decode(input: &str) -> Result<String, ParseError> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut iter = input.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let high = iter.next().and_then(|b| (b as char).to_digit(16));
+                let low = iter.next().and_then(|b| (b as char).to_digit(16));
+                match (high, low) {
+                    (Some(high), Some(low)) => bytes.push((high * 16 + low) as u8),
+                    _ => return Err(ParseError::InvalidQueryString),
+                }
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| ParseError::InvalidQueryString)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{parse, ParseError, SerializeError, Value};
+
+    fn pairs(pairs: &[(&str, &str)]) -> Option<Vec<(String, String)>> {
+        Some(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn query_params_bracket_nested_keys() {
+        let value = parse(r#"{"a":1,"b":{"c":"x"}}"#).unwrap();
+        assert_eq!(value.to_query_params(), pairs(&[("a", "1"), ("b[c]", "x")]));
+
+        let value = parse(
+            r#"{"q": "a b&c", "tags": ["x", {"y": true}], "n": null,
+                "deep": {"e": {}, "f": [], "g": {"h": 2.5}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value.to_query_params(),
+            pairs(&[
+                ("deep[g][h]", "2.5"),
+                ("n", ""),
+                ("q", "a b&c"),
+                ("tags[0]", "x"),
+                ("tags[1][y]", "true"),
+            ])
+        );
+    }
+
+    #[test]
+    fn query_params_need_an_object() {
+        assert_eq!(parse("[1]").unwrap().to_query_params(), None);
+        assert_eq!(parse("\"a=1\"").unwrap().to_query_params(), None);
+        assert_eq!(parse("{}").unwrap().to_query_params(), pairs(&[]));
+    }
+
+    #[test]
+    fn flat_object_to_query_string() {
+        let value = parse(r#"{"key": "val", "other": 2, "flag": true, "none": null}"#).unwrap();
+
+        assert_eq!(
+            value.to_query_string().unwrap(),
+            "flag=true&key=val&none=null&other=2"
+        );
+    }
+
+    #[test]
+    fn special_characters_are_percent_encoded() {
+        let value = Value::Object(HashMap::from([
+            ("a b&c=d".to_string(), Value::String("x/y?z#ü".to_string())),
+            ("plain-._~".to_string(), Value::String("100%".to_string())),
+        ]));
+
+        assert_eq!(
+            value.to_query_string().unwrap(),
+            "a%20b%26c%3Dd=x%2Fy%3Fz%23%C3%BC&plain-._~=100%25"
+        );
+    }
+
+    #[test]
+    fn nested_values_are_json_encoded() {
+        let value = parse(r#"{"list": [1, "a"], "obj": {"k": null}}"#).unwrap();
+
+        assert_eq!(
+            value.to_query_string().unwrap(),
+            "list=%5B1%2C%22a%22%5D&obj=%7B%22k%22%3Anull%7D"
+        );
+    }
+
+    #[test]
+    fn strings_that_look_like_json_are_quoted() {
+        let value = parse(r#"{"n": "2", "b": "true", "s": "\"quoted\""}"#).unwrap();
+
+        assert_eq!(
+            value.to_query_string().unwrap(),
+            "b=%22true%22&n=%222%22&s=%22%5C%22quoted%5C%22%22"
+        );
+    }
+
+    #[test]
+    fn round_trip_flat_object() {
+        let value = parse(
+            r#"{"name": "Ada Lovelace", "age": 36, "id": "007", "ok": false,
+                "empty": "", "sym": "a&b=c%d+e", "tags": ["x", "y"]}"#,
+        )
+        .unwrap();
+        let query = value.to_query_string().unwrap();
+
+        assert_eq!(Value::from_query_string(&query), Ok(value));
+    }
+
+    #[test]
+    fn from_query_string_form_encoding() {
+        assert_eq!(
+            Value::from_query_string("?q=hello+world&n=2&flag"),
+            Ok(Value::Object(HashMap::from([
+                ("q".to_string(), Value::String("hello world".to_string())),
+                ("n".to_string(), Value::num(2.0)),
+                ("flag".to_string(), Value::String(String::new())),
+            ])))
+        );
+        assert_eq!(
+            Value::from_query_string(""),
+            Ok(Value::Object(HashMap::new()))
+        );
+    }
+
+    #[test]
+    fn from_query_string_bad_encoding() {
+        assert_eq!(
+            Value::from_query_string("a=%zz"),
+            Err(ParseError::InvalidQueryString)
+        );
+        assert_eq!(
+            Value::from_query_string("a=%C3"),
+            Err(ParseError::InvalidQueryString)
+        );
+    }
+
+    #[test]
+    fn to_query_string_requires_object() {
+        assert!(matches!(
+            Value::Array(vec![]).to_query_string(),
+            Err(SerializeError::ExpectedObject)
+        ));
+    }
+}
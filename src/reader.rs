@@ -0,0 +1,41 @@
+use std::str;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{parse, ParseError, Value};
+
+/// Reads all of `reader` asynchronously, then parses it
+pub async fn from_async_reader<R: AsyncRead + Unpin>(mut reader: R) -> Result<Value, ParseError> {
+    let mut input = Vec::new();
+    reader
+        .read_to_end(&mut input)
+        .await
+        .map_err(|e| ParseError::Io(e.kind()))?;
+    parse(str::from_utf8(&input)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::from_async_reader;
+    use crate::{ParseError, Value};
+
+    #[tokio::test]
+    async fn parses_from_cursor() {
+        let reader = Cursor::new(br#"[true, null]"#.to_vec());
+        let expected = Value::Array(vec![Value::Boolean(true), Value::Null]);
+
+        assert_eq!(from_async_reader(reader).await, Ok(expected));
+    }
+
+    #[tokio::test]
+    async fn invalid_utf8_reports_offset() {
+        let reader = Cursor::new(vec![b'"', 0xff, b'"']);
+
+        assert_eq!(
+            from_async_reader(reader).await,
+            Err(ParseError::InvalidUtf8 { valid_up_to: 1 })
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Error messages that point into the source text, for command line tools
+
+use std::fmt::Write;
+
+use crate::parse::{check_surrounding_whitespace, parse_token_list};
+use crate::tokenize::tokenize_spanned;
+use crate::{ParseConfig, ParseError, TokenParseError, Value};
+
+/// Parses `input` like [`parse_with_config`](crate::parse_with_config),
+/// reporting the byte offset where parsing failed along with the error
+///
+/// The offset is where the offending token starts, or the end of the
+/// input (ignoring trailing whitespace) when it ended too early. A value
+/// rejected by `require_container` is reported at its start, and
+/// whitespace rejected by `no_surrounding_whitespace` where it is.
+///
+/// ```
+/// use json_parser::{parse_with_offset, ParseConfig, ParseError, TokenParseError};
+///
+/// assert_eq!(
+///     parse_with_offset("[1 2]", &ParseConfig::default()),
+///     Err((ParseError::ParseError(TokenParseError::ExpectedComma), 3))
+/// );
+/// ```
+pub fn parse_with_offset(input: &str, config: &ParseConfig) -> Result<Value, (ParseError, usize)> {
+    check_surrounding_whitespace(input, config).map_err(|error| {
+        let leading = input.len() - input.trim_start_matches(JSON_WHITESPACE).len();
+        let offset = if leading > 0 {
+            0
+        } else {
+            input.trim_end_matches(JSON_WHITESPACE).len()
+        };
+        (error, offset)
+    })?;
+    let (tokens, starts) = tokenize_spanned(input, config.tokenize_options())
+        .map_err(|(error, offset)| (error.into(), offset))?;
+
+    let mut iter = tokens.iter().peekable();
+    parse_token_list(&mut iter, config).map_err(|error| {
+        let offset = match error {
+            ParseError::EmptyInput => 0,
+            ParseError::ParseError(TokenParseError::UnexpectedEof) => input.trim_end().len(),
+            ParseError::ParseError(TokenParseError::TopLevelMustBeContainer) => starts[0],
+            // the parser fails on the last token it took, or on the first
+            // one when it rejects it after only peeking
+            _ => starts[(tokens.len() - iter.len()).saturating_sub(1)],
+        };
+        (error, offset)
+    })
+}
+
+const JSON_WHITESPACE: [char; 4] = [' ', '\t', '\n', '\r'];
+
+impl ParseError {
+    /// Renders the error the way `rustc` does, with the offending line of
+    /// `source` and a caret under the column where parsing failed
+    ///
+    /// `source` and `config` must be the input and configuration that
+    /// produced the error; `source` is parsed again with
+    /// [`parse_with_offset`] to find the position. When that doesn't
+    /// reproduce this error only the message is returned.
+    ///
+    /// ```
+    /// use json_parser::{parse, ParseConfig};
+    ///
+    /// let source = "[1,\n 2 3]";
+    /// let report = parse(source)
+    ///     .unwrap_err()
+    ///     .report(source, &ParseConfig::default());
+    /// assert_eq!(
+    ///     report,
+    ///     "error: expected `,`\n --> line 2, column 4\n  |\n2 |  2 3]\n  |    ^\n"
+    /// );
+    /// ```
+    pub fn report(&self, source: &str, config: &ParseConfig) -> String {
+        let mut output = format!("error: {self}\n");
+        let offset = match parse_with_offset(source, config) {
+            Err((error, offset)) if error == *self => offset,
+            _ => return output,
+        };
+
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |i| offset + i);
+        let line = source[line_start..line_end].trim_end_matches('\r');
+        let line_number = source[..line_start].matches('\n').count() + 1;
+        let column = source[line_start..offset].chars().count();
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        // tabs stay tabs so the caret lines up however they're displayed
+        let padding: String = source[line_start..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        writeln!(
+            output,
+            "{gutter}--> line {line_number}, column {}",
+            column + 1
+        )
+        .unwrap();
+        writeln!(output, "{gutter} |").unwrap();
+        writeln!(output, "{line_number} | {line}").unwrap();
+        writeln!(output, "{gutter} | {padding}^").unwrap();
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_with_offset;
+    use crate::{
+        parse, parse_with_config, ParseConfig, ParseError, TokenParseError, TokenizeError,
+    };
+
+    #[test]
+    fn unclosed_string_on_second_line() {
+        let source = "{\"a\": 1,\n  \"b\": \"oops}";
+        let error = parse(source).unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError::TokenizeError(TokenizeError::UnclosedQuotes)
+        );
+        assert_eq!(
+            error.report(source, &ParseConfig::default()),
+            "error: unclosed string\n\
+             \x20--> line 2, column 8\n\
+             \x20 |\n\
+             2 |   \"b\": \"oops}\n\
+             \x20 |        ^\n"
+        );
+    }
+
+    #[test]
+    fn offsets_point_at_the_failing_token() {
+        let cases = [
+            ("[1 2]", TokenParseError::ExpectedComma, 3),
+            (r#"{"a":}"#, TokenParseError::ExpectedValue, 5),
+            (
+                "  ]",
+                TokenParseError::UnexpectedToken(crate::Token::RightBracket),
+                2,
+            ),
+            ("[1, 2]  3", TokenParseError::TrailingTokens, 8),
+            ("[1, \n", TokenParseError::UnexpectedEof, 3),
+        ];
+        for (input, error, offset) in cases {
+            assert_eq!(
+                parse_with_offset(input, &ParseConfig::default()),
+                Err((ParseError::ParseError(error), offset)),
+                "{input:?}"
+            );
+        }
+        assert_eq!(
+            parse_with_offset("[1, @]", &ParseConfig::default()),
+            Err((
+                ParseError::TokenizeError(TokenizeError::CharNotRecognized('@')),
+                4
+            ))
+        );
+        assert_eq!(
+            parse_with_offset(" \n", &ParseConfig::default()),
+            Err((ParseError::EmptyInput, 0))
+        );
+        assert_eq!(
+            parse_with_offset("[1]", &ParseConfig::default()),
+            Ok(crate::json!([1]))
+        );
+    }
+
+    #[test]
+    fn report_at_end_of_input_and_in_wide_lines() {
+        let source = "[\"é\", \t";
+        assert_eq!(
+            parse(source)
+                .unwrap_err()
+                .report(source, &ParseConfig::default()),
+            "error: unexpected end of input\n\
+             \x20--> line 1, column 6\n\
+             \x20 |\n\
+             1 | [\"é\", \t\n\
+             \x20 |      ^\n"
+        );
+    }
+
+    #[test]
+    fn offsets_follow_the_config() {
+        let config = ParseConfig {
+            require_container: true,
+            no_surrounding_whitespace: true,
+            preserve_raw_numbers: true,
+            ..ParseConfig::default()
+        };
+        let cases = [
+            ("  1", ParseError::SurroundingWhitespace, 0),
+            ("[1]\n", ParseError::SurroundingWhitespace, 3),
+            ("1", TokenParseError::TopLevelMustBeContainer.into(), 0),
+            ("[1.50 1]", TokenParseError::ExpectedComma.into(), 6),
+        ];
+        for (input, error, offset) in cases {
+            assert_eq!(
+                parse_with_offset(input, &config),
+                Err((error, offset)),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn report_for_a_non_default_config() {
+        let config = ParseConfig {
+            require_container: true,
+            ..ParseConfig::default()
+        };
+        let source = "\n  \"text\"";
+        let error = parse_with_config(source, &config).unwrap_err();
+
+        assert_eq!(
+            error.report(source, &config),
+            "error: top-level value must be an object or array\n\
+             \x20--> line 2, column 3\n\
+             \x20 |\n\
+             2 |   \"text\"\n\
+             \x20 |   ^\n"
+        );
+    }
+
+    #[test]
+    fn report_without_a_matching_error_has_no_snippet() {
+        let config = ParseConfig {
+            require_container: true,
+            ..ParseConfig::default()
+        };
+        let error = parse_with_config("1", &config).unwrap_err();
+
+        assert_eq!(
+            error.report("1", &ParseConfig::default()),
+            "error: top-level value must be an object or array\n"
+        );
+    }
+}
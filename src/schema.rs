@@ -0,0 +1,329 @@
+use crate::Value;
+
+/// A single failed JSON Schema check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON Pointer to the offending value, `""` for the root
+    pub path: String,
+
+    /// What was expected
+    pub message: String,
+}
+
+impl Value {
+    /// Validates the value against a JSON Schema, returning every failed
+    /// check rather than stopping at the first one
+    ///
+    /// Supported keywords: `type`, `required`, `properties`, `items`,
+    /// `minLength`, `maxLength`, `minimum`, `maximum`, `enum` and `const`.
+    /// Other keywords are ignored, as is a schema that isn't an object.
+    pub fn validate(&self, schema: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        validate_at(self, schema, &mut String::new(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn validate_at(
+    value: &Value,
+    schema: &Value,
+    path: &mut String,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+    let mut fail = |message: String| {
+        errors.push(ValidationError {
+            path: path.clone(),
+            message,
+        })
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names
+                .iter()
+                .filter_map(|name| match name {
+                    Value::String(name) => Some(name.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+            fail(format!(
+                "expected type {}, found {}",
+                allowed.join(" or "),
+                value.type_name()
+            ));
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            fail("value is not one of the allowed enum values".to_string());
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            fail("value does not match const".to_string());
+        }
+    }
+
+    if let Value::String(s) = value {
+        let len = s.chars().count() as f64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_f64) {
+            if len < min {
+                fail(format!("string shorter than minLength {min}"));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_f64) {
+            if len > max {
+                fail(format!("string longer than maxLength {max}"));
+            }
+        }
+    }
+
+    if let Some(num) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+            if num < min {
+                fail(format!("{num} is less than minimum {min}"));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+            if num > max {
+                fail(format!("{num} is greater than maximum {max}"));
+            }
+        }
+    }
+
+    if let Value::Object(object) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required {
+                if let Value::String(key) = key {
+                    if !object.contains_key(key) {
+                        fail(format!("missing required property \"{key}\""));
+                    }
+                }
+            }
+        }
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            // sorted so errors come out in a stable order
+            let mut properties: Vec<_> = properties.iter().collect();
+            properties.sort_unstable_by_key(|(key, _)| *key);
+            for (key, property_schema) in properties {
+                if let Some(property) = object.get(key) {
+                    let len = path.len();
+                    push_segment(path, key);
+                    validate_at(property, property_schema, path, errors);
+                    path.truncate(len);
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(array), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in array.iter().enumerate() {
+            let len = path.len();
+            push_segment(path, &i.to_string());
+            validate_at(item, item_schema, path, errors);
+            path.truncate(len);
+        }
+    }
+}
+
+/// Appends `/segment` to a JSON Pointer, escaping `~` and `/`
+fn push_segment(path: &mut String, segment: &str) {
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("null", Value::Null)
+        | ("boolean", Value::Boolean(_))
+        | ("number", Value::Number(_))
+        | ("string", Value::String(_))
+        | ("array", Value::Array(_))
+        | ("object", Value::Object(_)) => true,
+        ("integer", Value::Number(num)) => num.as_f64().fract() == 0.0,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationError;
+    use crate::{parse, Value};
+
+    /// The product schema from json-schema.org's "Getting Started" guide
+    fn product_schema() -> Value {
+        parse(
+            r#"{
+              "$schema": "https://json-schema.org/draft/2020-12/schema",
+              "title": "Product",
+              "type": "object",
+              "properties": {
+                "productId": { "type": "integer" },
+                "productName": { "type": "string", "minLength": 1 },
+                "price": { "type": "number", "minimum": 0 },
+                "tags": {
+                  "type": "array",
+                  "items": { "type": "string", "maxLength": 8 }
+                },
+                "dimensions": {
+                  "type": "object",
+                  "properties": {
+                    "length": { "type": "number" },
+                    "width": { "type": "number" },
+                    "height": { "type": "number" }
+                  },
+                  "required": [ "length", "width", "height" ]
+                }
+              },
+              "required": [ "productId", "productName", "price" ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn error(path: &str, message: &str) -> ValidationError {
+        ValidationError {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn valid_product() {
+        let product = parse(
+            r#"{
+              "productId": 1,
+              "productName": "An ice sculpture",
+              "price": 12.5,
+              "tags": [ "cold", "ice" ],
+              "dimensions": { "length": 7.0, "width": 12.0, "height": 9.5 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(product.validate(&product_schema()), Ok(()));
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let product = parse(
+            r#"{
+              "productId": 1.5,
+              "productName": "",
+              "tags": [ "cold", "much-too-long", 3 ],
+              "dimensions": { "length": 7.0 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            product.validate(&product_schema()),
+            Err(vec![
+                error("", "missing required property \"price\""),
+                error("/dimensions", "missing required property \"width\""),
+                error("/dimensions", "missing required property \"height\""),
+                error("/productId", "expected type integer, found number"),
+                error("/productName", "string shorter than minLength 1"),
+                error("/tags/1", "string longer than maxLength 8"),
+                error("/tags/2", "expected type string, found number"),
+            ])
+        );
+    }
+
+    #[test]
+    fn wrong_root_type() {
+        let value = parse("[]").unwrap();
+
+        assert_eq!(
+            value.validate(&product_schema()),
+            Err(vec![error("", "expected type object, found array")])
+        );
+    }
+
+    #[test]
+    fn type_list_minimum_and_maximum() {
+        let schema = parse(r#"{"type": ["number", "null"], "minimum": 1, "maximum": 10}"#).unwrap();
+
+        assert_eq!(Value::Null.validate(&schema), Ok(()));
+        assert_eq!(Value::num(5.0).validate(&schema), Ok(()));
+        assert_eq!(
+            Value::num(11.0).validate(&schema),
+            Err(vec![error("", "11 is greater than maximum 10")])
+        );
+        assert_eq!(
+            Value::num(0.0).validate(&schema),
+            Err(vec![error("", "0 is less than minimum 1")])
+        );
+        assert_eq!(
+            Value::Boolean(true).validate(&schema),
+            Err(vec![error(
+                "",
+                "expected type number or null, found boolean"
+            )])
+        );
+    }
+
+    #[test]
+    fn enum_and_const() {
+        let schema = parse(r#"{"enum": ["red", "amber", "green", null, 42]}"#).unwrap();
+
+        assert_eq!(parse(r#""red""#).unwrap().validate(&schema), Ok(()));
+        assert_eq!(Value::Null.validate(&schema), Ok(()));
+        assert_eq!(Value::num(42.0).validate(&schema), Ok(()));
+        assert_eq!(
+            parse(r#""blue""#).unwrap().validate(&schema),
+            Err(vec![error(
+                "",
+                "value is not one of the allowed enum values"
+            )])
+        );
+
+        let schema = parse(r#"{"properties": {"country": {"const": "Canada"}}}"#).unwrap();
+        assert_eq!(
+            parse(r#"{"country": "Canada"}"#).unwrap().validate(&schema),
+            Ok(())
+        );
+        assert_eq!(
+            parse(r#"{"country": "Mexico"}"#).unwrap().validate(&schema),
+            Err(vec![error("/country", "value does not match const")])
+        );
+    }
+
+    #[test]
+    fn string_length_counts_characters() {
+        let schema = parse(r#"{"minLength": 2, "maxLength": 3}"#).unwrap();
+
+        assert_eq!(parse(r#""日本語""#).unwrap().validate(&schema), Ok(()));
+        assert_eq!(
+            parse(r#""A""#).unwrap().validate(&schema),
+            Err(vec![error("", "string shorter than minLength 2")])
+        );
+    }
+
+    #[test]
+    fn pointer_paths_escape_keys() {
+        let schema = parse(r#"{"properties": {"a/b~c": {"type": "string"}}}"#).unwrap();
+        let value = parse(r#"{"a/b~c": 1}"#).unwrap();
+
+        assert_eq!(
+            value.validate(&schema),
+            Err(vec![error(
+                "/a~1b~0c",
+                "expected type string, found number"
+            )])
+        );
+    }
+}
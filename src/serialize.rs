@@ -0,0 +1,1577 @@
+mod color;
+
+use std::fmt::{self, Write};
+use std::{io, slice, vec};
+
+pub use color::{to_string_colored, ColoredFormatter, Theme};
+
+use crate::{NumberRepr, Value};
+
+/// Options controlling how a `Value` is written out
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Number of spaces per nesting level, `None` for compact output
+    pub indent: Option<usize>,
+
+    /// Write every character above U+007F as a `\uXXXX` escape, using
+    /// surrogate pairs for characters outside the Basic Multilingual Plane
+    pub escape_non_ascii: bool,
+
+    /// Write `<`, `>` and `&` as `\u003c`, `\u003e` and `\u0026` so the
+    /// output can be embedded in an HTML `<script>` block
+    pub escape_html: bool,
+
+    /// How NaN and infinite numbers are written
+    pub non_finite: NonFiniteNumbers,
+
+    /// With `indent` set, containers whose single-line form (`[1, 2]`,
+    /// `{"a": 1}`, escapes included) is at most this many characters are
+    /// kept on one line; `0` always expands them
+    pub inline_limit: usize,
+}
+
+impl SerializeOptions {
+    /// Options for pretty output indented by two spaces
+    pub fn pretty() -> Self {
+        Self {
+            indent: Some(2),
+            ..Self::default()
+        }
+    }
+}
+
+/// What to write for NaN and infinite numbers, which JSON can't represent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFiniteNumbers {
+    /// Fail with `SerializeError::NonFiniteNumber`
+    #[default]
+    Error,
+
+    /// Write `null`
+    Null,
+
+    /// Write the strings `"NaN"`, `"Infinity"` and `"-Infinity"`
+    AsString,
+}
+
+/// One of the possible errors that could occur while serializing a value
+#[derive(Debug)]
+pub enum SerializeError {
+    /// A NaN or infinite number was found while `non_finite` was `Error`
+    NonFiniteNumber(f64),
+
+    /// The underlying writer failed
+    Io(io::Error),
+
+    /// The underlying `fmt::Write` target failed
+    Fmt,
+
+    /// The value has to be an object for this format, e.g. a query string
+    ExpectedObject,
+}
+
+impl From<fmt::Error> for SerializeError {
+    fn from(_: fmt::Error) -> Self {
+        Self::Fmt
+    }
+}
+
+type SerializeResult = Result<(), SerializeError>;
+
+/// A character that has to be escaped inside a JSON string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharEscape {
+    /// `\"`
+    Quote,
+    /// `\\`
+    Backslash,
+    /// `\b`
+    Backspace,
+    /// `\f`
+    FormFeed,
+    /// `\n`
+    LineFeed,
+    /// `\r`
+    CarriageReturn,
+    /// `\t`
+    Tab,
+    /// `\uXXXX` for a single UTF-16 code unit
+    Unicode(u16),
+}
+
+/// Hooks that decide how each piece of a document is written
+///
+/// For a document the serializer calls, in order: `write_null`,
+/// `write_bool`, `write_number` (`write_number_str` for integers and raw
+/// literals) or `begin_string` for scalars (a string
+/// is a series of `write_string_fragment` and `write_char_escape` calls
+/// followed by `end_string`); `begin_array`, then for every element
+/// `begin_array_value(first)`, the element, `end_array_value`, and
+/// finally `end_array`; `begin_object`, then for every member
+/// `begin_object_key(first)`, the key string, `end_object_key`,
+/// `begin_object_value`, the value, `end_object_value`, and finally
+/// `end_object`. Every method has a default producing compact JSON.
+pub trait Formatter {
+    fn write_null<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("null")
+    }
+
+    fn write_bool<W: ?Sized + Write>(&mut self, writer: &mut W, value: bool) -> fmt::Result {
+        writer.write_str(if value { "true" } else { "false" })
+    }
+
+    /// Only called for finite numbers, the serializer applies the
+    /// `non_finite` policy itself
+    fn write_number<W: ?Sized + Write>(&mut self, writer: &mut W, value: f64) -> fmt::Result {
+        write_number(writer, value)
+    }
+
+    /// Integers and raw number literals, already spelled as JSON
+    fn write_number_str<W: ?Sized + Write>(&mut self, writer: &mut W, value: &str) -> fmt::Result {
+        writer.write_str(value)
+    }
+
+    fn begin_string<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char('"')
+    }
+
+    fn end_string<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char('"')
+    }
+
+    /// A run of string content that needs no escaping
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> fmt::Result {
+        writer.write_str(fragment)
+    }
+
+    fn write_char_escape<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        escape: CharEscape,
+    ) -> fmt::Result {
+        match escape {
+            CharEscape::Quote => writer.write_str("\\\""),
+            CharEscape::Backslash => writer.write_str("\\\\"),
+            CharEscape::Backspace => writer.write_str("\\b"),
+            CharEscape::FormFeed => writer.write_str("\\f"),
+            CharEscape::LineFeed => writer.write_str("\\n"),
+            CharEscape::CarriageReturn => writer.write_str("\\r"),
+            CharEscape::Tab => writer.write_str("\\t"),
+            CharEscape::Unicode(unit) => write!(writer, "\\u{unit:04x}"),
+        }
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char('[')
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char(']')
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            writer.write_char(',')
+        }
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char('{')
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char('}')
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            writer.write_char(',')
+        }
+    }
+
+    fn end_object_key<W: ?Sized + Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char(':')
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Writes JSON without any whitespace
+#[derive(Debug, Clone, Default)]
+pub struct CompactFormatter;
+
+impl Formatter for CompactFormatter {}
+
+/// Writes JSON with one value per line, indented per nesting level
+#[derive(Debug, Clone)]
+pub struct PrettyFormatter {
+    indent: usize,
+    current_indent: usize,
+    has_value: bool,
+}
+
+impl PrettyFormatter {
+    /// Indents by two spaces
+    pub fn new() -> Self {
+        Self::with_indent(2)
+    }
+
+    /// Indents by `indent` spaces per level
+    pub fn with_indent(indent: usize) -> Self {
+        Self {
+            indent,
+            current_indent: 0,
+            has_value: false,
+        }
+    }
+
+    fn write_indent<W: ?Sized + Write>(&self, writer: &mut W) -> fmt::Result {
+        for _ in 0..self.indent * self.current_indent {
+            writer.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn begin_container<W: ?Sized + Write>(&mut self, writer: &mut W, open: char) -> fmt::Result {
+        self.current_indent += 1;
+        self.has_value = false;
+        writer.write_char(open)
+    }
+
+    fn end_container<W: ?Sized + Write>(&mut self, writer: &mut W, close: char) -> fmt::Result {
+        self.current_indent -= 1;
+        if self.has_value {
+            writer.write_char('\n')?;
+            self.write_indent(writer)?;
+        }
+        writer.write_char(close)
+    }
+
+    fn begin_entry<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        writer.write_str(if first { "\n" } else { ",\n" })?;
+        self.write_indent(writer)
+    }
+}
+
+impl Default for PrettyFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for PrettyFormatter {
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.begin_container(writer, '[')
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.end_container(writer, ']')
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        self.begin_entry(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.begin_container(writer, '{')
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.end_container(writer, '}')
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        self.begin_entry(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str(": ")
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+}
+
+/// Escapes `input` the way it would appear between the quotes of a
+/// compact JSON string, without adding the quotes themselves
+///
+/// The inverse of [`unescape_string`](crate::unescape_string).
+pub fn escape_string(input: &str) -> String {
+    let mut output = String::new();
+    let options = SerializeOptions::default();
+    Serializer::new(&mut output, ContentFormatter, &options)
+        .write_string(input)
+        .expect("writing to a String never fails");
+    output
+}
+
+/// Compact formatting that leaves out the quotes around strings
+struct ContentFormatter;
+
+impl Formatter for ContentFormatter {
+    fn begin_string<W: ?Sized + Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+
+    fn end_string<W: ?Sized + Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Serializes `value` as compact JSON
+pub fn to_string(value: &Value) -> Result<String, SerializeError> {
+    to_string_with_options(value, &SerializeOptions::default())
+}
+
+impl Value {
+    /// The exact number of bytes `to_string` writes for this value, counted
+    /// in one pass without building the string
+    ///
+    /// NaN and infinities, which `to_string` rejects, count as `null`.
+    pub fn serialized_len(&self) -> usize {
+        let options = SerializeOptions {
+            non_finite: NonFiniteNumbers::Null,
+            ..SerializeOptions::default()
+        };
+        let mut counter = ByteCounter(0);
+        let mut scalars = Serializer::new(&mut counter, CompactFormatter, &options);
+        // the order members are written in doesn't change the length, so
+        // unlike the serializer this doesn't sort object keys
+        let mut pending = vec![self];
+        while let Some(value) = pending.pop() {
+            match value {
+                Value::Array(array) => {
+                    scalars.out.0 += 2 + array.len().saturating_sub(1);
+                    pending.extend(array);
+                }
+                Value::Object(object) => {
+                    // braces, a colon per member and the commas between them
+                    scalars.out.0 += 2 + object.len() * 2 - usize::from(!object.is_empty());
+                    for (key, member) in object {
+                        scalars
+                            .write_string(key)
+                            .expect("counting bytes never fails");
+                        pending.push(member);
+                    }
+                }
+                scalar => scalars
+                    .write_value(scalar)
+                    .expect("counting bytes never fails"),
+            }
+        }
+        counter.0
+    }
+}
+
+/// Counts the bytes written instead of storing them
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Serializes `value` as JSON indented by two spaces
+pub fn to_string_pretty(value: &Value) -> Result<String, SerializeError> {
+    to_string_with_options(value, &SerializeOptions::pretty())
+}
+
+/// Serializes `value` according to `options`
+pub fn to_string_with_options(
+    value: &Value,
+    options: &SerializeOptions,
+) -> Result<String, SerializeError> {
+    let mut output = String::new();
+    write_with_options(&mut output, value, options)?;
+    Ok(output)
+}
+
+/// Serializes `value` through a custom `formatter`; `options.indent` is
+/// ignored since layout is up to the formatter
+pub fn to_string_with_formatter<F: Formatter>(
+    value: &Value,
+    formatter: F,
+    options: &SerializeOptions,
+) -> Result<String, SerializeError> {
+    let mut output = String::new();
+    Serializer::new(&mut output, formatter, options).write_value(value)?;
+    Ok(output)
+}
+
+/// Serializes `value` as compact JSON into `writer`
+pub fn to_writer<W: io::Write>(writer: W, value: &Value) -> Result<(), SerializeError> {
+    to_writer_with_options(writer, value, &SerializeOptions::default())
+}
+
+/// Serializes `value` as JSON indented by two spaces into `writer`
+pub fn to_writer_pretty<W: io::Write>(writer: W, value: &Value) -> Result<(), SerializeError> {
+    to_writer_with_options(writer, value, &SerializeOptions::pretty())
+}
+
+/// Serializes `value` into `writer` according to `options`
+pub fn to_writer_with_options<W: io::Write>(
+    writer: W,
+    value: &Value,
+    options: &SerializeOptions,
+) -> Result<(), SerializeError> {
+    let mut adapter = IoAdapter {
+        writer,
+        error: None,
+    };
+    let result = write_with_options(&mut adapter, value, options);
+    adapter.into_result(result)
+}
+
+/// Serializes `value` into `writer` through a custom `formatter`;
+/// `options.indent` is ignored since layout is up to the formatter
+pub fn to_writer_with_formatter<W: io::Write, F: Formatter>(
+    writer: W,
+    value: &Value,
+    formatter: F,
+    options: &SerializeOptions,
+) -> Result<(), SerializeError> {
+    let mut adapter = IoAdapter {
+        writer,
+        error: None,
+    };
+    let result = Serializer::new(&mut adapter, formatter, options).write_value(value);
+    adapter.into_result(result)
+}
+
+/// Compact JSON, or JSON indented by two spaces with `{:#}`
+///
+/// Writes straight into the formatter without building a `String`. NaN
+/// and infinite numbers are written as `null` since `Display` can't
+/// report them, use `to_string` to have them rejected.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = SerializeOptions {
+            indent: f.alternate().then_some(2),
+            non_finite: NonFiniteNumbers::Null,
+            ..SerializeOptions::default()
+        };
+        write_with_options(f, self, &options).map_err(|_| fmt::Error)
+    }
+}
+
+/// Picks the built-in formatter matching `options.indent`
+fn write_with_options<W: Write>(
+    out: W,
+    value: &Value,
+    options: &SerializeOptions,
+) -> SerializeResult {
+    match options.indent {
+        Some(indent) => {
+            let mut serializer =
+                Serializer::new(out, PrettyFormatter::with_indent(indent), options);
+            serializer.inline_limit = options.inline_limit;
+            serializer.write_value(value)
+        }
+        None => Serializer::new(out, CompactFormatter, options).write_value(value),
+    }
+}
+
+/// Single-line layout used for containers kept inline in pretty output
+struct InlineFormatter;
+
+impl Formatter for InlineFormatter {
+    fn begin_array_value<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        writer.write_str(if first { "" } else { ", " })
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        writer.write_str(if first { "" } else { ", " })
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str(": ")
+    }
+}
+
+/// Buffers output, failing as soon as it grows past `limit` characters
+struct LimitedWriter {
+    buffer: String,
+    chars: usize,
+    limit: usize,
+}
+
+impl Write for LimitedWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.chars += s.chars().count();
+        if self.chars > self.limit {
+            return Err(fmt::Error);
+        }
+        self.buffer.push_str(s);
+        Ok(())
+    }
+}
+
+/// Bridges `fmt::Write` to `io::Write`, keeping the underlying io error
+struct IoAdapter<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W> IoAdapter<W> {
+    /// Swaps the opaque `fmt::Error` for the io error that caused it
+    fn into_result(self, result: SerializeResult) -> SerializeResult {
+        match result {
+            Err(SerializeError::Fmt) => Err(SerializeError::Io(
+                self.error
+                    .unwrap_or_else(|| io::Error::other("formatter error")),
+            )),
+            result => result,
+        }
+    }
+}
+
+impl<W: io::Write> Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+/// A container whose children are still being written, and whether the
+/// next child is its first
+enum Frame<'v> {
+    Array(slice::Iter<'v, Value>, bool),
+    Object(vec::IntoIter<(&'v String, &'v Value)>, bool),
+}
+
+struct Serializer<'a, W, F> {
+    out: W,
+    formatter: F,
+    options: &'a SerializeOptions,
+
+    /// Longest single-line container to write inline, `0` for none
+    inline_limit: usize,
+}
+
+impl<'a, W: Write, F: Formatter> Serializer<'a, W, F> {
+    fn new(out: W, formatter: F, options: &'a SerializeOptions) -> Self {
+        Self {
+            out,
+            formatter,
+            options,
+            inline_limit: 0,
+        }
+    }
+
+    /// Writes `value` using an explicit stack of open containers instead of
+    /// recursion, so nesting depth is only limited by the heap
+    fn write_value(&mut self, value: &Value) -> SerializeResult {
+        let mut stack = Vec::new();
+        self.write_node(value, &mut stack)?;
+
+        while let Some(frame) = stack.last_mut() {
+            match frame {
+                Frame::Array(items, first) => match items.next() {
+                    Some(value) => {
+                        let first = std::mem::replace(first, false);
+                        self.formatter.begin_array_value(&mut self.out, first)?;
+                        if !self.write_node(value, &mut stack)? {
+                            self.formatter.end_array_value(&mut self.out)?;
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        self.formatter.end_array(&mut self.out)?;
+                        self.end_parent_value(&stack)?;
+                    }
+                },
+                Frame::Object(entries, first) => match entries.next() {
+                    Some((key, value)) => {
+                        let first = std::mem::replace(first, false);
+                        self.formatter.begin_object_key(&mut self.out, first)?;
+                        self.write_string(key)?;
+                        self.formatter.end_object_key(&mut self.out)?;
+                        self.formatter.begin_object_value(&mut self.out)?;
+                        if !self.write_node(value, &mut stack)? {
+                            self.formatter.end_object_value(&mut self.out)?;
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        self.formatter.end_object(&mut self.out)?;
+                        self.end_parent_value(&stack)?;
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Tells the container that just became the innermost open one that
+    /// its current child, a container itself, is complete
+    fn end_parent_value(&mut self, stack: &[Frame]) -> SerializeResult {
+        match stack.last() {
+            Some(Frame::Array(..)) => self.formatter.end_array_value(&mut self.out)?,
+            Some(Frame::Object(..)) => self.formatter.end_object_value(&mut self.out)?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Writes a scalar or an empty container in full and returns `false`,
+    /// or opens a non-empty container whose children are pushed on `stack`
+    /// and returns `true`
+    fn write_node<'v>(
+        &mut self,
+        value: &'v Value,
+        stack: &mut Vec<Frame<'v>>,
+    ) -> Result<bool, SerializeError> {
+        match value {
+            Value::Null => self.formatter.write_null(&mut self.out)?,
+            Value::Boolean(b) => self.formatter.write_bool(&mut self.out, *b)?,
+            Value::Number(NumberRepr::Integer(int)) => {
+                let mut digits = [0; 20];
+                let int = format_integer(*int, &mut digits);
+                self.formatter.write_number_str(&mut self.out, int)?
+            }
+            Value::Number(NumberRepr::Raw(literal)) => {
+                self.formatter.write_number_str(&mut self.out, literal)?
+            }
+            Value::Number(NumberRepr::Float(num)) => self.write_number(*num)?,
+            Value::String(s) => self.write_string(s)?,
+            Value::Array(array) if array.is_empty() => {
+                self.formatter.begin_array(&mut self.out)?;
+                self.formatter.end_array(&mut self.out)?;
+            }
+            Value::Object(object) if object.is_empty() => {
+                self.formatter.begin_object(&mut self.out)?;
+                self.formatter.end_object(&mut self.out)?;
+            }
+            Value::Array(_) | Value::Object(_) if self.write_inline(value)? => {}
+            Value::Array(array) => {
+                self.formatter.begin_array(&mut self.out)?;
+                stack.push(Frame::Array(array.iter(), true));
+                return Ok(true);
+            }
+            Value::Object(object) => {
+                // keys are sorted so the output doesn't depend on HashMap order
+                let mut entries: Vec<_> = object.iter().collect();
+                entries.sort_unstable_by_key(|(key, _)| *key);
+
+                self.formatter.begin_object(&mut self.out)?;
+                stack.push(Frame::Object(entries.into_iter(), true));
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Writes `value` on a single line when that fits in `inline_limit`
+    /// characters, returning whether it did
+    fn write_inline(&mut self, value: &Value) -> Result<bool, SerializeError> {
+        if self.inline_limit == 0 {
+            return Ok(false);
+        }
+        let mut line = LimitedWriter {
+            buffer: String::new(),
+            chars: 0,
+            limit: self.inline_limit,
+        };
+        match Serializer::new(&mut line, InlineFormatter, self.options).write_value(value) {
+            Ok(()) => {
+                self.out.write_str(&line.buffer)?;
+                Ok(true)
+            }
+            Err(SerializeError::Fmt) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write_number(&mut self, num: f64) -> SerializeResult {
+        if num.is_finite() {
+            return Ok(self.formatter.write_number(&mut self.out, num)?);
+        }
+        match self.options.non_finite {
+            NonFiniteNumbers::Error => Err(SerializeError::NonFiniteNumber(num)),
+            NonFiniteNumbers::Null => Ok(self.formatter.write_null(&mut self.out)?),
+            NonFiniteNumbers::AsString if num.is_nan() => self.write_string("NaN"),
+            NonFiniteNumbers::AsString if num > 0.0 => self.write_string("Infinity"),
+            NonFiniteNumbers::AsString => self.write_string("-Infinity"),
+        }
+    }
+
+    /// Hands runs of plain characters to the formatter as fragments and
+    /// everything that needs escaping as a `CharEscape`
+    fn write_string(&mut self, s: &str) -> SerializeResult {
+        self.formatter.begin_string(&mut self.out)?;
+        let mut start = 0;
+        for (i, ch) in s.char_indices() {
+            let escape = match ch {
+                '"' => CharEscape::Quote,
+                '\\' => CharEscape::Backslash,
+                '\u{8}' => CharEscape::Backspace,
+                '\u{c}' => CharEscape::FormFeed,
+                '\n' => CharEscape::LineFeed,
+                '\r' => CharEscape::CarriageReturn,
+                '\t' => CharEscape::Tab,
+                c if c < '\u{20}' => CharEscape::Unicode(c as u16),
+                '<' | '>' | '&' if self.options.escape_html => CharEscape::Unicode(ch as u16),
+                c if c > '\u{7f}' && self.options.escape_non_ascii => {
+                    self.write_fragment(&s[start..i])?;
+                    let mut units = [0; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        self.formatter
+                            .write_char_escape(&mut self.out, CharEscape::Unicode(*unit))?;
+                    }
+                    start = i + c.len_utf8();
+                    continue;
+                }
+                _ => continue,
+            };
+            self.write_fragment(&s[start..i])?;
+            self.formatter.write_char_escape(&mut self.out, escape)?;
+            start = i + ch.len_utf8();
+        }
+        self.write_fragment(&s[start..])?;
+        Ok(self.formatter.end_string(&mut self.out)?)
+    }
+
+    fn write_fragment(&mut self, fragment: &str) -> fmt::Result {
+        if fragment.is_empty() {
+            return Ok(());
+        }
+        self.formatter
+            .write_string_fragment(&mut self.out, fragment)
+    }
+}
+
+/// Spells out `int` at the end of `buffer`, which fits `i64::MIN`, and
+/// returns the digits written
+fn format_integer(int: i64, buffer: &mut [u8; 20]) -> &str {
+    let mut rest = int.unsigned_abs();
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (rest % 10) as u8;
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+    if int < 0 {
+        start -= 1;
+        buffer[start] = b'-';
+    }
+    std::str::from_utf8(&buffer[start..]).expect("digits are ASCII")
+}
+
+/// Writes the shortest decimal that parses back to the same `f64`
+///
+/// Integral values up to 2^53 are written without a fraction (`3`, not
+/// `3.0`). Magnitudes below `1e-6` or above 2^53 switch to exponent
+/// notation (`1e20`, `5e-324`): a whole float past 2^53 written as an
+/// integer would parse back as an `Integer` of a different value.
+/// Negative zero is written as `-0`.
+fn write_number<W: ?Sized + Write>(out: &mut W, num: f64) -> fmt::Result {
+    const MAX_SAFE: f64 = 9007199254740992.0;
+    // `{}` and `{:e}` both pick the shortest digits that round-trip
+    if num == 0.0 || (1e-6..=MAX_SAFE).contains(&num.abs()) {
+        write!(out, "{num}")
+    } else {
+        write!(out, "{num:e}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use std::fmt::{self, Write};
+
+    use super::{
+        escape_string, format_integer, to_string, to_string_pretty, to_string_with_formatter,
+        to_string_with_options, to_writer, to_writer_with_formatter, to_writer_with_options,
+        CharEscape, Formatter, NonFiniteNumbers, PrettyFormatter, SerializeError, SerializeOptions,
+    };
+    use crate::{json, parse, parse_with_config, NumberRepr, ParseConfig, Value};
+
+    fn nested() -> Value {
+        Value::Object(HashMap::from([
+            (
+                "b".to_string(),
+                Value::Array(vec![Value::num(1.0), Value::Null]),
+            ),
+            ("a".to_string(), Value::String("x".to_string())),
+            ("c".to_string(), Value::Object(HashMap::new())),
+        ]))
+    }
+
+    #[test]
+    fn compact_output() {
+        assert_eq!(
+            to_string(&nested()).unwrap(),
+            r#"{"a":"x","b":[1,null],"c":{}}"#
+        );
+    }
+
+    #[test]
+    fn pretty_output() {
+        let expected = "{\n  \"a\": \"x\",\n  \"b\": [\n    1,\n    null\n  ],\n  \"c\": {}\n}";
+        assert_eq!(to_string_pretty(&nested()).unwrap(), expected);
+    }
+
+    #[test]
+    fn mixed_nesting_output() {
+        let value = parse(r#"[[], {}, [[1, [2]], {"k": {"j": [true, "s"]}}], null]"#).unwrap();
+
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"[[],{},[[1,[2]],{"k":{"j":[true,"s"]}}],null]"#
+        );
+        assert_eq!(
+            to_string_pretty(&value).unwrap(),
+            r#"[
+  [],
+  {},
+  [
+    [
+      1,
+      [
+        2
+      ]
+    ],
+    {
+      "k": {
+        "j": [
+          true,
+          "s"
+        ]
+      }
+    }
+  ],
+  null
+]"#
+        );
+    }
+
+    /// Builds `[[[...]]]` nested `depth` levels deep without recursion
+    fn deep_array(depth: usize) -> Value {
+        let mut value = Value::Array(vec![]);
+        for _ in 1..depth {
+            value = Value::Array(vec![value]);
+        }
+        value
+    }
+
+    /// Tears down `value` without recursion, the derived drop glue would
+    /// overflow the stack on very deep documents
+    fn drop_deep_array(mut value: Value) {
+        while let Value::Array(mut array) = value {
+            value = array.pop().unwrap_or(Value::Null);
+        }
+    }
+
+    #[test]
+    fn million_deep_array_does_not_overflow() {
+        let depth = 1_000_000;
+        let value = deep_array(depth);
+
+        let output = to_string(&value).unwrap();
+        assert_eq!(output.len(), 2 * depth);
+        assert!(output.starts_with("[[[[") && output.ends_with("]]]]"));
+
+        let mut sink = std::io::sink();
+        to_writer(&mut sink, &value).unwrap();
+
+        drop_deep_array(value);
+    }
+
+    #[test]
+    fn deep_pretty_output() {
+        let value = deep_array(3);
+
+        assert_eq!(to_string_pretty(&value).unwrap(), "[\n  [\n    []\n  ]\n]");
+        drop_deep_array(value);
+    }
+
+    #[test]
+    fn writer_output_matches_string() {
+        let mut buf = Vec::new();
+        to_writer(&mut buf, &nested()).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            to_string(&nested()).unwrap()
+        );
+    }
+
+    fn check_number(num: f64, expected: &str) {
+        assert_eq!(to_string(&Value::num(num)).unwrap(), expected);
+    }
+
+    #[test]
+    fn integral_numbers_have_no_fraction() {
+        check_number(3.0, "3");
+        check_number(-42.0, "-42");
+        check_number(9007199254740992.0, "9007199254740992");
+        check_number(-9007199254740992.0, "-9007199254740992");
+    }
+
+    #[test]
+    fn fractions_use_shortest_round_trip() {
+        check_number(0.1, "0.1");
+        check_number(0.1 + 0.2, "0.30000000000000004");
+        check_number(-123.456, "-123.456");
+        check_number(0.000001, "0.000001");
+    }
+
+    #[test]
+    fn negative_zero() {
+        check_number(-0.0, "-0");
+        check_number(0.0, "0");
+    }
+
+    #[test]
+    fn extreme_magnitudes_use_exponents() {
+        check_number(9007199254740994.0, "9.007199254740994e15");
+        check_number(2.0362830660721725e17, "2.0362830660721725e17");
+        check_number(1e20, "1e20");
+        check_number(1e21, "1e21");
+        check_number(f64::MAX, "1.7976931348623157e308");
+        check_number(1e-7, "1e-7");
+        check_number(f64::MIN_POSITIVE, "2.2250738585072014e-308");
+        // subnormals
+        check_number(5e-324, "5e-324");
+        check_number(-1.5e-320, "-1.5e-320");
+    }
+
+    #[test]
+    fn integers_round_trip_exactly() {
+        for input in [
+            "0",
+            "-7",
+            "9007199254740993",
+            "9223372036854775807",
+            "-9223372036854775808",
+        ] {
+            let value = parse(input).unwrap();
+            assert!(
+                matches!(value, Value::Number(NumberRepr::Integer(_))),
+                "{input}"
+            );
+            assert_eq!(to_string(&value).unwrap(), input);
+            assert_eq!(value.serialized_len(), input.len());
+        }
+        assert_eq!(
+            to_string(&json!([1, 1.5, u64::MAX])).unwrap(),
+            "[1,1.5,1.8446744073709552e19]"
+        );
+    }
+
+    #[test]
+    fn raw_numbers_written_as_parsed() {
+        let config = ParseConfig {
+            preserve_raw_numbers: true,
+            ..ParseConfig::default()
+        };
+        let input = "[1.50,-0,2E+3,12345678901234567890123]";
+        let value = parse_with_config(input, &config).unwrap();
+
+        assert_eq!(to_string(&value).unwrap(), input);
+        assert_eq!(value[0], 1.5);
+        assert_eq!(value[2], 2000);
+    }
+
+    #[test]
+    fn random_numbers_round_trip() {
+        // xorshift64, fixed seed so failures are reproducible
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut checked = 0;
+        while checked < 100_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let num = f64::from_bits(state);
+            if !num.is_finite() {
+                continue;
+            }
+            let output = to_string(&Value::num(num)).unwrap();
+            assert_eq!(parse(&output), Ok(Value::num(num)), "{output}");
+            checked += 1;
+        }
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        let value = Value::String("a\"b\\c\nd\u{1}".to_string());
+        assert_eq!(to_string(&value).unwrap(), r#""a\"b\\c\nd\u0001""#);
+    }
+
+    #[test]
+    fn non_ascii_kept_by_default() {
+        let value = Value::String("héllo 💩".to_string());
+        assert_eq!(to_string(&value).unwrap(), "\"héllo 💩\"");
+    }
+
+    #[test]
+    fn escape_non_ascii_bmp_and_surrogate_pairs() {
+        let options = SerializeOptions {
+            escape_non_ascii: true,
+            ..SerializeOptions::default()
+        };
+        let value = Value::String("héllo 💩".to_string());
+        assert_eq!(
+            to_string_with_options(&value, &options).unwrap(),
+            r#""h\u00e9llo \ud83d\udca9""#
+        );
+    }
+
+    #[test]
+    fn escape_non_ascii_applies_to_keys_in_pretty_output() {
+        let options = SerializeOptions {
+            escape_non_ascii: true,
+            ..SerializeOptions::pretty()
+        };
+        let value = Value::Object(HashMap::from([(
+            "ключ".to_string(),
+            Value::String("é".to_string()),
+        )]));
+        assert_eq!(
+            to_string_with_options(&value, &options).unwrap(),
+            "{\n  \"\\u043a\\u043b\\u044e\\u0447\": \"\\u00e9\"\n}"
+        );
+    }
+
+    #[test]
+    fn escape_html_hides_closing_script_tag() {
+        let options = SerializeOptions {
+            escape_html: true,
+            ..SerializeOptions::default()
+        };
+        let value = Value::Object(HashMap::from([(
+            "<k>".to_string(),
+            Value::String("</script>&".to_string()),
+        )]));
+        let output = to_string_with_options(&value, &options).unwrap();
+
+        assert_eq!(output, r#"{"\u003ck\u003e":"\u003c/script\u003e\u0026"}"#);
+        assert!(!output.contains("</script>"));
+    }
+
+    #[test]
+    fn escape_html_output_reparses() {
+        let options = SerializeOptions {
+            escape_html: true,
+            ..SerializeOptions::default()
+        };
+        let value = Value::Array(vec![Value::String("</script> & \"x\"".to_string())]);
+        let output = to_string_with_options(&value, &options).unwrap();
+
+        assert_eq!(parse(&output).unwrap(), value);
+    }
+
+    #[test]
+    fn escape_html_keys_reparse() {
+        let options = SerializeOptions {
+            escape_html: true,
+            ..SerializeOptions::default()
+        };
+        let value = json!({"<k>": 1, "a&b": {"</script>": true}});
+        let output = to_string_with_options(&value, &options).unwrap();
+
+        assert!(!output.contains('<'));
+        assert_eq!(parse(&output).unwrap(), value);
+    }
+
+    #[test]
+    fn escape_html_composes_with_escape_non_ascii() {
+        let options = SerializeOptions {
+            escape_html: true,
+            escape_non_ascii: true,
+            ..SerializeOptions::default()
+        };
+        let value = Value::String("<é>\\u003c".to_string());
+
+        assert_eq!(
+            to_string_with_options(&value, &options).unwrap(),
+            r#""\u003c\u00e9\u003e\\u003c""#
+        );
+    }
+
+    #[test]
+    fn escaped_bmp_output_reparses() {
+        let options = SerializeOptions {
+            escape_non_ascii: true,
+            ..SerializeOptions::default()
+        };
+        let value = Value::Array(vec![Value::String("olá_こんにちは".to_string())]);
+        let output = to_string_with_options(&value, &options).unwrap();
+        assert!(output.is_ascii());
+        assert_eq!(parse(&output).unwrap(), value);
+    }
+
+    #[test]
+    fn escaped_keys_reparse() {
+        for indent in [None, Some(2)] {
+            let options = SerializeOptions {
+                escape_non_ascii: true,
+                indent,
+                ..SerializeOptions::default()
+            };
+            let value = json!({"é": "é", "ключ": {"💩": ["日本"]}});
+            let output = to_string_with_options(&value, &options).unwrap();
+
+            assert!(output.is_ascii());
+            assert_eq!(parse(&output).unwrap(), value);
+        }
+    }
+
+    fn with_policy(non_finite: NonFiniteNumbers, indent: Option<usize>) -> SerializeOptions {
+        SerializeOptions {
+            non_finite,
+            indent,
+            ..SerializeOptions::default()
+        }
+    }
+
+    fn deeply_nested(num: f64) -> Value {
+        Value::Object(HashMap::from([(
+            "a".to_string(),
+            Value::Array(vec![
+                Value::Boolean(true),
+                Value::Object(HashMap::from([("b".to_string(), Value::num(num))])),
+            ]),
+        )]))
+    }
+
+    const NON_FINITE: [f64; 3] = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+
+    #[test]
+    fn non_finite_errors_by_default() {
+        for num in NON_FINITE {
+            for indent in [None, Some(2)] {
+                let options = with_policy(NonFiniteNumbers::Error, indent);
+                let result = to_string_with_options(&deeply_nested(num), &options);
+                assert!(
+                    matches!(result, Err(SerializeError::NonFiniteNumber(n)) if n.to_bits() == num.to_bits())
+                );
+            }
+
+            let result = to_string(&Value::num(num));
+            assert!(matches!(result, Err(SerializeError::NonFiniteNumber(_))));
+        }
+    }
+
+    #[test]
+    fn non_finite_errors_mid_stream_in_writer() {
+        for num in NON_FINITE {
+            let mut buf = Vec::new();
+            let options = with_policy(NonFiniteNumbers::Error, None);
+            let result = to_writer_with_options(&mut buf, &deeply_nested(num), &options);
+
+            assert!(matches!(result, Err(SerializeError::NonFiniteNumber(_))));
+            assert_eq!(buf, br#"{"a":[true,{"b":"#);
+        }
+    }
+
+    #[test]
+    fn non_finite_as_null() {
+        for num in NON_FINITE {
+            let options = with_policy(NonFiniteNumbers::Null, None);
+            assert_eq!(
+                to_string_with_options(&deeply_nested(num), &options).unwrap(),
+                r#"{"a":[true,{"b":null}]}"#
+            );
+
+            let options = with_policy(NonFiniteNumbers::Null, Some(2));
+            assert_eq!(
+                to_string_with_options(&deeply_nested(num), &options).unwrap(),
+                "{\n  \"a\": [\n    true,\n    {\n      \"b\": null\n    }\n  ]\n}"
+            );
+
+            let mut buf = Vec::new();
+            to_writer_with_options(&mut buf, &Value::num(num), &options).unwrap();
+            assert_eq!(buf, b"null");
+        }
+    }
+
+    #[test]
+    fn non_finite_as_string() {
+        let expected = ["\"NaN\"", "\"Infinity\"", "\"-Infinity\""];
+        for (num, expected) in NON_FINITE.into_iter().zip(expected) {
+            let options = with_policy(NonFiniteNumbers::AsString, None);
+            assert_eq!(
+                to_string_with_options(&deeply_nested(num), &options).unwrap(),
+                format!(r#"{{"a":[true,{{"b":{expected}}}]}}"#)
+            );
+
+            let options = with_policy(NonFiniteNumbers::AsString, Some(2));
+            assert_eq!(
+                to_string_with_options(&Value::Array(vec![Value::num(num)]), &options).unwrap(),
+                format!("[\n  {expected}\n]")
+            );
+
+            let mut buf = Vec::new();
+            to_writer_with_options(&mut buf, &Value::num(num), &options).unwrap();
+            assert_eq!(String::from_utf8(buf).unwrap(), expected);
+        }
+    }
+
+    /// Writes the name of every hook it receives instead of any JSON
+    #[derive(Default)]
+    struct Recorder;
+
+    impl Formatter for Recorder {
+        fn write_null<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("null ")
+        }
+        fn write_number<W: ?Sized + Write>(&mut self, w: &mut W, value: f64) -> fmt::Result {
+            write!(w, "number({value}) ")
+        }
+        fn write_number_str<W: ?Sized + Write>(&mut self, w: &mut W, value: &str) -> fmt::Result {
+            write!(w, "number_str({value}) ")
+        }
+        fn begin_string<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("begin_string ")
+        }
+        fn end_string<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("end_string ")
+        }
+        fn write_string_fragment<W: ?Sized + Write>(&mut self, w: &mut W, s: &str) -> fmt::Result {
+            write!(w, "fragment({s}) ")
+        }
+        fn write_char_escape<W: ?Sized + Write>(
+            &mut self,
+            w: &mut W,
+            e: CharEscape,
+        ) -> fmt::Result {
+            write!(w, "escape({e:?}) ")
+        }
+        fn begin_array<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("begin_array ")
+        }
+        fn end_array<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("end_array ")
+        }
+        fn begin_array_value<W: ?Sized + Write>(&mut self, w: &mut W, first: bool) -> fmt::Result {
+            write!(w, "begin_array_value({first}) ")
+        }
+        fn end_array_value<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("end_array_value ")
+        }
+        fn begin_object<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("begin_object ")
+        }
+        fn end_object<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("end_object ")
+        }
+        fn begin_object_key<W: ?Sized + Write>(&mut self, w: &mut W, first: bool) -> fmt::Result {
+            write!(w, "begin_object_key({first}) ")
+        }
+        fn end_object_key<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("end_object_key ")
+        }
+        fn begin_object_value<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("begin_object_value ")
+        }
+        fn end_object_value<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str("end_object_value ")
+        }
+    }
+
+    #[test]
+    fn formatter_hooks_called_in_order() {
+        let value = parse(r#"{"k": [1, "a\nb"], "e": []}"#).unwrap();
+        let output =
+            to_string_with_formatter(&value, Recorder, &SerializeOptions::default()).unwrap();
+
+        assert_eq!(
+            output.split_whitespace().collect::<Vec<_>>(),
+            [
+                "begin_object",
+                "begin_object_key(true)",
+                "begin_string",
+                "fragment(e)",
+                "end_string",
+                "end_object_key",
+                "begin_object_value",
+                "begin_array",
+                "end_array",
+                "end_object_value",
+                "begin_object_key(false)",
+                "begin_string",
+                "fragment(k)",
+                "end_string",
+                "end_object_key",
+                "begin_object_value",
+                "begin_array",
+                "begin_array_value(true)",
+                "number_str(1)",
+                "end_array_value",
+                "begin_array_value(false)",
+                "begin_string",
+                "fragment(a)",
+                "escape(LineFeed)",
+                "fragment(b)",
+                "end_string",
+                "end_array_value",
+                "end_array",
+                "end_object_value",
+                "end_object",
+            ]
+        );
+    }
+
+    /// Compact output with a space after every comma and colon
+    struct Spaced;
+
+    impl Formatter for Spaced {
+        fn begin_array_value<W: ?Sized + Write>(&mut self, w: &mut W, first: bool) -> fmt::Result {
+            w.write_str(if first { "" } else { ", " })
+        }
+        fn begin_object_key<W: ?Sized + Write>(&mut self, w: &mut W, first: bool) -> fmt::Result {
+            w.write_str(if first { "" } else { ", " })
+        }
+        fn begin_object_value<W: ?Sized + Write>(&mut self, w: &mut W) -> fmt::Result {
+            w.write_str(": ")
+        }
+    }
+
+    #[test]
+    fn custom_formatter_changes_layout_only() {
+        let options = SerializeOptions {
+            escape_html: true,
+            ..SerializeOptions::default()
+        };
+        let value = parse(r#"{"b": [1, null], "a": "<x>"}"#).unwrap();
+
+        assert_eq!(
+            to_string_with_formatter(&value, Spaced, &options).unwrap(),
+            r#"{"a": "\u003cx\u003e", "b": [1, null]}"#
+        );
+    }
+
+    #[test]
+    fn pretty_formatter_through_writer() {
+        let mut output = Vec::new();
+        to_writer_with_formatter(
+            &mut output,
+            &nested(),
+            PrettyFormatter::with_indent(4),
+            &SerializeOptions::default(),
+        )
+        .unwrap();
+
+        let options = SerializeOptions {
+            indent: Some(4),
+            ..SerializeOptions::default()
+        };
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            to_string_with_options(&nested(), &options).unwrap()
+        );
+    }
+
+    #[test]
+    fn escape_string_has_no_quotes() {
+        assert_eq!(escape_string("say \"hi\"\n"), r#"say \"hi\"\n"#);
+        assert_eq!(escape_string("plain"), "plain");
+    }
+
+    #[test]
+    fn escape_and_unescape_round_trip() {
+        let input: String = [
+            '"', '\\', '/', '\u{8}', '\u{c}', '\n', '\r', '\t', '\u{0}', '\u{1f}',
+        ]
+        .into_iter()
+        .chain("é日本\u{1f600}<>&".chars())
+        .collect();
+
+        let escaped = escape_string(&input);
+        assert_eq!(
+            escaped,
+            "\\\"\\\\/\\b\\f\\n\\r\\t\\u0000\\u001fé日本\u{1f600}<>&"
+        );
+        assert_eq!(crate::unescape_string(&escaped).unwrap(), input);
+    }
+
+    fn with_inline_limit(limit: usize) -> SerializeOptions {
+        SerializeOptions {
+            inline_limit: limit,
+            ..SerializeOptions::pretty()
+        }
+    }
+
+    #[test]
+    fn inline_limit_keeps_short_containers_on_one_line() {
+        let value = parse(
+            r#"{
+              "point": [1, 2, 3],
+              "long": [100000, 200000, 300000, 400000],
+              "nested": {"inner": {"k": 1}, "list": [[1], []]}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            to_string_with_options(&value, &with_inline_limit(20)).unwrap(),
+            r#"{
+  "long": [
+    100000,
+    200000,
+    300000,
+    400000
+  ],
+  "nested": {
+    "inner": {"k": 1},
+    "list": [[1], []]
+  },
+  "point": [1, 2, 3]
+}"#
+        );
+    }
+
+    #[test]
+    fn inline_limit_boundary() {
+        // `{"inner": {"k": 1}}` is exactly 19 characters
+        let value = parse(r#"[{"inner": {"k": 1}}]"#).unwrap();
+
+        assert_eq!(
+            to_string_with_options(&value, &with_inline_limit(19)).unwrap(),
+            "[\n  {\"inner\": {\"k\": 1}}\n]"
+        );
+        assert_eq!(
+            to_string_with_options(&value, &with_inline_limit(18)).unwrap(),
+            "[\n  {\n    \"inner\": {\"k\": 1}\n  }\n]"
+        );
+    }
+
+    #[test]
+    fn inline_limit_counts_escapes() {
+        // rendered as `["\u003c"]`, 10 characters
+        let value = parse(r#"["<"]"#).unwrap();
+        let options = SerializeOptions {
+            escape_html: true,
+            ..with_inline_limit(9)
+        };
+
+        assert_eq!(
+            to_string_with_options(&value, &options).unwrap(),
+            "[\n  \"\\u003c\"\n]"
+        );
+    }
+
+    #[test]
+    fn inline_limit_ignored_for_compact_output() {
+        let options = SerializeOptions {
+            inline_limit: 80,
+            ..SerializeOptions::default()
+        };
+
+        assert_eq!(
+            to_string_with_options(&nested(), &options).unwrap(),
+            to_string(&nested()).unwrap()
+        );
+    }
+
+    #[test]
+    fn display_matches_to_string() {
+        let documents = [
+            r#"{"b": [1, null], "a": "x", "c": {}}"#,
+            r#"[[], {}, [[1, [2]], {"k": {"j": [true, "s\n"]}}], null]"#,
+            r#""plain""#,
+            "-0.5e-7",
+            "[]",
+        ];
+        for document in documents {
+            let value = parse(document).unwrap();
+
+            assert_eq!(format!("{value}"), to_string(&value).unwrap());
+            assert_eq!(format!("{value:#}"), to_string_pretty(&value).unwrap());
+        }
+    }
+
+    #[test]
+    fn display_writes_non_finite_as_null() {
+        let value = Value::Array(vec![Value::num(f64::NAN)]);
+
+        assert_eq!(format!("{value}"), "[null]");
+    }
+
+    #[test]
+    fn display_propagates_writer_errors() {
+        /// Accepts a few bytes, then fails
+        struct Short(usize);
+
+        impl Write for Short {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 = self.0.checked_sub(s.len()).ok_or(fmt::Error)?;
+                Ok(())
+            }
+        }
+
+        let value = parse(r#"{"key": "a long enough value"}"#).unwrap();
+        assert_eq!(write!(Short(5), "{value}"), Err(fmt::Error));
+        assert_eq!(write!(Short(100), "{value:#}"), Ok(()));
+    }
+
+    #[test]
+    fn serialized_len_matches_to_string() {
+        let documents = [
+            "null",
+            "-0.000001",
+            r#""esc\"aped\n\u0001 é 日本 😀""#,
+            "[]",
+            r#"{"b": [1, null, true, 1e300], "a": "x", "c": {"nested": [[], {}]}}"#,
+        ];
+        for document in documents {
+            let value = parse(document).unwrap();
+
+            assert_eq!(
+                value.serialized_len(),
+                to_string(&value).unwrap().len(),
+                "{document}"
+            );
+        }
+        assert_eq!(
+            nested().serialized_len(),
+            to_string(&nested()).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn serialized_len_counts_non_finite_as_null() {
+        assert_eq!(Value::Array(vec![Value::num(f64::NAN)]).serialized_len(), 6);
+    }
+
+    #[test]
+    fn integers_are_written_in_full() {
+        for int in [0, 7, -7, 10, -100, i64::MAX, i64::MIN] {
+            let mut digits = [0; 20];
+            assert_eq!(format_integer(int, &mut digits), int.to_string());
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt::{self, Write};
+
+use super::{CharEscape, Formatter, PrettyFormatter, SerializeError, SerializeOptions, Serializer};
+use crate::Value;
+
+const RESET: &str = "\x1b[0m";
+
+/// ANSI escape sequences used by `ColoredFormatter`, an empty sequence
+/// leaves that kind of token uncolored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Object keys, including their quotes
+    pub key: &'static str,
+
+    /// String values, including their quotes
+    pub string: &'static str,
+
+    pub number: &'static str,
+
+    /// `true`, `false` and `null`
+    pub literal: &'static str,
+
+    /// Brackets, braces, commas and colons
+    pub punctuation: &'static str,
+}
+
+impl Theme {
+    /// A theme that writes no escape sequences at all
+    pub fn plain() -> Self {
+        Self {
+            key: "",
+            string: "",
+            number: "",
+            literal: "",
+            punctuation: "",
+        }
+    }
+}
+
+impl Default for Theme {
+    /// Colors close to jq's defaults
+    fn default() -> Self {
+        Self {
+            key: "\x1b[34;1m",
+            string: "\x1b[0;32m",
+            number: "\x1b[0;39m",
+            literal: "\x1b[1;30m",
+            punctuation: "\x1b[1;39m",
+        }
+    }
+}
+
+/// Wraps another formatter, surrounding each token it writes with the
+/// matching color from a `Theme`
+///
+/// Colors are only ever placed around whole tokens, never inside a
+/// string, so stripping them leaves the JSON the inner formatter writes.
+#[derive(Debug, Clone)]
+pub struct ColoredFormatter<F> {
+    inner: F,
+    theme: Theme,
+    in_key: bool,
+}
+
+impl<F: Formatter> ColoredFormatter<F> {
+    pub fn new(inner: F, theme: Theme) -> Self {
+        Self {
+            inner,
+            theme,
+            in_key: false,
+        }
+    }
+
+    /// Writes whatever `write` produces wrapped in `color`, or nothing when
+    /// it produces nothing
+    fn paint<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        color: &str,
+        write: impl FnOnce(&mut F, &mut String) -> fmt::Result,
+    ) -> fmt::Result {
+        let mut token = String::new();
+        write(&mut self.inner, &mut token)?;
+        if token.is_empty() || color.is_empty() {
+            return writer.write_str(&token);
+        }
+        writer.write_str(color)?;
+        writer.write_str(&token)?;
+        writer.write_str(RESET)
+    }
+}
+
+impl<F: Formatter> Formatter for ColoredFormatter<F> {
+    fn write_null<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        let color = self.theme.literal;
+        self.paint(writer, color, |f, w| f.write_null(w))
+    }
+
+    fn write_bool<W: ?Sized + Write>(&mut self, writer: &mut W, value: bool) -> fmt::Result {
+        let color = self.theme.literal;
+        self.paint(writer, color, |f, w| f.write_bool(w, value))
+    }
+
+    fn write_number<W: ?Sized + Write>(&mut self, writer: &mut W, value: f64) -> fmt::Result {
+        let color = self.theme.number;
+        self.paint(writer, color, |f, w| f.write_number(w, value))
+    }
+
+    fn write_number_str<W: ?Sized + Write>(&mut self, writer: &mut W, value: &str) -> fmt::Result {
+        let color = self.theme.number;
+        self.paint(writer, color, |f, w| f.write_number_str(w, value))
+    }
+
+    fn begin_string<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        let color = if self.in_key {
+            self.theme.key
+        } else {
+            self.theme.string
+        };
+        writer.write_str(color)?;
+        self.inner.begin_string(writer)
+    }
+
+    fn end_string<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.inner.end_string(writer)?;
+        let color = if self.in_key {
+            self.theme.key
+        } else {
+            self.theme.string
+        };
+        if color.is_empty() {
+            Ok(())
+        } else {
+            writer.write_str(RESET)
+        }
+    }
+
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> fmt::Result {
+        self.inner.write_string_fragment(writer, fragment)
+    }
+
+    fn write_char_escape<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        escape: CharEscape,
+    ) -> fmt::Result {
+        self.inner.write_char_escape(writer, escape)
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        let color = self.theme.punctuation;
+        self.paint(writer, color, |f, w| f.begin_array(w))
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        let color = self.theme.punctuation;
+        self.paint(writer, color, |f, w| f.end_array(w))
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        let color = self.theme.punctuation;
+        self.paint(writer, color, |f, w| f.begin_array_value(w, first))
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.inner.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        let color = self.theme.punctuation;
+        self.paint(writer, color, |f, w| f.begin_object(w))
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        let color = self.theme.punctuation;
+        self.paint(writer, color, |f, w| f.end_object(w))
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        self.in_key = true;
+        let color = self.theme.punctuation;
+        self.paint(writer, color, |f, w| f.begin_object_key(w, first))
+    }
+
+    fn end_object_key<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.in_key = false;
+        self.inner.end_object_key(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        let color = self.theme.punctuation;
+        self.paint(writer, color, |f, w| f.begin_object_value(w))
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.inner.end_object_value(writer)
+    }
+}
+
+/// Serializes `value` as JSON indented by two spaces, colored with `theme`
+/// when `enabled` is set and identical to `to_string_pretty` otherwise
+pub fn to_string_colored(
+    value: &Value,
+    theme: &Theme,
+    enabled: bool,
+) -> Result<String, SerializeError> {
+    let theme = if enabled {
+        theme.clone()
+    } else {
+        Theme::plain()
+    };
+    let mut output = String::new();
+    let formatter = ColoredFormatter::new(PrettyFormatter::new(), theme);
+    Serializer::new(&mut output, formatter, &SerializeOptions::default()).write_value(value)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_string_colored, ColoredFormatter, Theme};
+    use crate::serialize::{to_string_pretty, to_string_with_formatter, CompactFormatter};
+    use crate::{parse, SerializeOptions};
+
+    /// Single-letter markers in place of escape sequences, so the output
+    /// is readable in assertions
+    fn marker_theme() -> Theme {
+        Theme {
+            key: "<K>",
+            string: "<S>",
+            number: "<N>",
+            literal: "<L>",
+            punctuation: "<P>",
+        }
+    }
+
+    #[test]
+    fn compact_colored_output() {
+        let value = parse(r#"{"a": [1, true, null], "b": "x\"y"}"#).unwrap();
+        let formatter = ColoredFormatter::new(CompactFormatter, marker_theme());
+        let output =
+            to_string_with_formatter(&value, formatter, &SerializeOptions::default()).unwrap();
+
+        let r = "\x1b[0m";
+        assert_eq!(
+            output,
+            format!(
+                "<P>{{{r}<K>\"a\"{r}<P>:{r}<P>[{r}<N>1{r}<P>,{r}<L>true{r}<P>,{r}<L>null{r}<P>]{r}\
+                 <P>,{r}<K>\"b\"{r}<P>:{r}<S>\"x\\\"y\"{r}<P>}}{r}"
+            )
+        );
+    }
+
+    #[test]
+    fn default_theme_pretty_output() {
+        let value = parse(r#"{"k": ["s", 2]}"#).unwrap();
+
+        assert_eq!(
+            to_string_colored(&value, &Theme::default(), true).unwrap(),
+            "\x1b[1;39m{\x1b[0m\x1b[1;39m\n  \x1b[0m\x1b[34;1m\"k\"\x1b[0m\x1b[1;39m: \x1b[0m\
+             \x1b[1;39m[\x1b[0m\x1b[1;39m\n    \x1b[0m\x1b[0;32m\"s\"\x1b[0m\
+             \x1b[1;39m,\n    \x1b[0m\x1b[0;39m2\x1b[0m\x1b[1;39m\n  ]\x1b[0m\
+             \x1b[1;39m\n}\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn disabled_matches_pretty_output() {
+        let value = parse(r#"{"a": [1, "two", {"b": null}], "c": false}"#).unwrap();
+
+        assert_eq!(
+            to_string_colored(&value, &Theme::default(), false).unwrap(),
+            to_string_pretty(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn stripping_colors_leaves_valid_json() {
+        let value = parse(r#"{"a": ["\u001b[31m\n", 1.5e300, {}]}"#).unwrap();
+        let colored = to_string_colored(&value, &Theme::default(), true).unwrap();
+
+        let mut stripped = String::new();
+        let mut rest = colored.as_str();
+        while let Some(start) = rest.find('\x1b') {
+            stripped.push_str(&rest[..start]);
+            rest = &rest[start + rest[start..].find('m').unwrap() + 1..];
+        }
+        stripped.push_str(rest);
+
+        assert_eq!(stripped, to_string_pretty(&value).unwrap());
+        assert_eq!(parse(&stripped).unwrap(), value);
+    }
+}
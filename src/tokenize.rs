@@ -1,46 +1,284 @@
-pub fn tokenize(input: String) -> Result<Vec<Token>, TokenizeError> {
-    let chars: Vec<char> = input.chars().collect();
+use std::fmt;
+
+use crate::NumberRepr;
+
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>, TokenizeError> {
+    tokenize_with(input, TokenizeOptions::default())
+}
+
+/// Extensions to strict JSON the tokenizer can be asked to accept
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenizeOptions {
+    /// Keep number literals as `NumberRepr::Raw` text
+    pub raw_numbers: bool,
+
+    /// Read the bare words `Infinity`, `-Infinity` and `NaN` as numbers
+    pub special_floats: bool,
+}
+
+/// Tokenizes `input` with the extensions in `options`
+pub fn tokenize_with(
+    input: &str,
+    options: TokenizeOptions,
+) -> Result<Vec<Token<'_>>, TokenizeError> {
+    let mut tokens = Vec::new();
+    tokenize_into(input, options, &mut tokens)?;
+    Ok(tokens)
+}
+
+/// Tokenizes `input` onto the end of `tokens`, so a caller can reuse the
+/// allocation
+pub(crate) fn tokenize_into<'a>(
+    input: &'a str,
+    options: TokenizeOptions,
+    tokens: &mut Vec<Token<'a>>,
+) -> Result<(), TokenizeError> {
     let mut index = 0;
 
+    while index < input.len() {
+        if input.as_bytes()[index].is_ascii_whitespace() {
+            index += 1;
+            continue;
+        }
+        let token = make_token(input, &mut index, options)?;
+        tokens.push(token);
+        index += 1;
+    }
+
+    Ok(())
+}
+
+/// Tokenizes `input` like `tokenize_with`, also returning the byte offset
+/// each token starts at, or the offset of the token that failed
+pub(crate) fn tokenize_spanned(
+    input: &str,
+    options: TokenizeOptions,
+) -> Result<(Vec<Token<'_>>, Vec<usize>), (TokenizeError, usize)> {
     let mut tokens = Vec::new();
-    while index < chars.len() {
-        let token = make_token(&chars, &mut index)?;
+    let mut starts = Vec::new();
+    let mut index = 0;
+
+    while index < input.len() {
+        if input.as_bytes()[index].is_ascii_whitespace() {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        let token = make_token(input, &mut index, options).map_err(|e| (e, start))?;
         tokens.push(token);
+        starts.push(start);
         index += 1;
     }
 
-    Ok(tokens)
+    Ok((tokens, starts))
 }
 
-fn make_token(chars: &Vec<char>, index: &mut usize) -> Result<Token, TokenizeError> {
-    let mut ch = chars[*index];
+/// Tokenizes all of `input`, standing in a `Token::Null` for anything
+/// that fails to tokenize and resuming at the next whitespace or
+/// punctuation, and returns every error along with the tokens
+pub fn tokenize_recovering(
+    input: &str,
+    options: TokenizeOptions,
+) -> (Vec<Token<'_>>, Vec<TokenizeError>) {
+    let bytes = input.as_bytes();
+    let mut index = 0;
 
-    while ch.is_ascii_whitespace() {
-        *index += 1;
-        if *index > chars.len() {
-            return Err(TokenizeError::UnexpectedEof);
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    while index < input.len() {
+        if bytes[index].is_ascii_whitespace() {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        match make_token(input, &mut index, options) {
+            Ok(token) => {
+                tokens.push(token);
+                index += 1;
+            }
+            Err(e) => {
+                errors.push(e);
+                tokens.push(Token::Null);
+                index = match e {
+                    TokenizeError::UnclosedQuotes | TokenizeError::TruncatedEscapeSequence => {
+                        input.len()
+                    }
+                    // the tokenizer stopped on the string's closing quote
+                    TokenizeError::InvalidEscapeChar(_) => index + 1,
+                    _ => resume_point(bytes, start),
+                };
+            }
+        }
+    }
+    (tokens, errors)
+}
+
+/// The next whitespace or punctuation byte after the token at `start`
+fn resume_point(bytes: &[u8], start: usize) -> usize {
+    bytes[start + 1..]
+        .iter()
+        .position(|b| b.is_ascii_whitespace() || b"[]{},:\"".contains(b))
+        .map_or(bytes.len(), |offset| start + 1 + offset)
+}
+
+/// Tokenizes input that arrives in chunks, e.g. from a socket or a REPL
+///
+/// A token split across chunks, such as a string missing its closing
+/// quote or a number that may have more digits coming, is held back until
+/// a later chunk completes it or `finish` is called. Tokens are returned
+/// owned, strings always as `Token::String`.
+#[derive(Debug)]
+pub struct Tokenizer {
+    /// Input received but not tokenized yet, starting at a token boundary
+    pending: String,
+
+    /// 1-based line and column of the start of `pending`
+    line: usize,
+    column: usize,
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Self {
+            pending: String::new(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Adds `chunk` to the input and returns every token it completed
+    pub fn feed(&mut self, chunk: &str) -> Result<Vec<Token<'static>>, TokenizeError> {
+        self.pending.push_str(chunk);
+        self.drain(false)
+    }
+
+    /// Ends the input and returns the tokens that were still held back
+    pub fn finish(mut self) -> Result<Vec<Token<'static>>, TokenizeError> {
+        self.drain(true)
+    }
+
+    /// 1-based line and column of the first character not yet turned into
+    /// a token
+    pub fn position(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    fn drain(&mut self, at_end: bool) -> Result<Vec<Token<'static>>, TokenizeError> {
+        let input = self.pending.as_str();
+        let mut index = 0;
+
+        let mut tokens = Vec::new();
+        while index < input.len() {
+            if input.as_bytes()[index].is_ascii_whitespace() {
+                index += 1;
+                continue;
+            }
+            if !at_end && is_incomplete(&input[index..]) {
+                break;
+            }
+            let token = make_token(input, &mut index, TokenizeOptions::default())?;
+            tokens.push(token.into_owned());
+            index += 1;
+        }
+
+        for ch in input[..index].chars() {
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.pending.drain(..index);
+        Ok(tokens)
+    }
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether the token at the start of `rest` could still grow with more
+/// input
+fn is_incomplete(rest: &str) -> bool {
+    let bytes = rest.as_bytes();
+    match bytes[0] {
+        b'"' => {
+            let mut is_escaping = false;
+            for byte in &bytes[1..] {
+                match byte {
+                    b'"' if !is_escaping => return false,
+                    b'\\' => is_escaping = !is_escaping,
+                    _ => is_escaping = false,
+                }
+            }
+            true
+        }
+        c if c.is_ascii_digit() || c == b'-' => bytes
+            .iter()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E')),
+        b'n' | b't' => bytes.len() < 4,
+        b'f' => bytes.len() < 5,
+        _ => false,
+    }
+}
+
+pub(crate) fn make_token<'a>(
+    input: &'a str,
+    index: &mut usize,
+    options: TokenizeOptions,
+) -> Result<Token<'a>, TokenizeError> {
+    let bytes = input.as_bytes();
+
+    if options.special_floats {
+        if let Some(token) = special_float(input, index, options.raw_numbers) {
+            return Ok(token);
+        }
+    }
+
+    let token = match bytes[*index] {
+        b'[' => Token::LeftBracket,
+        b']' => Token::RightBracket,
+        b'{' => Token::LeftBrace,
+        b'}' => Token::RightBrace,
+        b',' => Token::Comma,
+        b':' => Token::Colon,
+        b'n' => tokenize_null(bytes, index)?,
+        b't' => tokenize_true(bytes, index)?,
+        b'f' => tokenize_false(bytes, index)?,
+        c if c.is_ascii_digit() || c == b'-' => tokenize_float(bytes, index, options.raw_numbers)?,
+        b'"' => tokenize_string(input, index)?,
+        _ => {
+            // `index` always sits on a char boundary here, since every token
+            // before it ended on an ASCII byte or a closing quote
+            let c = input[*index..].chars().next().unwrap_or_default();
+            return Err(TokenizeError::CharNotRecognized(c));
         }
-        ch = chars[*index];
-    }
-
-    let token = match ch {
-        '[' => Token::LeftBracket,
-        ']' => Token::RightBracket,
-        '{' => Token::LeftBrace,
-        '}' => Token::RightBrace,
-        ',' => Token::Comma,
-        ':' => Token::Colon,
-        'n' => tokenize_null(chars, index)?,
-        't' => tokenize_true(chars, index)?,
-        'f' => tokenize_false(chars, index)?,
-        c if c.is_ascii_digit() => tokenize_float(chars, index)?,
-        '"' => tokenize_string(chars, index)?,
-        c => return Err(TokenizeError::CharNotRecognized(c)),
-        _ => todo!("implement other tokens"),
     };
     Ok(token)
 }
 
+/// `Infinity`, `-Infinity` or `NaN` at `index`, leaving `index` on its
+/// last byte like the other token readers
+fn special_float<'a>(input: &str, index: &mut usize, raw_numbers: bool) -> Option<Token<'a>> {
+    let rest = &input[*index..];
+    let (literal, value) = [
+        ("Infinity", f64::INFINITY),
+        ("-Infinity", f64::NEG_INFINITY),
+        ("NaN", f64::NAN),
+    ]
+    .into_iter()
+    .find(|(literal, _)| rest.starts_with(literal))?;
+    *index += literal.len() - 1;
+    Some(Token::Number(if raw_numbers {
+        NumberRepr::Raw(literal.to_string())
+    } else {
+        NumberRepr::Float(value)
+    }))
+}
+
 /// One of the possible errors that could occur while tokenizing the input string
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TokenizeError {
@@ -50,15 +288,36 @@ pub enum TokenizeError {
     ParseNumberError,
     /// String was never completed
     UnclosedQuotes,
+    /// The input ended right after the `\` starting an escape sequence
+    TruncatedEscapeSequence,
+    /// A `\\` followed by a character that doesn't start a JSON escape
+    /// sequence
+    InvalidEscapeChar(char),
     /// The input ended early
     UnexpectedEof,
     /// Character is not part of a json token
     CharNotRecognized(char),
 }
 
-fn tokenize_null(chars: &Vec<char>, index: &mut usize) -> Result<Token, TokenizeError> {
-    for expected_char in "null".chars() {
-        if expected_char != chars[*index] {
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnfinishedLiteralValue => f.write_str("unfinished literal"),
+            Self::ParseNumberError => f.write_str("invalid number"),
+            Self::UnclosedQuotes => f.write_str("unclosed string"),
+            Self::TruncatedEscapeSequence => f.write_str("input ends inside an escape sequence"),
+            Self::InvalidEscapeChar(c) => write!(f, "invalid escape sequence \\{c}"),
+            Self::UnexpectedEof => f.write_str("unexpected end of input"),
+            Self::CharNotRecognized(c) => write!(f, "unexpected character {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for TokenizeError {}
+
+fn tokenize_null<'a>(bytes: &[u8], index: &mut usize) -> Result<Token<'a>, TokenizeError> {
+    for expected_byte in b"null" {
+        if bytes.get(*index) != Some(expected_byte) {
             return Err(TokenizeError::UnfinishedLiteralValue);
         }
         *index += 1;
@@ -67,9 +326,9 @@ fn tokenize_null(chars: &Vec<char>, index: &mut usize) -> Result<Token, Tokenize
     Ok(Token::Null)
 }
 
-fn tokenize_false(chars: &Vec<char>, index: &mut usize) -> Result<Token, TokenizeError> {
-    for expected_char in "false".chars() {
-        if expected_char != chars[*index] {
+fn tokenize_false<'a>(bytes: &[u8], index: &mut usize) -> Result<Token<'a>, TokenizeError> {
+    for expected_byte in b"false" {
+        if bytes.get(*index) != Some(expected_byte) {
             return Err(TokenizeError::UnfinishedLiteralValue);
         }
         *index += 1;
@@ -78,9 +337,9 @@ fn tokenize_false(chars: &Vec<char>, index: &mut usize) -> Result<Token, Tokeniz
     Ok(Token::False)
 }
 
-fn tokenize_true(chars: &Vec<char>, index: &mut usize) -> Result<Token, TokenizeError> {
-    for expected_char in "true".chars() {
-        if expected_char != chars[*index] {
+fn tokenize_true<'a>(bytes: &[u8], index: &mut usize) -> Result<Token<'a>, TokenizeError> {
+    for expected_byte in b"true" {
+        if bytes.get(*index) != Some(expected_byte) {
             return Err(TokenizeError::UnfinishedLiteralValue);
         }
         *index += 1;
@@ -89,47 +348,89 @@ fn tokenize_true(chars: &Vec<char>, index: &mut usize) -> Result<Token, Tokenize
     Ok(Token::True)
 }
 
-fn tokenize_float(chars: &Vec<char>, cur_idx: &mut usize) -> Result<Token, TokenizeError> {
+fn tokenize_float<'a>(
+    bytes: &[u8],
+    cur_idx: &mut usize,
+    raw_numbers: bool,
+) -> Result<Token<'a>, TokenizeError> {
     let mut unparsed_num = String::new();
     let mut has_decimal = false;
+    let mut has_exponent = false;
 
-    while *cur_idx < chars.len() {
-        let ch = chars[*cur_idx];
+    while *cur_idx < bytes.len() {
+        let ch = bytes[*cur_idx] as char;
         match ch {
             c if c.is_ascii_digit() => unparsed_num.push(ch),
-            c if c == '.' && !has_decimal => {
+            '-' if unparsed_num.is_empty() => unparsed_num.push(ch),
+            c if c == '.' && !has_decimal && !has_exponent => {
                 unparsed_num.push(ch);
                 has_decimal = true;
             }
+            'e' | 'E' if !has_exponent => {
+                unparsed_num.push(ch);
+                has_exponent = true;
+            }
+            // sign of the exponent
+            '+' | '-' if unparsed_num.ends_with(['e', 'E']) => unparsed_num.push(ch),
             _ => break,
         }
         *cur_idx += 1;
     }
     *cur_idx -= 1;
-    unparsed_num
-        .parse()
-        .map(|num| Token::Number(num))
-        .map_err(|_| TokenizeError::ParseNumberError)
+    let number = NumberRepr::from_literal(&unparsed_num).ok_or(TokenizeError::ParseNumberError)?;
+    if raw_numbers {
+        return Ok(Token::Number(NumberRepr::Raw(unparsed_num)));
+    }
+    Ok(Token::Number(number))
 }
 
-fn tokenize_string(chars: &Vec<char>, cur_idx: &mut usize) -> Result<Token, TokenizeError> {
-    let mut string = String::new();
+/// Strings without escape sequences are borrowed straight from the input,
+/// only those containing a `\` are copied into an owned `String`
+fn tokenize_string<'a>(input: &'a str, cur_idx: &mut usize) -> Result<Token<'a>, TokenizeError> {
+    let bytes = input.as_bytes();
+    let start = *cur_idx + 1;
+    let mut has_escapes = false;
     let mut is_escaping = false;
+    let mut invalid_escape = None;
 
+    // `"` and `\` never occur inside a multi-byte UTF-8 sequence, so it is
+    // safe to scan bytes and slice the input at the closing quote
     loop {
         *cur_idx += 1;
-        if *cur_idx >= chars.len() {
-            return Err(TokenizeError::UnclosedQuotes);
+        if *cur_idx >= bytes.len() {
+            return Err(match invalid_escape {
+                Some(c) => TokenizeError::InvalidEscapeChar(c),
+                None if is_escaping => TokenizeError::TruncatedEscapeSequence,
+                None => TokenizeError::UnclosedQuotes,
+            });
         }
-        let ch = chars[*cur_idx];
-        match ch {
-            '"' if !is_escaping => break,
-            '\\' => is_escaping = !is_escaping,
-            _ => is_escaping = false,
+        match bytes[*cur_idx] {
+            b'"' if !is_escaping => break,
+            b'\\' => {
+                is_escaping = !is_escaping;
+                has_escapes = true;
+            }
+            b'"' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' | b'u' => is_escaping = false,
+            _ if is_escaping => {
+                // keep going to the closing quote so error recovery can
+                // resume after the string
+                is_escaping = false;
+                invalid_escape
+                    .get_or_insert_with(|| input[*cur_idx..].chars().next().unwrap_or_default());
+            }
+            _ => {}
         }
-        string.push(ch);
     }
-    Ok(Token::String(string))
+    if let Some(c) = invalid_escape {
+        return Err(TokenizeError::InvalidEscapeChar(c));
+    }
+
+    let string = &input[start..*cur_idx];
+    if has_escapes {
+        Ok(Token::String(string.to_string()))
+    } else {
+        Ok(Token::StringRef(string))
+    }
 }
 
 ///
@@ -152,8 +453,8 @@ fn tokenize_string(chars: &Vec<char>, cur_idx: &mut usize) -> Result<Token, Toke
 /// RightBrace,
 ///
 
-#[derive(Debug, PartialEq)]
-pub enum Token {
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'a> {
     /// `{`
     LeftBrace,
 
@@ -182,22 +483,71 @@ pub enum Token {
     True,
 
     /// Any number literal
-    Number(f64),
+    Number(NumberRepr),
 
-    /// Key of the key/value pair or string value
+    /// Key of the key/value pair or string value containing escape sequences
     String(String),
+
+    /// Key of the key/value pair or string value without escape sequences,
+    /// borrowed from the input
+    StringRef(&'a str),
+}
+
+impl Token<'_> {
+    /// Copies a borrowed string so the token no longer refers to the input
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::LeftBrace => Token::LeftBrace,
+            Token::RightBrace => Token::RightBrace,
+            Token::LeftBracket => Token::LeftBracket,
+            Token::RightBracket => Token::RightBracket,
+            Token::Comma => Token::Comma,
+            Token::Colon => Token::Colon,
+            Token::Null => Token::Null,
+            Token::False => Token::False,
+            Token::True => Token::True,
+            Token::Number(num) => Token::Number(num),
+            Token::String(s) => Token::String(s),
+            Token::StringRef(s) => Token::String(s.to_string()),
+        }
+    }
+}
+
+/// The token as it appears in JSON text, strings in quotes with their
+/// escape sequences as written
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LeftBrace => f.write_str("{"),
+            Token::RightBrace => f.write_str("}"),
+            Token::LeftBracket => f.write_str("["),
+            Token::RightBracket => f.write_str("]"),
+            Token::Comma => f.write_str(","),
+            Token::Colon => f.write_str(":"),
+            Token::Null => f.write_str("null"),
+            Token::False => f.write_str("false"),
+            Token::True => f.write_str("true"),
+            Token::Number(num) => num.fmt(f),
+            Token::String(s) => write!(f, "\"{s}\""),
+            Token::StringRef(s) => write!(f, "\"{s}\""),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{tokenize, Token, TokenizeError};
+    use super::{
+        tokenize, tokenize_recovering, tokenize_with, Token, TokenizeError, TokenizeOptions,
+        Tokenizer,
+    };
+    use crate::NumberRepr;
 
     #[test]
     fn just_comma() {
         let input = String::from(",");
         let expected = [Token::Comma];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
@@ -213,7 +563,7 @@ mod tests {
             Token::Colon,
         ];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
@@ -222,7 +572,7 @@ mod tests {
         let input = String::from("null");
         let expected = [Token::Null];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
@@ -231,7 +581,7 @@ mod tests {
         let input = String::from("false");
         let expected = [Token::False];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
@@ -240,70 +590,171 @@ mod tests {
         let input = String::from("true");
         let expected = [Token::True];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn truncated_literals_are_errors() {
+        for input in ["nul", "tru", "fals"] {
+            assert_eq!(
+                tokenize(input),
+                Err(TokenizeError::UnfinishedLiteralValue),
+                "{input}"
+            );
+        }
+    }
+
     #[test]
     fn true_comma() {
         let input = String::from("true,");
         let expected = [Token::True, Token::Comma];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn just_integer() {
         let input = String::from("123");
-        let expected = [Token::Number(123.0)];
+        let expected = [Token::Number(NumberRepr::Integer(123))];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn integer_comma() {
         let input = String::from("123,");
-        let expected = [Token::Number(123.0), Token::Comma];
+        let expected = [Token::Number(NumberRepr::Integer(123)), Token::Comma];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn negative_integer() {
         let input = String::from("-123");
-        let expected = [Token::Number(-123.0)];
+        let expected = [Token::Number(NumberRepr::Integer(-123))];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn just_float() {
         let input = String::from("123.4");
-        let expected = [Token::Number(123.4)];
+        let expected = [Token::Number(NumberRepr::Float(123.4))];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn float_comma() {
         let input = String::from("123.4,");
-        let expected = [Token::Number(123.4), Token::Comma];
+        let expected = [Token::Number(NumberRepr::Float(123.4)), Token::Comma];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn exponent_numbers() {
+        let input = String::from("[1e5,1.5E-3,2e+2,-4e1]");
+        let expected = [
+            Token::LeftBracket,
+            Token::Number(NumberRepr::Float(1e5)),
+            Token::Comma,
+            Token::Number(NumberRepr::Float(1.5e-3)),
+            Token::Comma,
+            Token::Number(NumberRepr::Float(2e2)),
+            Token::Comma,
+            Token::Number(NumberRepr::Float(-4e1)),
+            Token::RightBracket,
+        ];
+
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn number_representations() {
+        let tokens = tokenize("[7, -0, 7.0, 7e0, 9223372036854775808]").unwrap();
+        let numbers: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Number(num) => Some(num),
+                _ => None,
+            })
+            .collect();
+
+        assert!(matches!(numbers[0], NumberRepr::Integer(7)));
+        assert!(matches!(numbers[1], NumberRepr::Integer(0)));
+        assert!(matches!(numbers[2], NumberRepr::Float(_)));
+        assert!(matches!(numbers[3], NumberRepr::Float(_)));
+        assert!(matches!(numbers[4], NumberRepr::Float(_)));
+    }
+
+    fn raw_numbers() -> TokenizeOptions {
+        TokenizeOptions {
+            raw_numbers: true,
+            ..TokenizeOptions::default()
+        }
+    }
+
+    #[test]
+    fn raw_numbers_keep_literal_text() {
+        let tokens = tokenize_with("[1.50, -2e+3]", raw_numbers()).unwrap();
+        assert!(matches!(&tokens[1], Token::Number(NumberRepr::Raw(s)) if s == "1.50"));
+        assert!(matches!(&tokens[3], Token::Number(NumberRepr::Raw(s)) if s == "-2e+3"));
+        assert_eq!(
+            tokenize_with("1e", raw_numbers()),
+            Err(TokenizeError::ParseNumberError)
+        );
+    }
+
+    #[test]
+    fn exponent_without_digits() {
+        let input = String::from("1e");
+        let expected = Err(TokenizeError::ParseNumberError);
+
+        let actual = tokenize(&input);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn surrounding_whitespace() {
+        let input = String::from(" \n\tnull  ");
+        let expected = [Token::Null];
+
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn only_whitespace() {
+        let input = String::from("   ");
+
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual, []);
+    }
+
     #[test]
     fn just_ken() {
         let input = String::from("\"ken\"");
-        let expected = [Token::String(String::from("ken"))];
+        let expected = [Token::StringRef("ken")];
+
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn non_ascii_string() {
+        let input = String::from("\"olá_こんにちは\"");
+        let expected = [Token::StringRef("olá_こんにちは")];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
@@ -312,16 +763,185 @@ mod tests {
         let input = String::from("\"unclosed");
         let expected = Err(TokenizeError::UnclosedQuotes);
 
-        let actual = tokenize(input);
+        let actual = tokenize(&input);
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn backslash_at_end_of_input() {
+        for input in ["\"\\", "\"abc\\", "[\"\\\\\\"] {
+            assert_eq!(
+                tokenize(input),
+                Err(TokenizeError::TruncatedEscapeSequence),
+                "{input}"
+            );
+        }
+        // an escaped backslash doesn't leave an escape open
+        assert_eq!(tokenize("\"\\\\"), Err(TokenizeError::UnclosedQuotes));
+    }
+
+    #[test]
+    fn invalid_escape_char() {
+        assert_eq!(
+            tokenize(r#""\z""#),
+            Err(TokenizeError::InvalidEscapeChar('z'))
+        );
+        assert_eq!(
+            tokenize(r#"["ok", "a\é"]"#),
+            Err(TokenizeError::InvalidEscapeChar('é'))
+        );
+        // the first bad escape is reported, even if the string never closes
+        assert_eq!(
+            tokenize(r#""\x \y"#),
+            Err(TokenizeError::InvalidEscapeChar('x'))
+        );
+        assert_eq!(
+            tokenize(r#""\n\"\\\/\b\f\r\t\u00e9""#),
+            Ok(vec![Token::String(r#"\n\"\\\/\b\f\r\t\u00e9"#.to_string())])
+        );
+        assert_eq!(
+            TokenizeError::InvalidEscapeChar('z').to_string(),
+            "invalid escape sequence \\z"
+        );
+    }
+
+    #[test]
+    fn recovers_after_invalid_escape() {
+        let (tokens, errors) = tokenize_recovering(r#"["\q\"x", 1]"#, TokenizeOptions::default());
+        assert_eq!(errors, [TokenizeError::InvalidEscapeChar('q')]);
+        assert_eq!(
+            tokens,
+            [
+                Token::LeftBracket,
+                Token::Null,
+                Token::Comma,
+                Token::Number(NumberRepr::Integer(1)),
+                Token::RightBracket,
+            ]
+        );
+    }
+
     #[test]
     fn escaped_quote() {
         let input = String::from(r#""The \" is Ok ""#);
         let expected = [Token::String(String::from(r#"The \" is Ok "#))];
 
-        let actual = tokenize(input).unwrap();
+        let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn tokenizer_across_two_chunks() {
+        let mut tokenizer = Tokenizer::new();
+
+        assert_eq!(
+            tokenizer.feed("[1,"),
+            Ok(vec![
+                Token::LeftBracket,
+                Token::Number(NumberRepr::Integer(1)),
+                Token::Comma
+            ])
+        );
+        assert_eq!(
+            tokenizer.feed("2]"),
+            Ok(vec![
+                Token::Number(NumberRepr::Integer(2)),
+                Token::RightBracket
+            ])
+        );
+        assert_eq!(tokenizer.finish(), Ok(vec![]));
+    }
+
+    #[test]
+    fn tokenizer_holds_back_split_tokens() {
+        let mut tokenizer = Tokenizer::new();
+
+        assert_eq!(tokenizer.feed(r#"{"ke"#), Ok(vec![Token::LeftBrace]));
+        assert_eq!(
+            tokenizer.feed(r#"y\"": tr"#),
+            Ok(vec![Token::String(r#"key\""#.to_string()), Token::Colon])
+        );
+        assert_eq!(
+            tokenizer.feed("ue, \"n\": 12"),
+            Ok(vec![
+                Token::True,
+                Token::Comma,
+                Token::String("n".to_string()),
+                Token::Colon,
+            ])
+        );
+        assert_eq!(tokenizer.feed("3e"), Ok(vec![]));
+        assert_eq!(
+            tokenizer.feed("2}"),
+            Ok(vec![
+                Token::Number(NumberRepr::Float(123e2)),
+                Token::RightBrace
+            ])
+        );
+    }
+
+    #[test]
+    fn tokenizer_finish_flushes_trailing_number() {
+        let mut tokenizer = Tokenizer::new();
+
+        assert_eq!(tokenizer.feed("-4.5"), Ok(vec![]));
+        assert_eq!(
+            tokenizer.finish(),
+            Ok(vec![Token::Number(NumberRepr::Float(-4.5))])
+        );
+    }
+
+    #[test]
+    fn tokenizer_finish_reports_unfinished_tokens() {
+        for input in ["[tr", "nul", "[1, fals"] {
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.feed(input).unwrap();
+            assert_eq!(
+                tokenizer.finish(),
+                Err(TokenizeError::UnfinishedLiteralValue),
+                "{input}"
+            );
+        }
+
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.feed("[\"abc").unwrap();
+        assert_eq!(tokenizer.finish(), Err(TokenizeError::UnclosedQuotes));
+    }
+
+    #[test]
+    fn tokenizer_tracks_position() {
+        let mut tokenizer = Tokenizer::new();
+
+        tokenizer.feed("[\n  1,\n  \"ab").unwrap();
+        assert_eq!(tokenizer.position(), (3, 3));
+        tokenizer.feed("c\"]").unwrap();
+        assert_eq!(tokenizer.position(), (3, 9));
+    }
+
+    #[test]
+    fn tokenize_recovering_skips_to_next_delimiter() {
+        let (tokens, errors) =
+            tokenize_recovering("[1, é@x, \"s\"] \"open", TokenizeOptions::default());
+
+        assert_eq!(
+            tokens,
+            [
+                Token::LeftBracket,
+                Token::Number(NumberRepr::Integer(1)),
+                Token::Comma,
+                Token::Null,
+                Token::Comma,
+                Token::StringRef("s"),
+                Token::RightBracket,
+                Token::Null,
+            ]
+        );
+        assert_eq!(
+            errors,
+            [
+                TokenizeError::CharNotRecognized('é'),
+                TokenizeError::UnclosedQuotes,
+            ]
+        );
+    }
 }
@@ -0,0 +1,319 @@
+//! Parsing that keeps the `//` and `/* */` comments of a document, for
+//! formatters that have to write them back out
+
+use std::collections::HashMap;
+
+use crate::parse::unescape_string;
+use crate::pointer::JsonPointer;
+use crate::tokenize::{make_token, Token, TokenizeError, TokenizeOptions};
+use crate::{ParseError, TokenParseError, Value};
+
+/// The comments around one node of a document, each as written,
+/// delimiters included
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trivia {
+    /// Comments before the node; for an object member, before its key
+    pub leading: Vec<String>,
+
+    /// Comments after the node, up to the `,` or closing bracket that
+    /// follows it. Comments inside an empty array or object end up here
+    /// too, ahead of those after it.
+    pub trailing: Vec<String>,
+}
+
+/// Parses `input` allowing comments, returning the value along with the
+/// comments attached to each node, keyed by the node's JSON Pointer
+///
+/// Nodes without comments have no entry.
+///
+/// ```
+/// use json_parser::{json, parse_with_trivia};
+///
+/// let (value, trivia) = parse_with_trivia("{\n  // the answer\n  \"a\": 42 /* exact */\n}").unwrap();
+/// assert_eq!(value, json!({"a": 42}));
+/// assert_eq!(trivia["/a"].leading, ["// the answer"]);
+/// assert_eq!(trivia["/a"].trailing, ["/* exact */"]);
+/// ```
+pub fn parse_with_trivia(input: &str) -> Result<(Value, HashMap<String, Trivia>), ParseError> {
+    let (tokens, end_comments) = tokenize_with_comments(input)?;
+    if tokens.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    let mut parser = Parser {
+        tokens: tokens.into_iter(),
+        peeked: None,
+        end_comments,
+        trivia: HashMap::new(),
+    };
+    let root = JsonPointer::new();
+    let value = parser.parse_value(&root, Vec::new())?;
+    if parser.peek().is_some() {
+        return Err(TokenParseError::TrailingTokens.into());
+    }
+    Ok((value, parser.trivia))
+}
+
+/// A token with the comments just before it
+type Commented<'a> = (Vec<String>, Token<'a>);
+
+/// Tokenizes `input`, gathering comments onto the token that follows
+/// them; comments after the last token are returned separately
+fn tokenize_with_comments(input: &str) -> Result<(Vec<Commented<'_>>, Vec<String>), TokenizeError> {
+    let bytes = input.as_bytes();
+    let mut index = 0;
+
+    let mut tokens = Vec::new();
+    let mut comments = Vec::new();
+    while index < input.len() {
+        match bytes[index] {
+            b if b.is_ascii_whitespace() => index += 1,
+            b'/' if bytes.get(index + 1) == Some(&b'/') => {
+                let end = input[index..]
+                    .find('\n')
+                    .map_or(input.len(), |offset| index + offset);
+                comments.push(input[index..end].trim_end().to_string());
+                index = end;
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                let end = input[index + 2..]
+                    .find("*/")
+                    .map(|offset| index + 2 + offset + 2)
+                    .ok_or(TokenizeError::UnexpectedEof)?;
+                comments.push(input[index..end].to_string());
+                index = end;
+            }
+            _ => {
+                let token = make_token(input, &mut index, TokenizeOptions::default())?;
+                tokens.push((std::mem::take(&mut comments), token));
+                index += 1;
+            }
+        }
+    }
+    Ok((tokens, comments))
+}
+
+struct Parser<'a> {
+    tokens: std::vec::IntoIter<Commented<'a>>,
+    peeked: Option<Commented<'a>>,
+    /// Comments after the last token
+    end_comments: Vec<String>,
+    trivia: HashMap<String, Trivia>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<&Token<'a>> {
+        if self.peeked.is_none() {
+            self.peeked = self.tokens.next();
+        }
+        self.peeked.as_ref().map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Commented<'a>> {
+        self.peeked.take().or_else(|| self.tokens.next())
+    }
+
+    fn entry(&mut self, pointer: &JsonPointer) -> &mut Trivia {
+        self.trivia.entry(pointer.to_string()).or_default()
+    }
+
+    fn add_leading(&mut self, pointer: &JsonPointer, comments: Vec<String>) {
+        if !comments.is_empty() {
+            self.entry(pointer).leading.extend(comments);
+        }
+    }
+
+    fn add_trailing(&mut self, pointer: &JsonPointer, comments: Vec<String>) {
+        if !comments.is_empty() {
+            self.entry(pointer).trailing.extend(comments);
+        }
+    }
+
+    /// Parses the value at `pointer`, `leading` being comments already
+    /// read before it, such as those before an object key
+    fn parse_value(
+        &mut self,
+        pointer: &JsonPointer,
+        mut leading: Vec<String>,
+    ) -> Result<Value, TokenParseError> {
+        let (comments, token) = self.next().ok_or(TokenParseError::UnexpectedEof)?;
+        leading.extend(comments);
+        self.add_leading(pointer, leading);
+
+        let value = match token {
+            Token::Null => Value::Null,
+            Token::False => Value::Boolean(false),
+            Token::True => Value::Boolean(true),
+            Token::Number(num) => Value::Number(num),
+            Token::String(s) => Value::String(unescape_string(&s)?),
+            Token::StringRef(s) => Value::String(s.to_string()),
+            Token::LeftBracket => self.parse_array(pointer)?,
+            Token::LeftBrace => self.parse_object(pointer)?,
+            token if pointer.is_empty() => {
+                return Err(TokenParseError::UnexpectedToken(token.into_owned()))
+            }
+            _ => return Err(TokenParseError::ExpectedValue),
+        };
+
+        // comments up to the next `,` or closing bracket trail the value
+        let trailing = match self.peek() {
+            Some(Token::Comma | Token::RightBracket | Token::RightBrace) => {
+                std::mem::take(&mut self.peeked.as_mut().unwrap().0)
+            }
+            Some(_) => Vec::new(),
+            None => std::mem::take(&mut self.end_comments),
+        };
+        self.add_trailing(pointer, trailing);
+        Ok(value)
+    }
+
+    fn parse_array(&mut self, pointer: &JsonPointer) -> Result<Value, TokenParseError> {
+        let mut array = Vec::new();
+        if self.peek() == Some(&Token::RightBracket) {
+            let (comments, _) = self.next().unwrap();
+            self.add_trailing(pointer, comments);
+            return Ok(Value::Array(array));
+        }
+        loop {
+            let element = pointer.clone().push_index(array.len());
+            array.push(self.parse_value(&element, Vec::new())?);
+            match self.next() {
+                Some((_, Token::Comma)) => {}
+                Some((_, Token::RightBracket)) => break,
+                None => return Err(TokenParseError::UnexpectedEof),
+                _ => return Err(TokenParseError::ExpectedComma),
+            }
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn parse_object(&mut self, pointer: &JsonPointer) -> Result<Value, TokenParseError> {
+        let mut object = HashMap::new();
+        if self.peek() == Some(&Token::RightBrace) {
+            let (comments, _) = self.next().unwrap();
+            self.add_trailing(pointer, comments);
+            return Ok(Value::Object(object));
+        }
+        loop {
+            let (mut leading, key) = match self.next() {
+                Some((comments, Token::String(s))) => (comments, unescape_string(&s)?),
+                Some((comments, Token::StringRef(s))) => (comments, s.to_string()),
+                None => return Err(TokenParseError::UnexpectedEof),
+                _ => return Err(TokenParseError::ExpectedProperty),
+            };
+            match self.next() {
+                Some((comments, Token::Colon)) => leading.extend(comments),
+                None => return Err(TokenParseError::UnexpectedEof),
+                _ => return Err(TokenParseError::ExpectedColon),
+            }
+            let member = pointer.clone().push_key(key.as_str());
+            let value = self.parse_value(&member, leading)?;
+            object.insert(key, value);
+            match self.next() {
+                Some((_, Token::Comma)) => {}
+                Some((_, Token::RightBrace)) => break,
+                None => return Err(TokenParseError::UnexpectedEof),
+                _ => return Err(TokenParseError::ExpectedComma),
+            }
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{parse_with_trivia, Trivia};
+    use crate::{json, parse, ParseError, TokenParseError, TokenizeError};
+
+    fn trivia(leading: &[&str], trailing: &[&str]) -> Trivia {
+        Trivia {
+            leading: leading.iter().map(|s| s.to_string()).collect(),
+            trailing: trailing.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn comment_before_key_attaches_to_member() {
+        let input = r#"{
+            "a": 1,
+            // about b
+            "b": 2
+        }"#;
+        let (value, comments) = parse_with_trivia(input).unwrap();
+        assert_eq!(value, json!({"a": 1, "b": 2}));
+        assert_eq!(
+            comments,
+            HashMap::from([("/b".to_string(), trivia(&["// about b"], &[]))])
+        );
+    }
+
+    #[test]
+    fn comments_throughout_a_document() {
+        let input = r#"/* header */ {
+            "list": [ // first
+                1, /* one */
+                2 /* two */ , // after the comma
+                [/* empty */]
+            ], "a/b": /* value */ {} // done
+        } // end"#;
+        let (value, comments) = parse_with_trivia(input).unwrap();
+        assert_eq!(value, json!({"list": [1, 2, []], "a/b": {}}));
+        assert_eq!(
+            comments,
+            HashMap::from([
+                ("".to_string(), trivia(&["/* header */"], &["// end"])),
+                ("/list/0".to_string(), trivia(&["// first"], &[])),
+                (
+                    "/list/1".to_string(),
+                    trivia(&["/* one */"], &["/* two */"])
+                ),
+                (
+                    "/list/2".to_string(),
+                    trivia(&["// after the comma"], &["/* empty */"])
+                ),
+                ("/a~1b".to_string(), trivia(&["/* value */"], &["// done"])),
+            ])
+        );
+    }
+
+    #[test]
+    fn same_value_as_parse_without_comments() {
+        let input = r#"{"a": [1, "x\ny", null], "b\u0062": {"c": true}}"#;
+        let (value, comments) = parse_with_trivia(input).unwrap();
+        assert_eq!(value["bb"], json!({"c": true}));
+        assert_eq!(Ok(value), parse(input));
+        assert!(comments.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_with_trivia("[1] /* open"),
+            Err(ParseError::TokenizeError(TokenizeError::UnexpectedEof))
+        );
+        assert_eq!(
+            parse_with_trivia("[1] / 2"),
+            Err(ParseError::TokenizeError(TokenizeError::CharNotRecognized(
+                '/'
+            )))
+        );
+        assert_eq!(parse_with_trivia("// nothing"), Err(ParseError::EmptyInput));
+        assert_eq!(
+            parse_with_trivia("1 // one\n 2"),
+            Err(ParseError::ParseError(TokenParseError::TrailingTokens))
+        );
+        assert_eq!(
+            parse_with_trivia("[1, // more\n"),
+            Err(ParseError::ParseError(TokenParseError::UnexpectedEof))
+        );
+        assert_eq!(
+            parse_with_trivia("[1 2]"),
+            Err(ParseError::ParseError(TokenParseError::ExpectedComma))
+        );
+        assert_eq!(
+            parse_with_trivia(r#"{"a": /* none */ }"#),
+            Err(ParseError::ParseError(TokenParseError::ExpectedValue))
+        );
+    }
+}